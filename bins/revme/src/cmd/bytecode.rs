@@ -1,5 +1,8 @@
 use revm::{
-    interpreter::opcode::eof_printer::print_eof_code,
+    interpreter::{
+        analysis::cfg::ControlFlowGraph,
+        opcode::{assembler::assemble, disassembler::disassemble},
+    },
     primitives::{Bytes, Eof},
 };
 use structopt::StructOpt;
@@ -7,15 +10,37 @@ use structopt::StructOpt;
 /// Statetest command
 #[derive(StructOpt, Debug)]
 pub struct Cmd {
-    /// EOF bytecode in hex format. It bytes start with 0xFE it will be interpreted as a EOF.
-    /// Otherwise, it will be interpreted as a EOF bytecode.
+    /// Bytecode in hex format. It bytes start with 0xEF it will be interpreted as a EOF.
+    /// Otherwise, it will be interpreted as a legacy bytecode and disassembled.
+    ///
+    /// With `--assemble` it is the path to the file with the legacy mnemonic text.
     #[structopt(required = true)]
     bytes: String,
+    /// Assemble the legacy mnemonic text from the file and print the bytecode in hex.
+    #[structopt(long)]
+    assemble: bool,
+    /// Print the control-flow graph of the legacy bytecode in the Graphviz DOT format.
+    #[structopt(long)]
+    cfg: bool,
 }
 
 impl Cmd {
     /// Run statetest command.
     pub fn run(&self) {
+        if self.assemble {
+            let source = match std::fs::read_to_string(&self.bytes) {
+                Ok(source) => source,
+                Err(e) => {
+                    eprintln!("Failed to read {}: {e}", self.bytes);
+                    return;
+                }
+            };
+            match assemble(&source) {
+                Ok(bytes) => println!("{bytes}"),
+                Err(e) => eprintln!("{e}"),
+            }
+            return;
+        }
         let trimmed = self.bytes.trim_start_matches("0x");
         let Ok(bytes) = hex::decode(trimmed) else {
             eprintln!("Invalid hex string");
@@ -26,6 +51,14 @@ impl Cmd {
             eprintln!("Empty hex string");
             return;
         }
+        if self.cfg {
+            if bytes[0] == 0xEF {
+                eprintln!("Control-flow graph is only supported for legacy bytecode");
+                return;
+            }
+            print!("{}", ControlFlowGraph::new(bytes).to_dot());
+            return;
+        }
         if bytes[0] == 0xEF {
             let Ok(eof) = Eof::decode(bytes) else {
                 eprintln!("Invalid EOF bytecode");
@@ -33,7 +66,7 @@ impl Cmd {
             };
             println!("{:#?}", eof);
         } else {
-            print_eof_code(&bytes)
+            print!("{}", disassemble(&bytes));
         }
     }
 }
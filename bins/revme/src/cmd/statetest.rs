@@ -1,4 +1,3 @@
-pub mod merkle_trie;
 pub mod models;
 mod runner;
 pub mod utils;
@@ -1,16 +1,15 @@
 use super::{
-    merkle_trie::{log_rlp_hash, state_merkle_trie_root},
     models::{SpecName, Test, TestSuite},
     utils::recover_address,
 };
 use indicatif::{ProgressBar, ProgressDrawTarget};
 use revm::{
-    db::EmptyDB,
+    db::{EmptyDB, StateRoot},
     inspector_handle_register,
     inspectors::TracerEip3155,
     primitives::{
-        calc_excess_blob_gas, keccak256, Bytecode, Bytes, EVMResultGeneric, Env, Eof,
-        ExecutionResult, SpecId, TransactTo, B256, EOF_MAGIC_BYTES, U256,
+        calc_excess_blob_gas, keccak256, logs_rlp_hash, Bytecode, Bytes, EVMResultGeneric, Env,
+        Eof, ExecutionResult, SpecId, TransactTo, B256, EOF_MAGIC_BYTES, U256,
     },
     Evm, State,
 };
@@ -134,8 +133,8 @@ fn check_evm_execution<EXT>(
     evm: &Evm<'_, EXT, &mut State<EmptyDB>>,
     print_json_outcome: bool,
 ) -> Result<(), TestError> {
-    let logs_root = log_rlp_hash(exec_result.as_ref().map(|r| r.logs()).unwrap_or_default());
-    let state_root = state_merkle_trie_root(evm.context.evm.db.cache.trie_account());
+    let logs_root = logs_rlp_hash(exec_result.as_ref().map(|r| r.logs()).unwrap_or_default());
+    let state_root = StateRoot::from_cache_state(&evm.context.evm.db.cache).state_root();
 
     let print_json_output = |error: Option<String>| {
         if print_json_outcome {
@@ -287,6 +286,8 @@ pub fn execute_test_suite(
         env.block.difficulty = unit.env.current_difficulty;
         // after the Merge prevrandao replaces mix_hash field in block and replaced difficulty opcode in EVM.
         env.block.prevrandao = unit.env.current_random;
+        // EIP-4788
+        env.block.parent_beacon_block_root = unit.env.current_beacon_root;
         // EIP-4844
         if let Some(current_excess_blob_gas) = unit.env.current_excess_blob_gas {
             env.block
@@ -1,7 +1,7 @@
 use super::constants::*;
 use crate::{
     num_words,
-    primitives::{Address, SpecId, U256},
+    primitives::{eip7702, Address, SpecId, U256},
     SelfDestructResult,
 };
 use std::vec::Vec;
@@ -353,11 +353,25 @@ pub const fn memory_gas(num_words: u64) -> u64 {
 
 /// Initial gas that is deducted for transaction to be included.
 /// Initial gas contains initial stipend gas, gas for access list and input data.
+///
+/// EIP-7702 authorizations are not charged, see [`validate_initial_tx_gas_with_auth_list`].
 pub fn validate_initial_tx_gas(
     spec_id: SpecId,
     input: &[u8],
     is_create: bool,
     access_list: &[(Address, Vec<U256>)],
+) -> u64 {
+    validate_initial_tx_gas_with_auth_list(spec_id, input, is_create, access_list, 0)
+}
+
+/// Same as [`validate_initial_tx_gas`], additionally charging the EIP-7702 authorizations
+/// of the transaction.
+pub fn validate_initial_tx_gas_with_auth_list(
+    spec_id: SpecId,
+    input: &[u8],
+    is_create: bool,
+    access_list: &[(Address, Vec<U256>)],
+    authorization_list_num: u64,
 ) -> u64 {
     let mut initial_gas = 0;
     let zero_data_len = input.iter().filter(|v| **v == 0).count() as u64;
@@ -400,5 +414,10 @@ pub fn validate_initial_tx_gas(
         initial_gas += initcode_cost(input.len() as u64)
     }
 
+    // EIP-7702: Set EOA account code
+    if spec_id.is_enabled_in(SpecId::PRAGUE) {
+        initial_gas += authorization_list_num * eip7702::PER_EMPTY_ACCOUNT_COST;
+    }
+
     initial_gas
 }
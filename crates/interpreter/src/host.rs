@@ -1,4 +1,7 @@
-use crate::primitives::{Address, Bytes, Env, Log, B256, U256};
+use crate::{
+    gas::warm_cold_cost,
+    primitives::{Address, Bytes, Env, Log, B256, U256},
+};
 
 mod dummy;
 pub use dummy::DummyHost;
@@ -64,12 +67,26 @@ pub struct SStoreResult {
 }
 
 /// Result of the account load from Journal state.
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct LoadAccountResult {
     /// Is account cold loaded
     pub is_cold: bool,
     /// Is account empty, if true account is not created.
     pub is_empty: bool,
+    /// Is set if account code is EIP-7702 delegation designator.
+    /// Contains cold flag of the delegated account.
+    pub is_delegate_account_cold: Option<bool>,
+}
+
+impl LoadAccountResult {
+    /// Returns gas cost of loading the delegated account, zero if account is not delegated.
+    #[inline]
+    pub const fn delegation_cost(&self) -> u64 {
+        match self.is_delegate_account_cold {
+            Some(is_cold) => warm_cold_cost(is_cold),
+            None => 0,
+        }
+    }
 }
 
 /// Result of a selfdestruct instruction.
@@ -8,7 +8,7 @@ use crate::{
     interpreter::Interpreter,
     primitives::{Address, Bytes, Eof, Spec, SpecId::*, U256},
     CallInputs, CallScheme, CallValue, CreateInputs, CreateScheme, EOFCreateInputs, Host,
-    InstructionResult, InterpreterAction, InterpreterResult, LoadAccountResult, MAX_INITCODE_SIZE,
+    InstructionResult, InterpreterAction, InterpreterResult, MAX_INITCODE_SIZE,
 };
 use core::cmp::max;
 use std::boxed::Box;
@@ -168,7 +168,7 @@ pub fn extcall_gas_calc<H: Host + ?Sized>(
         transfers_value,
         load_result.is_cold,
         load_result.is_empty,
-    );
+    ) + load_result.delegation_cost();
     gas!(interpreter, call_cost, None);
 
     // 7. Calculate the gas available to callee as caller’s
@@ -379,15 +379,15 @@ pub fn call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &
         return;
     };
 
-    let Some(LoadAccountResult { is_cold, is_empty }) = host.load_account(to) else {
+    let Some(account_load) = host.load_account(to) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
     let Some(mut gas_limit) = calc_call_gas::<SPEC>(
         interpreter,
-        is_cold,
+        account_load,
         has_transfer,
-        is_empty,
+        account_load.is_empty,
         local_gas_limit,
     ) else {
         return;
@@ -429,14 +429,14 @@ pub fn call_code<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, ho
         return;
     };
 
-    let Some(LoadAccountResult { is_cold, .. }) = host.load_account(to) else {
+    let Some(account_load) = host.load_account(to) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
 
     let Some(mut gas_limit) = calc_call_gas::<SPEC>(
         interpreter,
-        is_cold,
+        account_load,
         value != U256::ZERO,
         false,
         local_gas_limit,
@@ -480,12 +480,12 @@ pub fn delegate_call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter
         return;
     };
 
-    let Some(LoadAccountResult { is_cold, .. }) = host.load_account(to) else {
+    let Some(account_load) = host.load_account(to) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
     let Some(gas_limit) =
-        calc_call_gas::<SPEC>(interpreter, is_cold, false, false, local_gas_limit)
+        calc_call_gas::<SPEC>(interpreter, account_load, false, false, local_gas_limit)
     else {
         return;
     };
@@ -521,13 +521,13 @@ pub fn static_call<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
         return;
     };
 
-    let Some(LoadAccountResult { is_cold, .. }) = host.load_account(to) else {
+    let Some(account_load) = host.load_account(to) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
     };
 
     let Some(gas_limit) =
-        calc_call_gas::<SPEC>(interpreter, is_cold, false, false, local_gas_limit)
+        calc_call_gas::<SPEC>(interpreter, account_load, false, false, local_gas_limit)
     else {
         return;
     };
@@ -54,13 +54,13 @@ pub fn rjumpv<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
 }
 
 pub fn jump<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
-    gas!(interpreter, gas::MID);
+    static_gas!(interpreter, gas::MID);
     pop!(interpreter, target);
     jump_inner(interpreter, target);
 }
 
 pub fn jumpi<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
-    gas!(interpreter, gas::HIGH);
+    static_gas!(interpreter, gas::HIGH);
     pop!(interpreter, target, cond);
     if cond != U256::ZERO {
         jump_inner(interpreter, target);
@@ -79,7 +79,7 @@ fn jump_inner(interpreter: &mut Interpreter, target: U256) {
 }
 
 pub fn jumpdest_or_nop<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
-    gas!(interpreter, gas::JUMPDEST);
+    static_gas!(interpreter, gas::JUMPDEST);
 }
 
 pub fn callf<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
@@ -148,7 +148,7 @@ pub fn jumpf<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
 }
 
 pub fn pc<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
-    gas!(interpreter, gas::BASE);
+    static_gas!(interpreter, gas::BASE);
     // - 1 because we have already advanced the instruction pointer in `Interpreter::step`
     push!(interpreter, U256::from(interpreter.program_counter() - 1));
 }
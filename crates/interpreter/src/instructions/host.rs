@@ -32,7 +32,7 @@ pub fn balance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host
 /// EIP-1884: Repricing for trie-size-dependent opcodes
 pub fn selfbalance<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, ISTANBUL);
-    gas!(interpreter, gas::LOW);
+    static_gas!(interpreter, gas::LOW);
     let Some((balance, _)) = host.balance(interpreter.contract.target_address) else {
         interpreter.instruction_result = InstructionResult::FatalExternalError;
         return;
@@ -103,7 +103,7 @@ pub fn extcodecopy<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter,
 }
 
 pub fn blockhash<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
-    gas!(interpreter, gas::BLOCKHASH);
+    static_gas!(interpreter, gas::BLOCKHASH);
     pop_top!(interpreter, number);
 
     let Some(hash) = host.block_hash(*number) else {
@@ -152,7 +152,7 @@ pub fn sstore<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host:
 pub fn tstore<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, CANCUN);
     require_non_staticcall!(interpreter);
-    gas!(interpreter, gas::WARM_STORAGE_READ_COST);
+    static_gas!(interpreter, gas::WARM_STORAGE_READ_COST);
 
     pop!(interpreter, index, value);
 
@@ -163,7 +163,7 @@ pub fn tstore<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host:
 /// Load value from transient storage
 pub fn tload<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, host: &mut H) {
     check!(interpreter, CANCUN);
-    gas!(interpreter, gas::WARM_STORAGE_READ_COST);
+    static_gas!(interpreter, gas::WARM_STORAGE_READ_COST);
 
     pop_top!(interpreter, index);
 
@@ -59,6 +59,20 @@ macro_rules! gas {
     };
 }
 
+/// Records the static `gas` of the instruction, unless it was already charged for the whole
+/// basic block, see [`Interpreter::run_gas_blocks`](crate::Interpreter::run_gas_blocks).
+///
+/// The cost must match the static gas summed by
+/// [`analyze_gas_blocks`](crate::interpreter::analysis::analyze_gas_blocks).
+#[macro_export]
+macro_rules! static_gas {
+    ($interp:expr, $gas:expr) => {
+        if !$interp.gas_block_charged {
+            $crate::gas!($interp, $gas);
+        }
+    };
+}
+
 /// Records a `gas` refund.
 #[macro_export]
 macro_rules! refund {
@@ -125,21 +139,21 @@ macro_rules! pop_address {
 #[macro_export]
 macro_rules! pop_address_ret {
     ($interp:expr, $x1:ident, $ret:expr) => {
-        if $interp.stack.len() < 1 {
+        if !$interp.gas_block_charged && $interp.stack.len() < 1 {
             $interp.instruction_result = $crate::InstructionResult::StackUnderflow;
             return $ret;
         }
-        // SAFETY: Length is checked above.
+        // SAFETY: Length is checked above or with the basic block.
         let $x1 = $crate::primitives::Address::from_word($crate::primitives::B256::from(unsafe {
             $interp.stack.pop_unsafe()
         }));
     };
     ($interp:expr, $x1:ident, $x2:ident, $ret:expr) => {
-        if $interp.stack.len() < 2 {
+        if !$interp.gas_block_charged && $interp.stack.len() < 2 {
             $interp.instruction_result = $crate::InstructionResult::StackUnderflow;
             return $ret;
         }
-        // SAFETY: Length is checked above.
+        // SAFETY: Length is checked above or with the basic block.
         let $x1 = $crate::primitives::Address::from_word($crate::primitives::B256::from(unsafe {
             $interp.stack.pop_unsafe()
         }));
@@ -174,43 +188,43 @@ macro_rules! pop {
 #[macro_export]
 macro_rules! pop_ret {
     ($interp:expr, $x1:ident, $ret:expr) => {
-        if $interp.stack.len() < 1 {
+        if !$interp.gas_block_charged && $interp.stack.len() < 1 {
             $interp.instruction_result = $crate::InstructionResult::StackUnderflow;
             return $ret;
         }
-        // SAFETY: Length is checked above.
+        // SAFETY: Length is checked above or with the basic block.
         let $x1 = unsafe { $interp.stack.pop_unsafe() };
     };
     ($interp:expr, $x1:ident, $x2:ident, $ret:expr) => {
-        if $interp.stack.len() < 2 {
+        if !$interp.gas_block_charged && $interp.stack.len() < 2 {
             $interp.instruction_result = $crate::InstructionResult::StackUnderflow;
             return $ret;
         }
-        // SAFETY: Length is checked above.
+        // SAFETY: Length is checked above or with the basic block.
         let ($x1, $x2) = unsafe { $interp.stack.pop2_unsafe() };
     };
     ($interp:expr, $x1:ident, $x2:ident, $x3:ident, $ret:expr) => {
-        if $interp.stack.len() < 3 {
+        if !$interp.gas_block_charged && $interp.stack.len() < 3 {
             $interp.instruction_result = $crate::InstructionResult::StackUnderflow;
             return $ret;
         }
-        // SAFETY: Length is checked above.
+        // SAFETY: Length is checked above or with the basic block.
         let ($x1, $x2, $x3) = unsafe { $interp.stack.pop3_unsafe() };
     };
     ($interp:expr, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $ret:expr) => {
-        if $interp.stack.len() < 4 {
+        if !$interp.gas_block_charged && $interp.stack.len() < 4 {
             $interp.instruction_result = $crate::InstructionResult::StackUnderflow;
             return $ret;
         }
-        // SAFETY: Length is checked above.
+        // SAFETY: Length is checked above or with the basic block.
         let ($x1, $x2, $x3, $x4) = unsafe { $interp.stack.pop4_unsafe() };
     };
     ($interp:expr, $x1:ident, $x2:ident, $x3:ident, $x4:ident, $x5:ident, $ret:expr) => {
-        if $interp.stack.len() < 5 {
+        if !$interp.gas_block_charged && $interp.stack.len() < 5 {
             $interp.instruction_result = $crate::InstructionResult::StackUnderflow;
             return $ret;
         }
-        // SAFETY: Length is checked above.
+        // SAFETY: Length is checked above or with the basic block.
         let ($x1, $x2, $x3, $x4, $x5) = unsafe { $interp.stack.pop5_unsafe() };
     };
 }
@@ -220,27 +234,27 @@ macro_rules! pop_ret {
 #[macro_export]
 macro_rules! pop_top {
     ($interp:expr, $x1:ident) => {
-        if $interp.stack.len() < 1 {
+        if !$interp.gas_block_charged && $interp.stack.len() < 1 {
             $interp.instruction_result = $crate::InstructionResult::StackUnderflow;
             return;
         }
-        // SAFETY: Length is checked above.
+        // SAFETY: Length is checked above or with the basic block.
         let $x1 = unsafe { $interp.stack.top_unsafe() };
     };
     ($interp:expr, $x1:ident, $x2:ident) => {
-        if $interp.stack.len() < 2 {
+        if !$interp.gas_block_charged && $interp.stack.len() < 2 {
             $interp.instruction_result = $crate::InstructionResult::StackUnderflow;
             return;
         }
-        // SAFETY: Length is checked above.
+        // SAFETY: Length is checked above or with the basic block.
         let ($x1, $x2) = unsafe { $interp.stack.pop_top_unsafe() };
     };
     ($interp:expr, $x1:ident, $x2:ident, $x3:ident) => {
-        if $interp.stack.len() < 3 {
+        if !$interp.gas_block_charged && $interp.stack.len() < 3 {
             $interp.instruction_result = $crate::InstructionResult::StackUnderflow;
             return;
         }
-        // SAFETY: Length is checked above.
+        // SAFETY: Length is checked above or with the basic block.
         let ($x1, $x2, $x3) = unsafe { $interp.stack.pop2_top_unsafe() };
     };
 }
@@ -249,25 +263,28 @@ macro_rules! pop_top {
 #[macro_export]
 macro_rules! push_b256 {
 	($interp:expr, $($x:expr),* $(,)?) => ($(
-        match $interp.stack.push_b256($x) {
-            Ok(()) => {},
-            Err(e) => {
-                $interp.instruction_result = e;
-                return;
-            },
-        }
+        let value: $crate::primitives::B256 = $x;
+        $crate::push!($interp, $crate::primitives::U256::from_be_bytes(value.0));
     )*)
 }
 
 /// Pushes a `B256` value onto the stack. Fails the instruction if the stack is full.
+///
+/// Stack is not checked if it was already checked for the whole basic block, see
+/// [`Interpreter::run_gas_blocks`](crate::Interpreter::run_gas_blocks).
 #[macro_export]
 macro_rules! push {
     ($interp:expr, $($x:expr),* $(,)?) => ($(
-        match $interp.stack.push($x) {
-            Ok(()) => {},
-            Err(e) => {
-                $interp.instruction_result = e;
-                return;
+        if $interp.gas_block_charged {
+            // SAFETY: Stack growth is checked with the basic block.
+            unsafe { $interp.stack.push_unsafe($x) };
+        } else {
+            match $interp.stack.push($x) {
+                Ok(()) => {},
+                Err(e) => {
+                    $interp.instruction_result = e;
+                    return;
+                }
             }
         }
     )*)
@@ -5,10 +5,8 @@ use crate::{
 };
 
 pub fn pop<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
-    gas!(interpreter, gas::BASE);
-    if let Err(result) = interpreter.stack.pop() {
-        interpreter.instruction_result = result;
-    }
+    static_gas!(interpreter, gas::BASE);
+    pop!(interpreter, _value);
 }
 
 /// EIP-3855: PUSH0 instruction
@@ -16,21 +14,20 @@ pub fn pop<H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
 /// Introduce a new instruction which pushes the constant value 0 onto the stack.
 pub fn push0<H: Host + ?Sized, SPEC: Spec>(interpreter: &mut Interpreter, _host: &mut H) {
     check!(interpreter, SHANGHAI);
-    gas!(interpreter, gas::BASE);
-    if let Err(result) = interpreter.stack.push(U256::ZERO) {
-        interpreter.instruction_result = result;
-    }
+    static_gas!(interpreter, gas::BASE);
+    push!(interpreter, U256::ZERO);
 }
 
 pub fn push<const N: usize, H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
-    gas!(interpreter, gas::VERYLOW);
+    static_gas!(interpreter, gas::VERYLOW);
     // SAFETY: In analysis we append trailing bytes to the bytecode so that this is safe to do
     // without bounds checking.
     let ip = interpreter.instruction_pointer;
-    if let Err(result) = interpreter
-        .stack
-        .push_slice(unsafe { core::slice::from_raw_parts(ip, N) })
-    {
+    let slice = unsafe { core::slice::from_raw_parts(ip, N) };
+    if interpreter.gas_block_charged {
+        // SAFETY: stack is checked with the basic block.
+        unsafe { interpreter.stack.push_unsafe(U256::from_be_slice(slice)) };
+    } else if let Err(result) = interpreter.stack.push_slice(slice) {
         interpreter.instruction_result = result;
         return;
     }
@@ -38,15 +35,21 @@ pub fn push<const N: usize, H: Host + ?Sized>(interpreter: &mut Interpreter, _ho
 }
 
 pub fn dup<const N: usize, H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
-    gas!(interpreter, gas::VERYLOW);
-    if let Err(result) = interpreter.stack.dup(N) {
+    static_gas!(interpreter, gas::VERYLOW);
+    if interpreter.gas_block_charged {
+        // SAFETY: stack is checked with the basic block.
+        unsafe { interpreter.stack.dup_unsafe(N) };
+    } else if let Err(result) = interpreter.stack.dup(N) {
         interpreter.instruction_result = result;
     }
 }
 
 pub fn swap<const N: usize, H: Host + ?Sized>(interpreter: &mut Interpreter, _host: &mut H) {
-    gas!(interpreter, gas::VERYLOW);
-    if let Err(result) = interpreter.stack.swap(N) {
+    static_gas!(interpreter, gas::VERYLOW);
+    if interpreter.gas_block_charged {
+        // SAFETY: stack is checked with the basic block.
+        unsafe { interpreter.stack.swap_unsafe(N) };
+    } else if let Err(result) = interpreter.stack.swap(N) {
         interpreter.instruction_result = result;
     }
 }
@@ -11,11 +11,11 @@ pub use stack::{Stack, STACK_LIMIT};
 
 use crate::EOFCreateOutcome;
 use crate::{
-    gas, primitives::Bytes, push, push_b256, return_ok, return_revert, CallOutcome, CreateOutcome,
-    FunctionStack, Gas, Host, InstructionResult, InterpreterAction,
+    gas, opcode, primitives::Bytes, push, push_b256, return_ok, return_revert, CallOutcome,
+    CreateOutcome, FunctionStack, Gas, Host, InstructionResult, InterpreterAction,
 };
 use core::cmp::min;
-use revm_primitives::{Bytecode, Eof, U256};
+use revm_primitives::{Bytecode, DecodedCode, DecodedInstruction, Eof, U256};
 use std::borrow::ToOwned;
 use std::sync::Arc;
 
@@ -61,6 +61,9 @@ pub struct Interpreter {
     /// Set inside CALL or CREATE instructions and RETURN or REVERT instructions. Additionally those instructions will set
     /// InstructionResult to CallOrCreate/Return/Revert so we know the reason.
     pub next_action: InterpreterAction,
+    /// Whether the static gas and the stack of the current instruction were already charged
+    /// and checked with its basic block, see [`Self::run_gas_blocks`].
+    pub gas_block_charged: bool,
 }
 
 impl Default for Interpreter {
@@ -91,6 +94,7 @@ impl Interpreter {
             shared_memory: EMPTY_SHARED_MEMORY,
             stack: Stack::new(),
             next_action: InterpreterAction::None,
+            gas_block_charged: false,
         }
     }
 
@@ -391,6 +395,292 @@ impl Interpreter {
         }
     }
 
+    /// Executes the interpreter until it returns or stops, charging the static gas and checking
+    /// the stack once per basic block of the legacy bytecode.
+    ///
+    /// Bytecode must be analysed with the blocks, see
+    /// [`to_analysed_with_gas_blocks`](analysis::to_analysed_with_gas_blocks), otherwise this
+    /// is the same as [`Self::run`]. Instructions of a block that does not have enough gas
+    /// or stack are executed one by one, so the instruction result is the same as with
+    /// [`Self::run`]. Only the remaining gas of an exceptional halt in the middle of a block
+    /// differs, which is consumed in full either way.
+    ///
+    /// Instruction table must charge the static gas as the mainnet instructions do, with
+    /// [`static_gas!`](crate::static_gas), and pop and push the stack items of
+    /// [`OPCODE_INFO_JUMPTABLE`](crate::OPCODE_INFO_JUMPTABLE) with [`pop!`](crate::pop) and
+    /// [`push!`](crate::push), which skip the stack checks within the checked block.
+    /// Instructions that are wrapped by an inspector should be run with [`Self::run`] so the
+    /// inspector sees the gas of every instruction.
+    pub fn run_gas_blocks<FN, H: Host + ?Sized>(
+        &mut self,
+        shared_memory: SharedMemory,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+    ) -> InterpreterAction
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        let gas_blocks = match self.contract.bytecode.legacy_gas_blocks() {
+            Some(gas_blocks) if !self.is_eof => gas_blocks.clone(),
+            _ => return self.run(shared_memory, instruction_table, host),
+        };
+        let blocks = gas_blocks.as_slice();
+
+        self.next_action = InterpreterAction::None;
+        self.shared_memory = shared_memory;
+        let mut block_idx = 0;
+        while self.instruction_result == InstructionResult::Continue {
+            let Some(idx) = gas_blocks.find(self.program_counter(), block_idx) else {
+                // not at the start of a block, e.g. in the padding of the code.
+                self.step(instruction_table, host);
+                continue;
+            };
+            block_idx = idx;
+            let block = &blocks[idx];
+            if self.stack.len() < block.stack_required as usize
+                || self.stack.len() + block.stack_max_growth as usize > STACK_LIMIT
+                || !self.gas.record_cost(block.static_gas)
+            {
+                // the block fails, execute the first instruction on its own.
+                self.step(instruction_table, host);
+                continue;
+            }
+
+            // SAFETY: blocks are within the code.
+            let last = unsafe { self.bytecode.as_ptr().add(block.last as usize) };
+            self.gas_block_charged = true;
+            loop {
+                let is_last = self.instruction_pointer == last;
+                self.step(instruction_table, host);
+                if is_last || self.instruction_result != InstructionResult::Continue {
+                    break;
+                }
+            }
+            self.gas_block_charged = false;
+        }
+
+        if self.next_action.is_some() {
+            return core::mem::take(&mut self.next_action);
+        }
+        InterpreterAction::Return {
+            result: InterpreterResult {
+                result: self.instruction_result,
+                output: Bytes::new(),
+                gas: self.gas,
+            },
+        }
+    }
+
+    /// Executes the interpreter until it returns or stops, dispatching on the pre-decoded
+    /// instruction stream of the bytecode.
+    ///
+    /// Bytecode must be decoded, see [`to_decoded`](analysis::to_decoded), otherwise this is
+    /// the same as [`Self::run_gas_blocks`]. `PUSH` and the static jumps resolved by the
+    /// decoding are executed in the loop, without the dispatch and without reading the
+    /// immediates from the code. The rest of the instructions are executed by the table.
+    ///
+    /// Instruction table must implement `PUSH`, `JUMP`, `JUMPI`, `RJUMP` and `RJUMPI` as the
+    /// mainnet instructions do. Instructions that are wrapped by an inspector should be run
+    /// with [`Self::run_decoded_by_table`] so the inspector sees every instruction.
+    pub fn run_decoded<FN, H: Host + ?Sized>(
+        &mut self,
+        shared_memory: SharedMemory,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+    ) -> InterpreterAction
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        if self.current_decoded_code().is_none() {
+            return self.run_gas_blocks(shared_memory, instruction_table, host);
+        }
+        self.run_decoded_inner::<FN, H, true>(shared_memory, instruction_table, host)
+    }
+
+    /// Executes the interpreter until it returns or stops, following the pre-decoded
+    /// instruction stream of the bytecode but executing every instruction by the table.
+    ///
+    /// Instruction pointer is set from the byte offset of the decoded instruction before it is
+    /// executed, so [`Self::program_counter`] is the same as with [`Self::run`] for the
+    /// inspectors and the tracers. If the bytecode is not decoded this is the same as
+    /// [`Self::run`].
+    pub fn run_decoded_by_table<FN, H: Host + ?Sized>(
+        &mut self,
+        shared_memory: SharedMemory,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+    ) -> InterpreterAction
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        self.run_decoded_inner::<FN, H, false>(shared_memory, instruction_table, host)
+    }
+
+    /// Returns the decoded instructions of the current code section, if decoded.
+    #[inline]
+    fn current_decoded_code(&self) -> Option<Arc<DecodedCode>> {
+        self.contract
+            .bytecode
+            .decoded_code(self.function_stack.current_code_idx)
+            .cloned()
+    }
+
+    fn run_decoded_inner<FN, H: Host + ?Sized, const INLINE: bool>(
+        &mut self,
+        shared_memory: SharedMemory,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+    ) -> InterpreterAction
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        self.next_action = InterpreterAction::None;
+        self.shared_memory = shared_memory;
+        if !self.run_decoded_loop::<FN, H, INLINE>(instruction_table, host) {
+            // left the decoded instructions, continue with the bytes.
+            while self.instruction_result == InstructionResult::Continue {
+                self.step(instruction_table, host);
+            }
+        }
+
+        if self.next_action.is_some() {
+            return core::mem::take(&mut self.next_action);
+        }
+        InterpreterAction::Return {
+            result: InterpreterResult {
+                result: self.instruction_result,
+                output: Bytes::new(),
+                gas: self.gas,
+            },
+        }
+    }
+
+    /// Runs the decoded instructions until the interpreter stops.
+    ///
+    /// Returns `false` without executing the current instruction if the instruction pointer
+    /// is not at a decoded instruction.
+    fn run_decoded_loop<FN, H: Host + ?Sized, const INLINE: bool>(
+        &mut self,
+        instruction_table: &[FN; 256],
+        host: &mut H,
+    ) -> bool
+    where
+        FN: Fn(&mut Interpreter, &mut H),
+    {
+        let Some(mut decoded) = self.current_decoded_code() else {
+            return false;
+        };
+        let Some(mut idx) = decoded.index_of(self.program_counter()) else {
+            return false;
+        };
+        let mut base = self.bytecode.as_ptr();
+        loop {
+            let Some(&instruction) = decoded.instructions.get(idx) else {
+                // falling off the end of the code is a STOP, as in the padded legacy code.
+                self.instruction_result = InstructionResult::Stop;
+                return true;
+            };
+
+            if INLINE {
+                match self.step_decoded_inline(&decoded, idx) {
+                    Some(Ok(next)) => {
+                        idx = next;
+                        continue;
+                    }
+                    Some(Err(result)) => {
+                        // SAFETY: decoded offsets are within the code.
+                        self.instruction_pointer = unsafe { base.add(decoded.pc(idx) + 1) };
+                        self.instruction_result = result;
+                        return true;
+                    }
+                    None => {}
+                }
+            }
+
+            // SAFETY: decoded offsets are within the code.
+            self.instruction_pointer = unsafe { base.add(decoded.pc(idx) + 1) };
+            (instruction_table[instruction.opcode as usize])(self, host);
+            if self.instruction_result != InstructionResult::Continue {
+                return true;
+            }
+
+            let mut next = idx + 1;
+            if self.bytecode.as_ptr() != base {
+                // switched to another EOF code section.
+                let Some(section) = self.current_decoded_code() else {
+                    return false;
+                };
+                decoded = section;
+                base = self.bytecode.as_ptr();
+                next = 0;
+            }
+            let pc = self.program_counter();
+            idx = match decoded.pcs.get(next) {
+                Some(next_pc) if *next_pc as usize == pc => next,
+                _ => match decoded.index_of(pc) {
+                    Some(idx) => idx,
+                    None => return false,
+                },
+            };
+        }
+    }
+
+    /// Executes `PUSH` or the resolved static jump at `idx` of the decoded instructions.
+    ///
+    /// Returns the index of the next instruction or the result of the halt, or `None` if the
+    /// instruction should be executed by the table.
+    #[inline(always)]
+    fn step_decoded_inline(
+        &mut self,
+        decoded: &DecodedCode,
+        idx: usize,
+    ) -> Option<Result<usize, InstructionResult>> {
+        let DecodedInstruction { opcode, immediate } = decoded.instructions[idx];
+        let resolved = immediate != DecodedInstruction::NO_TARGET;
+        let target = immediate as usize;
+        let (cost, stack_inputs) = match opcode {
+            opcode::PUSH1..=opcode::PUSH32 => (gas::VERYLOW, 0),
+            opcode::JUMP if resolved => (gas::MID, 1),
+            opcode::JUMPI if resolved => (gas::HIGH, 2),
+            opcode::RJUMP if resolved => (gas::BASE, 0),
+            opcode::RJUMPI if resolved => (gas::CONDITION_JUMP_GAS, 1),
+            _ => return None,
+        };
+        if !self.gas.record_cost(cost) {
+            return Some(Err(InstructionResult::OutOfGas));
+        }
+        if self.stack.len() < stack_inputs {
+            return Some(Err(InstructionResult::StackUnderflow));
+        }
+        // SAFETY: stack length is checked above.
+        let next = match opcode {
+            opcode::JUMP | opcode::RJUMP => {
+                if opcode == opcode::JUMP {
+                    unsafe { self.stack.pop_unsafe() };
+                }
+                target
+            }
+            opcode::JUMPI | opcode::RJUMPI => {
+                if opcode == opcode::JUMPI {
+                    unsafe { self.stack.pop_unsafe() };
+                }
+                let condition = unsafe { self.stack.pop_unsafe() };
+                if condition.is_zero() {
+                    idx + 1
+                } else {
+                    target
+                }
+            }
+            _ => {
+                if let Err(result) = self.stack.push(decoded.push_values[target]) {
+                    return Some(Err(result));
+                }
+                idx + 1
+            }
+        };
+        Some(Ok(next))
+    }
+
     /// Resize the memory to the new size. Returns whether the gas was enough to resize the memory.
     #[inline]
     #[must_use]
@@ -460,7 +750,7 @@ pub fn resize_memory(memory: &mut SharedMemory, gas: &mut Gas, new_size: usize)
 mod tests {
     use super::*;
     use crate::{opcode::InstructionTable, DummyHost};
-    use revm_primitives::CancunSpec;
+    use revm_primitives::{hex, CancunSpec};
 
     #[test]
     fn object_safety() {
@@ -476,4 +766,162 @@ mod tests {
             crate::opcode::make_instruction_table::<dyn Host, CancunSpec>();
         let _ = interp.run(EMPTY_SHARED_MEMORY, &table, host);
     }
+
+    #[test]
+    fn gas_blocks_match_run() {
+        let table: InstructionTable<DummyHost> =
+            crate::opcode::make_instruction_table::<DummyHost, CancunSpec>();
+        let run = |code: &'static [u8], gas_limit: u64, gas_blocks: bool| {
+            let bytecode =
+                analysis::to_analysed_with_gas_blocks(Bytecode::new_raw(Bytes::from_static(code)));
+            let contract = Contract::new(
+                Bytes::new(),
+                bytecode,
+                None,
+                crate::primitives::Address::default(),
+                crate::primitives::Address::default(),
+                U256::ZERO,
+            );
+            let mut interp = Interpreter::new(contract, gas_limit, false);
+            let mut host = DummyHost::default();
+            let action = if gas_blocks {
+                interp.run_gas_blocks(SharedMemory::new(), &table, &mut host)
+            } else {
+                interp.run(SharedMemory::new(), &table, &mut host)
+            };
+            let InterpreterAction::Return { result } = action else {
+                panic!("unexpected action {action:?}");
+            };
+            result
+        };
+
+        // PUSH1 5, loop: JUMPDEST ... JUMPI, GAS, POP, RETURN(0, 32)
+        let code = &hex!("60055b6001900380600052806002575a5060206000f3");
+        let full = run(code, u64::MAX, false);
+        assert_eq!(full.result, InstructionResult::Return);
+        let gas_used = full.gas.spent();
+        for gas_limit in 0..=gas_used {
+            let result = run(code, gas_limit, true);
+            let expected = run(code, gas_limit, false);
+            assert_eq!(result.result, expected.result, "gas limit {gas_limit}");
+            if expected.is_ok() {
+                assert_eq!(result, expected);
+            }
+        }
+
+        // ADD with one element on the stack, and a JUMPI to an invalid destination.
+        for code in [&hex!("600101") as &[u8], &hex!("6001600357")] {
+            assert_eq!(run(code, 100, true).result, run(code, 100, false).result);
+        }
+
+        // blocks of JUMPDEST, PUSH0, DUP1 and SWAP1, the last one overflows the stack.
+        let code: &[u8] = [0x5b, 0x5f, 0x80, 0x90].repeat(STACK_LIMIT / 2 + 1).leak();
+        let result = run(code, u64::MAX, true);
+        assert_eq!(result.result, InstructionResult::StackOverflow);
+        assert_eq!(result.result, run(code, u64::MAX, false).result);
+    }
+
+    #[test]
+    fn decoded_match_run() {
+        let table: InstructionTable<DummyHost> =
+            crate::opcode::make_instruction_table::<DummyHost, CancunSpec>();
+        let run = |code: &[u8], gas_limit: u64, mode: usize| {
+            let bytecode = analysis::to_decoded(Bytecode::new_raw(Bytes::copy_from_slice(code)));
+            let contract = Contract::new(
+                Bytes::new(),
+                bytecode,
+                None,
+                crate::primitives::Address::default(),
+                crate::primitives::Address::default(),
+                U256::ZERO,
+            );
+            let mut interp = Interpreter::new(contract, gas_limit, false);
+            let mut host = DummyHost::default();
+            let action = match mode {
+                0 => interp.run(SharedMemory::new(), &table, &mut host),
+                1 => interp.run_decoded(SharedMemory::new(), &table, &mut host),
+                _ => interp.run_decoded_by_table(SharedMemory::new(), &table, &mut host),
+            };
+            let InterpreterAction::Return { result } = action else {
+                panic!("unexpected action {action:?}");
+            };
+            (
+                result,
+                interp.program_counter(),
+                interp.stack.data().clone(),
+            )
+        };
+
+        let codes: [&[u8]; 5] = [
+            // PUSH1 5, loop: JUMPDEST ... JUMPI, GAS, POP, RETURN(0, 32)
+            &hex!("60055b6001900380600052806002575a5060206000f3"),
+            // JUMP to an invalid destination, and a dynamic JUMP.
+            &hex!("600356"),
+            &hex!("6005600201565b00"),
+            // JUMPI with one element on the stack, and a truncated PUSH2.
+            &hex!("600157"),
+            &hex!("6001610a"),
+        ];
+        for code in codes {
+            let full = run(code, u64::MAX, 0);
+            for gas_limit in 0..=full.0.gas.spent().min(1000) {
+                let expected = run(code, gas_limit, 0);
+                assert_eq!(run(code, gas_limit, 1), expected, "gas limit {gas_limit}");
+                assert_eq!(run(code, gas_limit, 2), expected, "gas limit {gas_limit}");
+            }
+        }
+    }
+
+    #[test]
+    fn decoded_eof_match_run() {
+        use crate::primitives::{
+            eof::{EofBody, TypesSection},
+            PragueSpec,
+        };
+
+        let table: InstructionTable<DummyHost> =
+            crate::opcode::make_instruction_table::<DummyHost, PragueSpec>();
+        // PUSH1 3, loop: CALLF 1, PUSH1 1, SWAP1, SUB, DUP1, RJUMPI loop, STOP
+        // and PUSH1 7, POP, RETF
+        let eof = EofBody {
+            types_section: vec![TypesSection::new(0, 0x80, 2), TypesSection::new(0, 0, 1)],
+            code_section: vec![
+                Bytes::from_static(&hex!("6003e300016001900380e1fff500")),
+                Bytes::from_static(&hex!("600750e4")),
+            ],
+            container_section: vec![],
+            data_section: Bytes::new(),
+            is_data_filled: true,
+        }
+        .into_eof();
+        let bytecode = analysis::to_decoded(Bytecode::Eof(Arc::new(eof)));
+        assert!(bytecode.decoded_code(1).is_some());
+
+        let run = |gas_limit: u64, mode: usize| {
+            let mut interp = Interpreter::new_bytecode(bytecode.clone());
+            interp.gas = Gas::new(gas_limit);
+            let mut host = DummyHost::default();
+            let action = match mode {
+                0 => interp.run(SharedMemory::new(), &table, &mut host),
+                1 => interp.run_decoded(SharedMemory::new(), &table, &mut host),
+                _ => interp.run_decoded_by_table(SharedMemory::new(), &table, &mut host),
+            };
+            let InterpreterAction::Return { result } = action else {
+                panic!("unexpected action {action:?}");
+            };
+            (
+                result,
+                interp.program_counter(),
+                interp.stack.data().clone(),
+            )
+        };
+
+        let full = run(u64::MAX, 0);
+        assert_eq!(full.0.result, InstructionResult::Stop);
+        for gas_limit in 0..=full.0.gas.spent() {
+            let expected = run(gas_limit, 0);
+            assert_eq!(run(gas_limit, 1), expected, "gas limit {gas_limit}");
+            assert_eq!(run(gas_limit, 2), expected, "gas limit {gas_limit}");
+        }
+    }
 }
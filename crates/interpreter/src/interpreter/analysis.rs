@@ -1,18 +1,24 @@
 use revm_primitives::{eof::EofDecodeError, HashSet};
 
 use crate::{
+    gas,
     instructions::utility::{read_i16, read_u16},
     opcode,
     primitives::{
         bitvec::prelude::{bitvec, BitVec, Lsb0},
         eof::TypesSection,
-        legacy::JumpTable,
-        Bytecode, Bytes, Eof, LegacyAnalyzedBytecode,
+        legacy::{GasBlock, GasBlocks, JumpTable},
+        AnalysisKind, Bytecode, Bytes, Eof, LegacyAnalyzedBytecode,
     },
     OPCODE_INFO_JUMPTABLE, STACK_LIMIT,
 };
 use std::{sync::Arc, vec, vec::Vec};
 
+pub mod cfg;
+mod decode;
+
+pub use decode::{decode_eof_code, decode_legacy, to_decoded};
+
 const EOF_NON_RETURNING_FUNCTION: u8 = 0x80;
 
 /// Perform bytecode analysis.
@@ -37,6 +43,19 @@ pub fn to_analysed(bytecode: Bytecode) -> Bytecode {
     Bytecode::LegacyAnalyzed(LegacyAnalyzedBytecode::new(bytes, len, jump_table))
 }
 
+/// Perform bytecode analysis of the given kind.
+///
+/// Bytecode that is already analysed this way is returned as-is, so the analysed bytecode can
+/// be kept and passed again.
+pub fn to_analysed_with_kind(bytecode: Bytecode, kind: &AnalysisKind) -> Bytecode {
+    match kind {
+        AnalysisKind::Raw => bytecode,
+        AnalysisKind::GasBlocks => to_analysed_with_gas_blocks(bytecode),
+        AnalysisKind::Decoded => to_decoded(bytecode),
+        _ => to_analysed(bytecode),
+    }
+}
+
 /// Analyze bytecode to build a jump map.
 fn analyze(code: &[u8]) -> JumpTable {
     let mut jumps: BitVec<u8> = bitvec![u8, Lsb0; 0; code.len()];
@@ -66,6 +85,102 @@ fn analyze(code: &[u8]) -> JumpTable {
     JumpTable(Arc::new(jumps))
 }
 
+/// Perform bytecode analysis with the basic blocks, see [`analyze_gas_blocks`].
+///
+/// Analyzed legacy bytecode without the blocks is extended with them, other bytecode is
+/// returned as-is.
+pub fn to_analysed_with_gas_blocks(bytecode: Bytecode) -> Bytecode {
+    match to_analysed(bytecode) {
+        Bytecode::LegacyAnalyzed(analyzed) if analyzed.gas_blocks().is_none() => {
+            let gas_blocks = analyze_gas_blocks(analyzed.original_byte_slice());
+            Bytecode::LegacyAnalyzed(analyzed.with_gas_blocks(gas_blocks))
+        }
+        bytecode => bytecode,
+    }
+}
+
+/// Splits legacy code into basic blocks and sums the static gas and stack requirements
+/// of every block.
+///
+/// Block starts at `JUMPDEST` and ends after a jump or an instruction that charges more than
+/// its static gas, or observes the remaining gas, e.g. memory expansion, `SSTORE`, `GAS`, calls
+/// and terminating instructions. So the static gas of the following instructions is never
+/// charged before an instruction that could run out of gas on its own or depends on it.
+pub fn analyze_gas_blocks(code: &[u8]) -> GasBlocks {
+    // instructions that charge gas on their own, including all unknown and terminating ones,
+    // have zero static gas.
+    let blocks = cfg::split_blocks(code, |opcode| {
+        static_gas(opcode) == 0
+            || matches!(
+                opcode,
+                opcode::JUMP
+                    | opcode::JUMPI
+                    | opcode::GAS
+                    | opcode::MLOAD
+                    | opcode::MSTORE
+                    | opcode::MSTORE8
+            )
+    });
+    let blocks = blocks
+        .into_iter()
+        .map(|block| GasBlock {
+            start: block.start as u32,
+            last: block.last as u32,
+            static_gas: cfg::Instructions::new(&code[..block.end], block.start)
+                .map(|(_, opcode, _)| static_gas(opcode))
+                .sum(),
+            stack_required: block.stack_required as u16,
+            stack_max_growth: block.stack_max_growth as u16,
+        })
+        .collect();
+
+    GasBlocks(Arc::new(blocks))
+}
+
+/// Static gas of the legacy instruction that is charged with [`static_gas!`](crate::static_gas).
+const fn static_gas(opcode: u8) -> u64 {
+    match opcode {
+        opcode::ADD
+        | opcode::SUB
+        | opcode::LT..=opcode::SAR
+        | opcode::CALLDATALOAD
+        | opcode::MLOAD
+        | opcode::MSTORE
+        | opcode::MSTORE8
+        | opcode::BLOBHASH
+        | opcode::PUSH1..=opcode::SWAP16 => gas::VERYLOW,
+        opcode::ADDRESS
+        | opcode::ORIGIN
+        | opcode::CALLER
+        | opcode::CALLVALUE
+        | opcode::CALLDATASIZE
+        | opcode::CODESIZE
+        | opcode::GASPRICE
+        | opcode::RETURNDATASIZE
+        | opcode::COINBASE..=opcode::CHAINID
+        | opcode::BASEFEE
+        | opcode::BLOBBASEFEE
+        | opcode::POP
+        | opcode::PC
+        | opcode::MSIZE
+        | opcode::GAS
+        | opcode::PUSH0 => gas::BASE,
+        opcode::MUL
+        | opcode::DIV
+        | opcode::SDIV
+        | opcode::MOD
+        | opcode::SMOD
+        | opcode::SIGNEXTEND
+        | opcode::SELFBALANCE => gas::LOW,
+        opcode::ADDMOD | opcode::MULMOD | opcode::JUMP => gas::MID,
+        opcode::JUMPI => gas::HIGH,
+        opcode::JUMPDEST => gas::JUMPDEST,
+        opcode::BLOCKHASH => gas::BLOCKHASH,
+        opcode::TLOAD | opcode::TSTORE => gas::WARM_STORAGE_READ_COST,
+        _ => 0,
+    }
+}
+
 pub fn validate_raw_eof(bytecode: Bytes) -> Result<Eof, EofError> {
     let eof = Eof::decode(bytecode)?;
     validate_eof(&eof)?;
@@ -561,6 +676,56 @@ mod test {
     use super::*;
     use revm_primitives::hex;
 
+    #[test]
+    fn gas_blocks() {
+        // PUSH1 5, loop: JUMPDEST ... JUMPI, GAS, POP, RETURN(0, 32)
+        let code = hex!("60055b6001900380600052806002575a5060206000f3");
+        let blocks = analyze_gas_blocks(&code);
+        assert_eq!(
+            blocks.as_slice(),
+            &[
+                GasBlock {
+                    start: 0,
+                    last: 0,
+                    static_gas: 3,
+                    stack_required: 0,
+                    stack_max_growth: 1,
+                },
+                GasBlock {
+                    start: 2,
+                    last: 10,
+                    static_gas: 19,
+                    stack_required: 1,
+                    stack_max_growth: 2,
+                },
+                GasBlock {
+                    start: 11,
+                    last: 14,
+                    static_gas: 16,
+                    stack_required: 1,
+                    stack_max_growth: 2,
+                },
+                GasBlock {
+                    start: 15,
+                    last: 15,
+                    static_gas: 2,
+                    stack_required: 0,
+                    stack_max_growth: 1,
+                },
+                GasBlock {
+                    start: 16,
+                    last: 21,
+                    static_gas: 8,
+                    stack_required: 1,
+                    stack_max_growth: 1,
+                },
+            ]
+        );
+        assert_eq!(blocks.find(16, 3), Some(4));
+        assert_eq!(blocks.find(2, 4), Some(1));
+        assert_eq!(blocks.find(3, 0), None);
+    }
+
     #[test]
     fn test1() {
         // result:Result { result: false, exception: Some("EOF_ConflictingStackHeight") }
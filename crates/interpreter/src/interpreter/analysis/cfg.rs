@@ -0,0 +1,610 @@
+//! Control-flow graph of the legacy bytecode.
+//!
+//! [`ControlFlowGraph`] splits the code into basic blocks and connects them with the jumps
+//! whose targets are known statically. Targets are found by tracking the constants that are
+//! pushed and moved on the stack from the entry of the code, so the return jumps of the
+//! internal functions are resolved as well as the jumps that directly follow a `PUSH`.
+
+use super::analyze;
+use crate::{
+    opcode::{self, OpCode},
+    primitives::{hex, legacy::JumpTable, Bytes, U256},
+    OPCODE_INFO_JUMPTABLE, STACK_LIMIT,
+};
+use core::fmt::Write;
+use std::{collections::VecDeque, string::String, vec, vec::Vec};
+
+/// Maximum number of constants tracked for one stack item, more are treated as unknown.
+const MAX_TRACKED_VALUES: usize = 64;
+
+/// Basic block of the legacy bytecode.
+///
+/// Block starts at `JUMPDEST` or after a jump or a terminating instruction, and ends with a
+/// jump, a terminating instruction or before the next `JUMPDEST`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BasicBlock {
+    /// Offset of the first instruction.
+    pub start: usize,
+    /// Offset of the last instruction.
+    pub last: usize,
+    /// Offset after the last instruction and its immediate.
+    pub end: usize,
+    /// Number of stack items the block reads below the stack height at its entry.
+    pub stack_required: usize,
+    /// Change of the stack height from the entry to the exit of the block.
+    pub stack_delta: isize,
+    /// Maximum stack height above the stack height at the entry.
+    pub stack_max_growth: usize,
+    /// Outgoing edges, ordered by the target.
+    pub successors: Vec<Edge>,
+    /// Whether the block is reachable from the entry of the code.
+    pub reachable: bool,
+}
+
+/// Edge between two basic blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Edge {
+    /// Index of the target block.
+    pub target: usize,
+    /// How the target block is entered.
+    pub kind: EdgeKind,
+}
+
+/// Kind of the [`Edge`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EdgeKind {
+    /// Execution continues to the next block, including the not taken `JUMPI`.
+    Fallthrough,
+    /// `JUMP` or taken `JUMPI`.
+    Jump,
+}
+
+/// Solidity-style dispatch of the external function, `PUSH4 selector, EQ, PUSH target, JUMPI`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SelectorDispatch {
+    /// Function selector.
+    pub selector: [u8; 4],
+    /// Offset of the `JUMPI`.
+    pub pc: usize,
+    /// Offset of the function entry.
+    pub target: usize,
+}
+
+/// Control-flow graph of the legacy bytecode, see the [module](self) documentation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ControlFlowGraph {
+    code: Bytes,
+    blocks: Vec<BasicBlock>,
+    dynamic_jumps: Vec<usize>,
+    selectors: Vec<SelectorDispatch>,
+}
+
+impl ControlFlowGraph {
+    /// Builds the graph of the legacy code.
+    ///
+    /// Blocks that are entered only by the unresolved dynamic jumps are not reachable.
+    pub fn new(code: Bytes) -> Self {
+        let mut blocks = split_blocks(&code, |opcode| {
+            is_halt(opcode) || matches!(opcode, opcode::JUMP | opcode::JUMPI)
+        });
+        let jump_table = analyze(&code);
+
+        // entry stacks of the reachable blocks, solved by the iteration to the fixed point.
+        let mut entries: Vec<Option<AbstractStack>> = vec![None; blocks.len()];
+        let mut queue = VecDeque::new();
+        let mut queued = vec![false; blocks.len()];
+        if !blocks.is_empty() {
+            entries[0] = Some(AbstractStack::default());
+            queue.push_back(0);
+            queued[0] = true;
+        }
+        while let Some(idx) = queue.pop_front() {
+            queued[idx] = false;
+            let mut stack = entries[idx].clone().unwrap();
+            let target = execute_block(&code, &blocks[idx], &mut stack);
+            for edge in successors(&blocks, idx, &code, &jump_table, target.as_ref()) {
+                let changed = match &mut entries[edge.target] {
+                    Some(entry) => entry.merge(&stack),
+                    entry @ None => {
+                        *entry = Some(stack.clone());
+                        true
+                    }
+                };
+                if changed && !queued[edge.target] {
+                    queue.push_back(edge.target);
+                    queued[edge.target] = true;
+                }
+            }
+        }
+
+        let mut dynamic_jumps = Vec::new();
+        for idx in 0..blocks.len() {
+            let reachable = entries[idx].is_some();
+            // unreachable blocks are connected by the jumps resolved within the block.
+            let mut stack = entries[idx].take().unwrap_or_default();
+            let target = execute_block(&code, &blocks[idx], &mut stack);
+            if reachable && target == Some(Value::Unknown) {
+                dynamic_jumps.push(blocks[idx].last);
+            }
+            blocks[idx].successors = successors(&blocks, idx, &code, &jump_table, target.as_ref());
+            blocks[idx].reachable = reachable;
+        }
+
+        let selectors = find_selectors(&code, &blocks);
+        Self {
+            code,
+            blocks,
+            dynamic_jumps,
+            selectors,
+        }
+    }
+
+    /// Returns the analysed code.
+    pub fn code(&self) -> &Bytes {
+        &self.code
+    }
+
+    /// Returns the basic blocks, ordered by the offset.
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    /// Returns the index of the block that contains the instruction at `pc`.
+    pub fn block_index(&self, pc: usize) -> Option<usize> {
+        let idx = self.blocks.partition_point(|block| block.start <= pc);
+        idx.checked_sub(1).filter(|idx| pc < self.blocks[*idx].end)
+    }
+
+    /// Returns whether the instruction at `pc` is reachable from the entry of the code.
+    pub fn is_reachable(&self, pc: usize) -> bool {
+        self.block_index(pc)
+            .is_some_and(|idx| self.blocks[idx].reachable)
+    }
+
+    /// Returns the indices of the reachable blocks.
+    pub fn reachable_blocks(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.blocks.len()).filter(|idx| self.blocks[*idx].reachable)
+    }
+
+    /// Returns the indices of the blocks that are not reachable.
+    pub fn dead_blocks(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.blocks.len()).filter(|idx| !self.blocks[*idx].reachable)
+    }
+
+    /// Returns the offsets of the reachable `JUMP` and `JUMPI` instructions with a target
+    /// that could not be resolved.
+    pub fn dynamic_jumps(&self) -> &[usize] {
+        &self.dynamic_jumps
+    }
+
+    /// Returns the detected function selector dispatches, ordered by the offset.
+    pub fn selectors(&self) -> &[SelectorDispatch] {
+        &self.selectors
+    }
+
+    /// Returns the graph in the Graphviz DOT format.
+    ///
+    /// Fallthrough edges are dashed, blocks that are not reachable are gray and blocks ending
+    /// with an unresolved dynamic jump are red.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        dot.push_str("digraph cfg {\n    node [shape=box, fontname=monospace];\n");
+        for (idx, block) in self.blocks.iter().enumerate() {
+            let mut label = String::new();
+            for selector in self.selectors.iter().filter(|s| s.target == block.start) {
+                let _ = write!(label, "selector 0x{}\\l", hex::encode(selector.selector));
+            }
+            for (pc, opcode, immediate) in Instructions::new(&self.code[..block.end], block.start) {
+                let _ = write!(label, "{pc:04x}: {}", mnemonic(opcode));
+                if !immediate.is_empty() {
+                    let _ = write!(label, " 0x{}", hex::encode(immediate));
+                }
+                label.push_str("\\l");
+            }
+            let _ = write!(dot, "    b{idx} [label=\"{label}\"");
+            if !block.reachable {
+                dot.push_str(", color=gray, fontcolor=gray");
+            } else if self.dynamic_jumps.contains(&block.last) {
+                dot.push_str(", color=red");
+            }
+            dot.push_str("];\n");
+        }
+        for (idx, block) in self.blocks.iter().enumerate() {
+            for edge in &block.successors {
+                let _ = write!(dot, "    b{idx} -> b{}", edge.target);
+                if edge.kind == EdgeKind::Fallthrough {
+                    dot.push_str(" [style=dashed]");
+                }
+                dot.push_str(";\n");
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Iterator over the legacy instructions, yielding the offset, the opcode and the immediate.
+///
+/// Immediate of the `PUSH` truncated by the end of the code is shorter than its size.
+pub(crate) struct Instructions<'a> {
+    code: &'a [u8],
+    pc: usize,
+}
+
+impl<'a> Instructions<'a> {
+    /// Creates the iterator over the instructions of `code` starting at `pc`.
+    pub(crate) fn new(code: &'a [u8], pc: usize) -> Self {
+        Self { code, pc }
+    }
+}
+
+impl<'a> Iterator for Instructions<'a> {
+    type Item = (usize, u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pc = self.pc;
+        let opcode = *self.code.get(pc)?;
+        self.pc = pc + 1 + immediate_len(opcode);
+        let end = self.pc.min(self.code.len());
+        Some((pc, opcode, &self.code[pc + 1..end]))
+    }
+}
+
+fn mnemonic(opcode: u8) -> &'static str {
+    match OpCode::new(opcode) {
+        Some(op) => op.as_str(),
+        None => "UNKNOWN",
+    }
+}
+
+/// Returns whether the instruction ends the execution of the legacy code, including the
+/// unknown and the EOF-only instructions.
+fn is_halt(opcode: u8) -> bool {
+    let Some(info) = OPCODE_INFO_JUMPTABLE[opcode as usize] else {
+        return true;
+    };
+    info.is_terminating()
+        || matches!(
+            opcode,
+            opcode::DATALOAD..=opcode::DATACOPY
+                | opcode::RJUMP..=opcode::EXCHANGE
+                | opcode::EOFCREATE
+                | opcode::RETURNDATALOAD..=opcode::EXTDELEGATECALL
+                | opcode::EXTSTATICCALL
+        )
+}
+
+/// Splits the code into the basic blocks and computes their stack requirements.
+///
+/// Block starts at `JUMPDEST` and ends after the instruction for which `ends_block` returns
+/// true. Used by both the graph and the [gas blocks](super::analyze_gas_blocks).
+pub(crate) fn split_blocks(code: &[u8], ends_block: impl Fn(u8) -> bool) -> Vec<BasicBlock> {
+    let mut blocks: Vec<BasicBlock> = Vec::new();
+    let mut block = BasicBlock::default();
+    // stack height relative to the start of the block.
+    let mut height: isize = 0;
+    for (pc, opcode, _) in Instructions::new(code, 0) {
+        if opcode == opcode::JUMPDEST && pc != block.start {
+            blocks.push(block);
+            block = BasicBlock {
+                start: pc,
+                ..Default::default()
+            };
+            height = 0;
+        }
+
+        block.last = pc;
+        block.end = (pc + 1 + immediate_len(opcode)).min(code.len());
+        if let Some(info) = OPCODE_INFO_JUMPTABLE[opcode as usize] {
+            let required = info.inputs() as isize - height;
+            block.stack_required = block.stack_required.max(required.max(0) as usize);
+            height += info.io_diff() as isize;
+            block.stack_max_growth = block.stack_max_growth.max(height.max(0) as usize);
+        }
+        block.stack_delta = height;
+
+        if ends_block(opcode) && block.end < code.len() {
+            blocks.push(block);
+            block = BasicBlock {
+                start: pc + 1 + immediate_len(opcode),
+                ..Default::default()
+            };
+            height = 0;
+        }
+    }
+    if !code.is_empty() {
+        blocks.push(block);
+    }
+    blocks
+}
+
+/// Returns the size of the immediate of the legacy instruction.
+#[inline]
+pub(crate) fn immediate_len(opcode: u8) -> usize {
+    if (opcode::PUSH1..=opcode::PUSH32).contains(&opcode) {
+        (opcode - opcode::PUSH0) as usize
+    } else {
+        0
+    }
+}
+
+/// Returns the outgoing edges of the block, given the jump target at its exit.
+fn successors(
+    blocks: &[BasicBlock],
+    idx: usize,
+    code: &[u8],
+    jump_table: &JumpTable,
+    target: Option<&Value>,
+) -> Vec<Edge> {
+    let block = &blocks[idx];
+    let opcode = code[block.last];
+    let mut edges = Vec::new();
+    if let Some(Value::Known(targets)) = target {
+        for target in targets {
+            let Ok(target) = usize::try_from(*target) else {
+                continue;
+            };
+            if !jump_table.is_valid(target) {
+                continue;
+            }
+            // JUMPDEST always starts a block.
+            if let Ok(target) = blocks.binary_search_by_key(&target, |block| block.start) {
+                edges.push(Edge {
+                    target,
+                    kind: EdgeKind::Jump,
+                });
+            }
+        }
+    }
+    if opcode != opcode::JUMP && !is_halt(opcode) && idx + 1 < blocks.len() {
+        edges.push(Edge {
+            target: idx + 1,
+            kind: EdgeKind::Fallthrough,
+        });
+    }
+    edges.sort_unstable();
+    edges.dedup();
+    edges
+}
+
+/// Finds the `PUSH4 selector, [DUPn], EQ, PUSH target, JUMPI` sequences.
+fn find_selectors(code: &[u8], blocks: &[BasicBlock]) -> Vec<SelectorDispatch> {
+    let mut selectors = Vec::new();
+    for block in blocks {
+        let instructions = Instructions::new(&code[..block.end], block.start).collect::<Vec<_>>();
+        for (i, (_, opcode, immediate)) in instructions.iter().enumerate() {
+            if *opcode != opcode::PUSH4 || immediate.len() != 4 {
+                continue;
+            }
+            let mut rest = &instructions[i + 1..];
+            if let Some((_, opcode::DUP1..=opcode::DUP16, _)) = rest.first() {
+                rest = &rest[1..];
+            }
+            let [(_, opcode::EQ, _), (_, push, target), (pc, opcode::JUMPI, _), ..] = rest else {
+                continue;
+            };
+            if !(opcode::PUSH1..=opcode::PUSH4).contains(push) {
+                continue;
+            }
+            selectors.push(SelectorDispatch {
+                selector: [immediate[0], immediate[1], immediate[2], immediate[3]],
+                pc: *pc,
+                target: U256::from_be_slice(target).to::<usize>(),
+            });
+        }
+    }
+    selectors
+}
+
+/// Abstract value of a stack item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Value {
+    /// One of the constants, sorted.
+    Known(Vec<U256>),
+    /// Any value.
+    Unknown,
+}
+
+impl Value {
+    fn constant(value: U256) -> Self {
+        Self::Known(vec![value])
+    }
+
+    /// Extends the value with the constants of `other`, returns whether it changed.
+    fn merge(&mut self, other: &Self) -> bool {
+        let Self::Known(values) = self else {
+            return false;
+        };
+        let Self::Known(other) = other else {
+            *self = Self::Unknown;
+            return true;
+        };
+        let len = values.len();
+        for value in other {
+            if let Err(idx) = values.binary_search(value) {
+                values.insert(idx, *value);
+            }
+        }
+        if values.len() > MAX_TRACKED_VALUES {
+            *self = Self::Unknown;
+            return true;
+        }
+        values.len() != len
+    }
+}
+
+/// Abstract stack, the items below the tracked ones are unknown.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct AbstractStack(Vec<Value>);
+
+impl AbstractStack {
+    fn push(&mut self, value: Value) {
+        if self.0.len() == STACK_LIMIT {
+            self.0.remove(0);
+        }
+        self.0.push(value);
+    }
+
+    fn pop(&mut self) -> Value {
+        self.0.pop().unwrap_or(Value::Unknown)
+    }
+
+    /// Returns the item at the depth `n`, where zero is the top.
+    fn peek(&self, n: usize) -> Value {
+        self.0
+            .len()
+            .checked_sub(n + 1)
+            .map_or(Value::Unknown, |idx| self.0[idx].clone())
+    }
+
+    /// Swaps the top with the item at the depth `n`.
+    fn swap(&mut self, n: usize) {
+        if self.0.len() <= n {
+            let missing = n + 1 - self.0.len();
+            self.0.splice(0..0, vec![Value::Unknown; missing]);
+        }
+        let top = self.0.len() - 1;
+        self.0.swap(top, top - n);
+    }
+
+    /// Merges the stack at the other entry of the block, aligned by the top.
+    ///
+    /// Returns whether the stack changed.
+    fn merge(&mut self, other: &Self) -> bool {
+        let mut changed = false;
+        if other.0.len() < self.0.len() {
+            self.0.drain(..self.0.len() - other.0.len());
+            changed = true;
+        }
+        let offset = other.0.len() - self.0.len();
+        for (value, other) in self.0.iter_mut().zip(&other.0[offset..]) {
+            changed |= value.merge(other);
+        }
+        changed
+    }
+}
+
+/// Executes the block on the abstract stack, returns the target of the jump at its end.
+fn execute_block(code: &[u8], block: &BasicBlock, stack: &mut AbstractStack) -> Option<Value> {
+    for (_, opcode, immediate) in Instructions::new(&code[..block.end], block.start) {
+        match opcode {
+            opcode::PUSH0..=opcode::PUSH32 => {
+                // truncated value is padded with zeros, as in the padded code.
+                let mut bytes = [0; 32];
+                bytes[..immediate.len()].copy_from_slice(immediate);
+                let len = immediate_len(opcode);
+                stack.push(Value::constant(U256::from_be_slice(&bytes[..len])));
+            }
+            opcode::DUP1..=opcode::DUP16 => {
+                let value = stack.peek((opcode - opcode::DUP1) as usize);
+                stack.push(value);
+            }
+            opcode::SWAP1..=opcode::SWAP16 => stack.swap((opcode - opcode::SWAP1) as usize + 1),
+            opcode::JUMP => return Some(stack.pop()),
+            opcode::JUMPI => {
+                let target = stack.pop();
+                stack.pop();
+                return Some(target);
+            }
+            _ => {
+                // unknown instruction halts.
+                let info = OPCODE_INFO_JUMPTABLE[opcode as usize]?;
+                for _ in 0..info.inputs() {
+                    stack.pop();
+                }
+                for _ in 0..info.outputs() {
+                    stack.push(Value::Unknown);
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dispatch_and_internal_call() {
+        // 0x00: selector = CALLDATALOAD(0) >> 224, JUMPI to 0x14 if it is 0x12345678
+        // 0x10: REVERT(0, 0)
+        // 0x14: call the function at 0x25 with the return address 0x20
+        // 0x1a: dead code, 0x1d: dead JUMPDEST with a dynamic JUMP, 0x1f: dead STOP
+        // 0x20: dynamic JUMP to CALLDATALOAD(0)
+        // 0x25: return from the function
+        let code = Bytes::from_static(&hex!(
+            "600035 60e01c 80 6312345678 14 6014 57 6000 80 fd"
+            "5b 6020 6025 56 6001 00 5b 56 00 5b 6000 35 56 5b 56"
+        ));
+        let cfg = ControlFlowGraph::new(code);
+
+        let starts = cfg.blocks().iter().map(|b| b.start).collect::<Vec<_>>();
+        assert_eq!(starts, [0x00, 0x10, 0x14, 0x1a, 0x1d, 0x1f, 0x20, 0x25]);
+
+        let jump = |target| Edge {
+            target,
+            kind: EdgeKind::Jump,
+        };
+        let fallthrough = |target| Edge {
+            target,
+            kind: EdgeKind::Fallthrough,
+        };
+        assert_eq!(cfg.blocks()[0].successors, [fallthrough(1), jump(2)]);
+        assert_eq!(cfg.blocks()[1].successors, []);
+        assert_eq!(cfg.blocks()[2].successors, [jump(7)]);
+        // return address is resolved through the stack.
+        assert_eq!(cfg.blocks()[7].successors, [jump(6)]);
+        assert_eq!(cfg.blocks()[6].successors, []);
+
+        assert_eq!(cfg.reachable_blocks().collect::<Vec<_>>(), [0, 1, 2, 6, 7]);
+        assert_eq!(cfg.dead_blocks().collect::<Vec<_>>(), [3, 4, 5]);
+        assert!(cfg.is_reachable(0x23));
+        assert!(!cfg.is_reachable(0x1b));
+        assert_eq!(cfg.dynamic_jumps(), [0x24]);
+        assert_eq!(
+            cfg.selectors(),
+            [SelectorDispatch {
+                selector: hex!("12345678"),
+                pc: 0x0f,
+                target: 0x14,
+            }]
+        );
+
+        let block = &cfg.blocks()[0];
+        assert_eq!(
+            (
+                block.stack_required,
+                block.stack_delta,
+                block.stack_max_growth
+            ),
+            (0, 1, 3)
+        );
+        let block = &cfg.blocks()[7];
+        assert_eq!(
+            (
+                block.stack_required,
+                block.stack_delta,
+                block.stack_max_growth
+            ),
+            (1, -1, 0)
+        );
+
+        let dot = cfg.to_dot();
+        assert!(dot.contains("b0 -> b1 [style=dashed];"));
+        assert!(dot.contains("b7 -> b6;"));
+        assert!(dot.contains("selector 0x12345678\\l0014: JUMPDEST\\l"));
+    }
+
+    #[test]
+    fn empty_and_truncated() {
+        let cfg = ControlFlowGraph::new(Bytes::new());
+        assert!(cfg.blocks().is_empty());
+
+        // PUSH1 5, JUMP, truncated PUSH2
+        let cfg = ControlFlowGraph::new(Bytes::from_static(&hex!("60055661")));
+        assert_eq!(cfg.blocks().len(), 2);
+        assert_eq!(cfg.blocks()[1].end, 4);
+        assert!(!cfg.blocks()[1].reachable);
+    }
+}
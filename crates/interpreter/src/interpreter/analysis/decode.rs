@@ -0,0 +1,205 @@
+//! Lowering of the bytecode into the pre-decoded instruction stream.
+
+use super::to_analysed;
+use crate::{
+    opcode,
+    primitives::{Bytecode, DecodedCode, DecodedInstruction, JumpTable, U256},
+    OPCODE_INFO_JUMPTABLE,
+};
+use std::sync::Arc;
+
+/// Perform bytecode analysis and pre-decode the code, see [`decode_legacy`] and
+/// [`decode_eof_code`].
+///
+/// Already decoded bytecode is returned as-is.
+pub fn to_decoded(bytecode: Bytecode) -> Bytecode {
+    match to_analysed(bytecode) {
+        Bytecode::LegacyAnalyzed(analyzed) if analyzed.decoded().is_none() => {
+            let decoded = decode_legacy(analyzed.original_byte_slice(), analyzed.jump_table());
+            Bytecode::LegacyAnalyzed(analyzed.with_decoded(Arc::new(decoded)))
+        }
+        Bytecode::Eof(mut eof) if eof.decoded().is_none() => {
+            let decoded = eof
+                .body
+                .code_section
+                .iter()
+                .map(|code| Arc::new(decode_eof_code(code)))
+                .collect();
+            Arc::make_mut(&mut eof).set_decoded(decoded);
+            Bytecode::Eof(eof)
+        }
+        bytecode => bytecode,
+    }
+}
+
+/// Decodes legacy code.
+///
+/// `JUMP` and `JUMPI` that directly follow a `PUSH` of a valid jump destination are resolved
+/// to the index of the destination. Instruction stream ends with the `STOP` from the padding
+/// of the analysed code.
+pub fn decode_legacy(code: &[u8], jump_table: &JumpTable) -> DecodedCode {
+    let mut decoded = DecodedCode::default();
+    let mut pc = 0;
+    while pc < code.len() {
+        pc = decode_instruction(&mut decoded, code, pc, 0);
+    }
+    decoded.instructions.push(DecodedInstruction::default());
+    decoded.pcs.push(pc as u32);
+
+    for idx in 0..decoded.instructions.len() {
+        let instruction = decoded.instructions[idx];
+        if !is_jump(instruction.opcode) {
+            continue;
+        }
+        let target = idx
+            .checked_sub(1)
+            .map(|prev| decoded.instructions[prev])
+            .filter(|prev| is_push(prev.opcode))
+            .map(|push| decoded.push_values[push.immediate as usize])
+            .and_then(|target| usize::try_from(target).ok())
+            .filter(|target| {
+                matches!(instruction.opcode, opcode::JUMP | opcode::JUMPI)
+                    && jump_table.is_valid(*target)
+            })
+            .and_then(|target| decoded.index_of(target));
+        decoded.instructions[idx].immediate =
+            target.map_or(DecodedInstruction::NO_TARGET, |target| target as u32);
+    }
+    decoded
+}
+
+/// Decodes EOF code section.
+///
+/// `RJUMP` and `RJUMPI` are resolved to the index of the destination.
+pub fn decode_eof_code(code: &[u8]) -> DecodedCode {
+    let mut decoded = DecodedCode::default();
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        let mut immediate_size =
+            OPCODE_INFO_JUMPTABLE[opcode as usize].map_or(0, |info| info.immediate_size() as usize);
+        if opcode == opcode::RJUMPV {
+            immediate_size += code
+                .get(pc + 1)
+                .map_or(0, |max_index| (*max_index as usize + 1) * 2);
+        }
+        pc = decode_instruction(&mut decoded, code, pc, immediate_size);
+    }
+
+    for idx in 0..decoded.instructions.len() {
+        let opcode = decoded.instructions[idx].opcode;
+        if !is_jump(opcode) {
+            continue;
+        }
+        let pc = decoded.pc(idx);
+        let target = matches!(opcode, opcode::RJUMP | opcode::RJUMPI)
+            .then(|| code.get(pc + 1..pc + 3))
+            .flatten()
+            .and_then(|offset| {
+                let offset = i16::from_be_bytes([offset[0], offset[1]]) as isize;
+                (pc as isize).checked_add(3 + offset)
+            })
+            .and_then(|target| decoded.index_of(usize::try_from(target).ok()?));
+        decoded.instructions[idx].immediate =
+            target.map_or(DecodedInstruction::NO_TARGET, |target| target as u32);
+    }
+    decoded
+}
+
+/// Decodes the instruction at `pc` and returns the offset of the next one.
+///
+/// `PUSH` values are read from the code, other immediates of `immediate_size` are skipped.
+fn decode_instruction(
+    decoded: &mut DecodedCode,
+    code: &[u8],
+    pc: usize,
+    immediate_size: usize,
+) -> usize {
+    let opcode = code[pc];
+    let mut instruction = DecodedInstruction {
+        opcode,
+        immediate: 0,
+    };
+    let mut next = pc + 1 + immediate_size;
+    if is_push(opcode) {
+        let len = (opcode - opcode::PUSH0) as usize;
+        // truncated value is padded with zeros, as in the padded code.
+        let mut bytes = [0; 32];
+        let available = code.get(pc + 1..).unwrap_or_default();
+        let read = len.min(available.len());
+        bytes[..read].copy_from_slice(&available[..read]);
+        instruction.immediate = decoded.push_values.len() as u32;
+        decoded.push_values.push(U256::from_be_slice(&bytes[..len]));
+        next = pc + 1 + len;
+    }
+    decoded.instructions.push(instruction);
+    decoded.pcs.push(pc as u32);
+    next
+}
+
+#[inline]
+const fn is_push(opcode: u8) -> bool {
+    matches!(opcode, opcode::PUSH1..=opcode::PUSH32)
+}
+
+/// Returns whether the immediate of the instruction is the index of the target.
+#[inline]
+const fn is_jump(opcode: u8) -> bool {
+    matches!(
+        opcode,
+        opcode::JUMP | opcode::JUMPI | opcode::RJUMP | opcode::RJUMPI
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::primitives::{hex, Bytes};
+    use std::vec::Vec;
+
+    #[test]
+    fn decode_legacy_jumps() {
+        // PUSH1 4, JUMP, INVALID, JUMPDEST, PUSH2 0x0102, PUSH1 1, JUMP, PUSH
+        let bytecode = to_decoded(Bytecode::new_raw(Bytes::from_static(&hex!(
+            "600456fe5b61010260015661"
+        ))));
+        let decoded = bytecode.decoded_code(0).unwrap();
+        let opcodes = decoded
+            .instructions
+            .iter()
+            .map(|i| i.opcode)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            opcodes,
+            [0x60, 0x56, 0xfe, 0x5b, 0x61, 0x60, 0x56, 0x61, 0x00]
+        );
+        assert_eq!(decoded.pcs, [0, 2, 3, 4, 5, 8, 10, 11, 14]);
+        assert_eq!(
+            decoded.push_values,
+            [U256::from(4), U256::from(0x0102), U256::from(1), U256::ZERO]
+        );
+        // resolved to the JUMPDEST, and not resolved to the invalid destination.
+        assert_eq!(decoded.instructions[1].immediate, 3);
+        assert_eq!(
+            decoded.instructions[6].immediate,
+            DecodedInstruction::NO_TARGET
+        );
+        assert_eq!(decoded.index_of(8), Some(5));
+        assert_eq!(decoded.index_of(9), None);
+    }
+
+    #[test]
+    fn decode_eof_rjumps() {
+        // PUSH1 1, RJUMPI +1, STOP, RJUMP -9 (back to PUSH1), RJUMPV with two cases, STOP
+        let decoded = decode_eof_code(&hex!("6001e1000100e0fff7e2010000000000"));
+        let opcodes = decoded
+            .instructions
+            .iter()
+            .map(|i| i.opcode)
+            .collect::<Vec<_>>();
+        assert_eq!(opcodes, [0x60, 0xe1, 0x00, 0xe0, 0xe2, 0x00]);
+        assert_eq!(decoded.pcs, [0, 2, 5, 6, 9, 15]);
+        assert_eq!(decoded.instructions[1].immediate, 3);
+        assert_eq!(decoded.instructions[3].immediate, 0);
+    }
+}
@@ -98,6 +98,7 @@ impl<'de> Deserialize<'de> for Interpreter {
                 return_data_buffer,
                 is_static,
                 next_action,
+                gas_block_charged: false,
             })
         }
 
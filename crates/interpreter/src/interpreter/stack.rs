@@ -209,6 +209,45 @@ impl Stack {
         Ok(())
     }
 
+    /// Pushes a new value onto the stack.
+    ///
+    /// # Safety
+    ///
+    /// The caller is responsible for checking that the stack is not full.
+    #[inline]
+    pub unsafe fn push_unsafe(&mut self, value: U256) {
+        let len = self.data.len();
+        // SAFETY: capacity is `STACK_LIMIT` and the length is checked by the caller.
+        self.data.as_mut_ptr().add(len).write(value);
+        self.data.set_len(len + 1);
+    }
+
+    /// Duplicates the `N`th value from the top of the stack.
+    ///
+    /// # Safety
+    ///
+    /// The caller is responsible for checking that the stack has at least `n` items and is not
+    /// full.
+    #[inline]
+    pub unsafe fn dup_unsafe(&mut self, n: usize) {
+        let len = self.data.len();
+        let ptr = self.data.as_mut_ptr().add(len);
+        ptr::copy_nonoverlapping(ptr.sub(n), ptr, 1);
+        self.data.set_len(len + 1);
+    }
+
+    /// Swaps the topmost value with the `N`th value from the top.
+    ///
+    /// # Safety
+    ///
+    /// The caller is responsible for checking that the stack has more than `n` items and that
+    /// `n` is not zero.
+    #[inline]
+    pub unsafe fn swap_unsafe(&mut self, n: usize) {
+        let top = self.data.as_mut_ptr().add(self.data.len() - 1);
+        core::ptr::swap_nonoverlapping(top, top.sub(n), 1);
+    }
+
     /// Peek a value at given index for the stack, where the top of
     /// the stack is at index `0`. If the index is too large,
     /// `StackError::Underflow` is returned.
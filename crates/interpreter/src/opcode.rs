@@ -1,5 +1,8 @@
 //! EVM opcode definitions and utilities.
 
+#[cfg(feature = "parse")]
+pub mod assembler;
+pub mod disassembler;
 pub mod eof_printer;
 
 mod tables;
@@ -0,0 +1,305 @@
+//! Assembler of the legacy bytecode.
+
+use super::{OpCode, PUSH1, PUSH2};
+use crate::primitives::{hex, Bytes, HashMap, U256};
+use core::fmt;
+use std::{string::String, string::ToString, vec::Vec};
+
+/// Error of the [`assemble`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssembleError {
+    /// Line of the error, starting from one.
+    pub line: usize,
+    /// Kind of the error.
+    pub kind: AssembleErrorKind,
+}
+
+/// Kind of the [`AssembleError`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssembleErrorKind {
+    /// Mnemonic is not a known opcode or directive.
+    UnknownMnemonic(String),
+    /// `PUSH` or `.data` without the immediate.
+    MissingImmediate,
+    /// Immediate of an instruction that does not take one, or more than one immediate.
+    UnexpectedImmediate(String),
+    /// Immediate is not a number, a label or hex data.
+    InvalidImmediate(String),
+    /// Immediate does not fit into the `PUSH`.
+    ImmediateTooLarge(String),
+    /// Label is not an identifier of letters, digits and underscores.
+    InvalidLabel(String),
+    /// Label is defined more than once.
+    DuplicateLabel(String),
+    /// Label is not defined.
+    UndefinedLabel(String),
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl fmt::Display for AssembleErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownMnemonic(s) => write!(f, "unknown mnemonic `{s}`"),
+            Self::MissingImmediate => f.write_str("missing immediate"),
+            Self::UnexpectedImmediate(s) => write!(f, "unexpected immediate `{s}`"),
+            Self::InvalidImmediate(s) => write!(f, "invalid immediate `{s}`"),
+            Self::ImmediateTooLarge(s) => write!(f, "immediate `{s}` does not fit"),
+            Self::InvalidLabel(s) => write!(f, "invalid label `{s}`"),
+            Self::DuplicateLabel(s) => write!(f, "duplicate label `{s}`"),
+            Self::UndefinedLabel(s) => write!(f, "undefined label `{s}`"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AssembleError {}
+
+/// Assembles the legacy code from the mnemonic text.
+///
+/// Every line holds an optional label definition, an instruction and a comment:
+///
+/// ```text
+/// ; comments start with `;` or `//`
+///         PUSH1 3         // immediates are hex `0x..` or decimal numbers
+/// loop:   JUMPDEST
+///         PUSH 1          // `PUSH` without the size takes the smallest one
+///         SWAP1
+///         SUB
+///         DUP1
+///         PUSH loop       // labels are pushed with `PUSH2` unless the size is given
+///         JUMPI
+///         .data 0xfe      // raw bytes
+/// ```
+///
+/// Mnemonics are case-insensitive, and a leading offset like `0x0000` is ignored, so the output
+/// of [`disassemble`](super::disassembler::disassemble) is accepted as well.
+pub fn assemble(source: &str) -> Result<Bytes, AssembleError> {
+    let mut items = Vec::new();
+    let mut labels: HashMap<&str, usize> = HashMap::default();
+    let mut offset = 0;
+    for (idx, line) in source.lines().enumerate() {
+        let line_number = idx + 1;
+        let error = |kind| AssembleError {
+            line: line_number,
+            kind,
+        };
+        let line = line.split(';').next().unwrap_or_default();
+        let line = line.split("//").next().unwrap_or_default();
+        let mut tokens = line.split_whitespace().peekable();
+
+        // leading offset of the disassembly.
+        if tokens.peek().is_some_and(|token| token.starts_with("0x")) {
+            tokens.next();
+        }
+        while let Some(label) = tokens.peek().and_then(|token| token.strip_suffix(':')) {
+            if !is_label(label) {
+                return Err(error(AssembleErrorKind::InvalidLabel(label.to_string())));
+            }
+            if labels.insert(label, offset).is_some() {
+                return Err(error(AssembleErrorKind::DuplicateLabel(label.to_string())));
+            }
+            tokens.next();
+        }
+        let Some(mnemonic) = tokens.next() else {
+            continue;
+        };
+        let immediate = tokens.next();
+        if let Some(extra) = tokens.next() {
+            return Err(error(AssembleErrorKind::UnexpectedImmediate(
+                extra.to_string(),
+            )));
+        }
+
+        let item = parse_item(mnemonic, immediate).map_err(error)?;
+        offset += item.len();
+        items.push((line_number, item));
+    }
+
+    let mut code = Vec::with_capacity(offset);
+    for (line, item) in items {
+        let error = |kind| AssembleError { line, kind };
+        match item {
+            Item::Opcode(opcode) => code.push(opcode),
+            Item::Push(size, immediate) => {
+                let value = match immediate {
+                    Immediate::Value(value) => value,
+                    Immediate::Label(label) => match labels.get(label) {
+                        Some(offset) => U256::from(*offset),
+                        None => {
+                            return Err(error(AssembleErrorKind::UndefinedLabel(label.to_string())))
+                        }
+                    },
+                };
+                if value.byte_len() > size {
+                    return Err(error(AssembleErrorKind::ImmediateTooLarge(
+                        value.to_string(),
+                    )));
+                }
+                code.push(PUSH1 - 1 + size as u8);
+                code.extend_from_slice(&value.to_be_bytes::<32>()[32 - size..]);
+            }
+            Item::Data(data) => code.extend_from_slice(&data),
+        }
+    }
+    Ok(code.into())
+}
+
+/// Assembled item of one line.
+enum Item<'a> {
+    Opcode(u8),
+    /// `PUSH` of the size.
+    Push(usize, Immediate<'a>),
+    Data(Vec<u8>),
+}
+
+impl Item<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Self::Opcode(_) => 1,
+            Self::Push(size, _) => 1 + size,
+            Self::Data(data) => data.len(),
+        }
+    }
+}
+
+enum Immediate<'a> {
+    Value(U256),
+    Label(&'a str),
+}
+
+fn parse_item<'a>(
+    mnemonic: &str,
+    immediate: Option<&'a str>,
+) -> Result<Item<'a>, AssembleErrorKind> {
+    if mnemonic == ".data" {
+        let data = immediate.ok_or(AssembleErrorKind::MissingImmediate)?;
+        let bytes = data
+            .strip_prefix("0x")
+            .and_then(|data| hex::decode(data).ok())
+            .ok_or_else(|| AssembleErrorKind::InvalidImmediate(data.to_string()))?;
+        return Ok(Item::Data(bytes));
+    }
+
+    let mnemonic = mnemonic.to_ascii_uppercase();
+    let size = if mnemonic == "PUSH" {
+        None
+    } else {
+        let opcode = OpCode::parse(&mnemonic)
+            .ok_or_else(|| AssembleErrorKind::UnknownMnemonic(mnemonic.clone()))?;
+        if !opcode.is_push() {
+            return match immediate {
+                Some(immediate) => Err(AssembleErrorKind::UnexpectedImmediate(
+                    immediate.to_string(),
+                )),
+                None => Ok(Item::Opcode(opcode.get())),
+            };
+        }
+        Some(opcode.info().immediate_size() as usize)
+    };
+
+    let text = immediate.ok_or(AssembleErrorKind::MissingImmediate)?;
+    let immediate = if is_label(text) {
+        Immediate::Label(text)
+    } else {
+        let value = match text.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16),
+            None => U256::from_str_radix(text, 10),
+        };
+        Immediate::Value(value.map_err(|_| AssembleErrorKind::InvalidImmediate(text.to_string()))?)
+    };
+    let size = size.unwrap_or(match immediate {
+        Immediate::Value(value) => value.byte_len().max(1),
+        Immediate::Label(_) => (PUSH2 - PUSH1 + 1) as usize,
+    });
+    Ok(Item::Push(size, immediate))
+}
+
+/// Returns whether the text is a label, an identifier of letters, digits and underscores.
+fn is_label(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcode::disassembler::disassemble;
+
+    #[test]
+    fn assemble_labels() {
+        let code = assemble(
+            "
+            ; count down from 3
+                    PUSH1 3
+            loop:   JUMPDEST
+                    push 1          // lower case
+                    SWAP1
+                    SUB
+                    DUP1
+                    PUSH loop
+                    JUMPI
+                    PUSH32 0xff
+                    .data 0xfe00
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            code,
+            hex!("6003 5b 6001 90 03 80 610002 57 7f00000000000000000000000000000000000000000000000000000000000000ff fe00")[..]
+        );
+    }
+
+    #[test]
+    fn assemble_errors() {
+        let err = |source: &str| assemble(source).unwrap_err();
+        assert_eq!(
+            err("STOP\nFOO"),
+            AssembleError {
+                line: 2,
+                kind: AssembleErrorKind::UnknownMnemonic("FOO".to_string()),
+            }
+        );
+        assert_eq!(err("PUSH1").kind, AssembleErrorKind::MissingImmediate);
+        assert_eq!(
+            err("ADD 1").kind,
+            AssembleErrorKind::UnexpectedImmediate("1".to_string())
+        );
+        assert_eq!(
+            err("PUSH1 0x100").kind,
+            AssembleErrorKind::ImmediateTooLarge("256".to_string())
+        );
+        assert_eq!(
+            err("a: STOP\na: STOP").kind,
+            AssembleErrorKind::DuplicateLabel("a".to_string())
+        );
+        assert_eq!(
+            err("PUSH a").kind,
+            AssembleErrorKind::UndefinedLabel("a".to_string())
+        );
+        assert_eq!(
+            err("PUSH 0xzz").kind,
+            AssembleErrorKind::InvalidImmediate("0xzz".to_string())
+        );
+    }
+
+    #[test]
+    fn disassemble_round_trip() {
+        let code = hex!(
+            "6080604052348015600f57600080fd5b50603e80601d6000396000f3fe"
+            "0c61"
+            "a2646970667358221220"
+            "0000000000000000000000000000000000000000000000000000000000000001"
+            "64736f6c634300081400"
+            "33"
+        );
+        assert_eq!(assemble(&disassemble(&code)).unwrap(), code[..]);
+    }
+}
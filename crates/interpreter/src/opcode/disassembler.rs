@@ -0,0 +1,224 @@
+//! Disassembler of the legacy bytecode.
+//!
+//! Output can be assembled back into the same bytecode with
+//! [`assemble`](super::assembler::assemble).
+
+use super::{OpCode, JUMPDEST};
+use crate::{
+    interpreter::analysis::cfg::{immediate_len, Instructions},
+    primitives::hex,
+};
+use core::fmt::Write;
+use std::{format, string::String, vec::Vec};
+
+/// Disassembles the legacy code.
+///
+/// Every instruction is printed on its own line with its offset and immediate, and every
+/// `JUMPDEST` is preceded by a label, e.g.:
+///
+/// ```text
+/// 0x0000  PUSH1 0x04  ; L_0004
+/// 0x0002  JUMP
+/// 0x0003  INVALID
+/// L_0004:
+/// 0x0004  JUMPDEST
+/// ```
+///
+/// Immediates that are the offset of a `JUMPDEST` are annotated with its label. Unknown opcodes
+/// and the `PUSH` truncated by the end of the code are printed as `.data`, so is the Solidity
+/// CBOR metadata at the end of the code, followed by its decoded entries.
+pub fn disassemble(code: &[u8]) -> String {
+    let (code, metadata) = split_metadata(code);
+    let jumpdests = jumpdests(code);
+    let label = |pc: usize| format!("L_{pc:04x}");
+
+    let mut out = String::new();
+    for (pc, op, immediate) in Instructions::new(code, 0) {
+        let Some(opcode) = OpCode::new(op) else {
+            let _ = writeln!(out, "0x{pc:04x}  .data 0x{op:02x}  ; unknown opcode");
+            continue;
+        };
+        if immediate.len() < immediate_len(op) {
+            let _ = writeln!(
+                out,
+                "0x{pc:04x}  .data 0x{}  ; truncated {opcode}",
+                hex::encode(&code[pc..])
+            );
+            break;
+        }
+
+        if op == JUMPDEST {
+            let _ = writeln!(out, "{}:", label(pc));
+        }
+        let _ = write!(out, "0x{pc:04x}  {opcode}");
+        if !immediate.is_empty() {
+            let _ = write!(out, " 0x{}", hex::encode(immediate));
+            let target = immediate.iter().try_fold(0usize, |acc, byte| {
+                acc.checked_mul(256)?.checked_add(*byte as usize)
+            });
+            if let Some(target) = target.filter(|target| jumpdests.binary_search(target).is_ok()) {
+                let _ = write!(out, "  ; {}", label(target));
+            }
+        }
+        out.push('\n');
+    }
+
+    if let Some(metadata) = metadata {
+        let _ = writeln!(out, "; metadata");
+        let _ = writeln!(
+            out,
+            "0x{:04x}  .data 0x{}",
+            code.len(),
+            hex::encode(metadata)
+        );
+        let map = &metadata[..metadata.len() - 2];
+        for (key, value) in metadata_entries(map).unwrap_or_default() {
+            let _ = writeln!(out, ";   {key}: {value}");
+        }
+    }
+    out
+}
+
+/// Splits the Solidity CBOR metadata from the end of the legacy code.
+///
+/// Metadata is a CBOR map followed by its length in two big-endian bytes, which are included
+/// in the returned metadata. Code is split only if the map is decoded and takes exactly the
+/// given length.
+pub fn split_metadata(code: &[u8]) -> (&[u8], Option<&[u8]>) {
+    let Some(len_offset) = code.len().checked_sub(2) else {
+        return (code, None);
+    };
+    let len = u16::from_be_bytes([code[len_offset], code[len_offset + 1]]) as usize;
+    match len_offset.checked_sub(len) {
+        Some(start) if len != 0 && metadata_entries(&code[start..len_offset]).is_some() => {
+            let (code, metadata) = code.split_at(start);
+            (code, Some(metadata))
+        }
+        _ => (code, None),
+    }
+}
+
+/// Returns the sorted offsets of the `JUMPDEST` instructions.
+fn jumpdests(code: &[u8]) -> Vec<usize> {
+    Instructions::new(code, 0)
+        .filter(|(_, op, _)| *op == JUMPDEST)
+        .map(|(pc, _, _)| pc)
+        .collect()
+}
+
+/// Decodes the entries of the CBOR metadata map, with text keys and byte string, text or
+/// boolean values. Returns `None` if the metadata is not exactly such a map.
+fn metadata_entries(map: &[u8]) -> Option<Vec<(String, String)>> {
+    let mut reader = CborReader(map);
+    let (5, len) = reader.header()? else {
+        return None;
+    };
+    let mut entries = Vec::new();
+    for _ in 0..len {
+        let (3, key_len) = reader.header()? else {
+            return None;
+        };
+        let key = core::str::from_utf8(reader.take(key_len)?).ok()?;
+        let value = match reader.header()? {
+            // solc version is encoded as major, minor and patch bytes.
+            (2, 3) if key == "solc" => {
+                let version = reader.take(3)?;
+                format!("{}.{}.{}", version[0], version[1], version[2])
+            }
+            (2, len) => format!("0x{}", hex::encode(reader.take(len)?)),
+            (3, len) => String::from(core::str::from_utf8(reader.take(len)?).ok()?),
+            (7, 20) => String::from("false"),
+            (7, 21) => String::from("true"),
+            _ => return None,
+        };
+        entries.push((String::from(key), value));
+    }
+    reader.0.is_empty().then_some(entries)
+}
+
+/// Reader of the subset of CBOR that is used in the metadata.
+struct CborReader<'a>(&'a [u8]);
+
+impl<'a> CborReader<'a> {
+    /// Reads the major type and the argument of the next item.
+    fn header(&mut self) -> Option<(u8, usize)> {
+        let (&initial, rest) = self.0.split_first()?;
+        self.0 = rest;
+        let argument = match initial & 0x1f {
+            argument @ 0..=23 => argument as usize,
+            24 => *self.take(1)?.first()? as usize,
+            25 => {
+                let bytes = self.take(2)?;
+                u16::from_be_bytes([bytes[0], bytes[1]]) as usize
+            }
+            _ => return None,
+        };
+        Some((initial >> 5, argument))
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.0.len() {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(taken)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disassemble_jumps() {
+        // PUSH1 4, JUMP, INVALID, JUMPDEST, unknown opcode, truncated PUSH2
+        let text = disassemble(&hex!("600456fe5b0c610a"));
+        assert_eq!(
+            text,
+            "0x0000  PUSH1 0x04  ; L_0004\n\
+             0x0002  JUMP\n\
+             0x0003  INVALID\n\
+             L_0004:\n\
+             0x0004  JUMPDEST\n\
+             0x0005  .data 0x0c  ; unknown opcode\n\
+             0x0006  .data 0x610a  ; truncated PUSH2\n"
+        );
+    }
+
+    #[test]
+    fn disassemble_metadata() {
+        // STOP, and the metadata with the ipfs hash and the solc version.
+        let code = hex!(
+            "00"
+            "a2646970667358221220"
+            "0000000000000000000000000000000000000000000000000000000000000001"
+            "64736f6c634300081400"
+            "33"
+        );
+        let (body, metadata) = split_metadata(&code);
+        assert_eq!(body, [0x00]);
+        assert_eq!(metadata.unwrap().len(), 0x35);
+
+        let text = disassemble(&code);
+        assert!(text.starts_with("0x0000  STOP\n; metadata\n0x0001  .data 0xa264"));
+        assert!(text.contains(
+            ";   ipfs: 0x12200000000000000000000000000000000000000000000000000000000000000001\n"
+        ));
+        assert!(text.ends_with(";   solc: 0.8.20\n"));
+
+        assert_eq!(split_metadata(&hex!("6001")), (&hex!("6001")[..], None));
+        // ends like the metadata length, but the map is not valid or does not take the length.
+        for code in [
+            &hex!("00a1ff0003") as &[u8],
+            &hex!("00a0000003"),
+            &hex!("00a0a00002"),
+        ] {
+            assert_eq!(split_metadata(code), (code, None));
+        }
+        assert_eq!(
+            split_metadata(&hex!("00a00001")).1,
+            Some(&hex!("a00001")[..])
+        );
+    }
+}
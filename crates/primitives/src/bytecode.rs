@@ -1,11 +1,18 @@
+mod decoded;
+mod eip7702;
 pub mod eof;
 pub mod legacy;
 
+pub use decoded::{DecodedCode, DecodedInstruction};
+pub use eip7702::{
+    Eip7702Bytecode, Eip7702DecodeError, EIP7702_BYTECODE_LEN, EIP7702_MAGIC, EIP7702_MAGIC_BYTES,
+    EIP7702_VERSION,
+};
 pub use eof::{Eof, EOF_MAGIC, EOF_MAGIC_BYTES, EOF_MAGIC_HASH};
-pub use legacy::{JumpTable, LegacyAnalyzedBytecode};
+pub use legacy::{GasBlock, GasBlocks, JumpTable, LegacyAnalyzedBytecode};
 use std::sync::Arc;
 
-use crate::{keccak256, Bytes, B256, KECCAK_EMPTY};
+use crate::{keccak256, Address, Bytes, B256, KECCAK_EMPTY};
 
 /// State of the [`Bytecode`] analysis.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -17,6 +24,8 @@ pub enum Bytecode {
     LegacyAnalyzed(LegacyAnalyzedBytecode),
     /// Ethereum Object Format
     Eof(Arc<Eof>),
+    /// EIP-7702 delegation designator.
+    Eip7702(Eip7702Bytecode),
 }
 
 impl Default for Bytecode {
@@ -43,6 +52,26 @@ impl Bytecode {
         }
     }
 
+    /// Return basic blocks if bytecode is analyzed with them.
+    #[inline]
+    pub fn legacy_gas_blocks(&self) -> Option<&GasBlocks> {
+        match &self {
+            Self::LegacyAnalyzed(analyzed) => analyzed.gas_blocks(),
+            _ => None,
+        }
+    }
+
+    /// Return the pre-decoded code of the legacy bytecode, or of the EOF code section
+    /// at `code_idx`, if bytecode is decoded.
+    #[inline]
+    pub fn decoded_code(&self, code_idx: usize) -> Option<&Arc<DecodedCode>> {
+        match &self {
+            Self::LegacyAnalyzed(analyzed) => analyzed.decoded(),
+            Self::Eof(eof) => eof.decoded()?.get(code_idx),
+            _ => None,
+        }
+    }
+
     /// Calculate hash of the bytecode.
     pub fn hash_slow(&self) -> B256 {
         if self.is_empty() {
@@ -67,12 +96,41 @@ impl Bytecode {
         matches!(self, Self::Eof(_))
     }
 
+    /// Return reference to the EIP-7702 delegation designator if bytecode is one.
+    #[inline]
+    pub const fn eip7702(&self) -> Option<&Eip7702Bytecode> {
+        match self {
+            Self::Eip7702(eip7702) => Some(eip7702),
+            _ => None,
+        }
+    }
+
+    /// Return true if bytecode is EIP-7702 delegation designator.
+    #[inline]
+    pub const fn is_eip7702(&self) -> bool {
+        matches!(self, Self::Eip7702(_))
+    }
+
     /// Creates a new raw [`Bytecode`].
+    ///
+    /// Bytes that form a valid EIP-7702 delegation designator are decoded as
+    /// [`Bytecode::Eip7702`], everything else is treated as raw legacy bytecode.
     #[inline]
     pub fn new_raw(bytecode: Bytes) -> Self {
+        if bytecode.starts_with(&EIP7702_MAGIC_BYTES) {
+            if let Ok(eip7702) = Eip7702Bytecode::new_raw(bytecode.clone()) {
+                return Self::Eip7702(eip7702);
+            }
+        }
         Self::LegacyRaw(bytecode)
     }
 
+    /// Creates a new EIP-7702 [`Bytecode`] that delegates to the given address.
+    #[inline]
+    pub fn new_eip7702(address: Address) -> Self {
+        Self::Eip7702(Eip7702Bytecode::new(address))
+    }
+
     /// Create new checked bytecode.
     ///
     /// # Safety
@@ -103,6 +161,7 @@ impl Bytecode {
                 .body
                 .code(0)
                 .expect("Valid EOF has at least one code section"),
+            Self::Eip7702(eip7702) => eip7702.raw(),
         }
     }
 
@@ -118,6 +177,7 @@ impl Bytecode {
             Self::LegacyRaw(bytes) => bytes.clone(),
             Self::LegacyAnalyzed(analyzed) => analyzed.bytecode().clone(),
             Self::Eof(eof) => eof.raw().clone(),
+            Self::Eip7702(eip7702) => eip7702.raw().clone(),
         }
     }
 
@@ -128,6 +188,7 @@ impl Bytecode {
             Self::LegacyRaw(bytes) => bytes,
             Self::LegacyAnalyzed(analyzed) => analyzed.bytecode(),
             Self::Eof(eof) => eof.raw(),
+            Self::Eip7702(eip7702) => eip7702.raw(),
         }
     }
 
@@ -138,6 +199,7 @@ impl Bytecode {
             Self::LegacyRaw(bytes) => bytes.clone(),
             Self::LegacyAnalyzed(analyzed) => analyzed.original_bytes(),
             Self::Eof(eof) => eof.raw().clone(),
+            Self::Eip7702(eip7702) => eip7702.raw().clone(),
         }
     }
 
@@ -148,6 +210,7 @@ impl Bytecode {
             Self::LegacyRaw(bytes) => bytes,
             Self::LegacyAnalyzed(analyzed) => analyzed.original_byte_slice(),
             Self::Eof(eof) => eof.raw(),
+            Self::Eip7702(eip7702) => eip7702.raw(),
         }
     }
 
@@ -158,6 +221,7 @@ impl Bytecode {
             Self::LegacyRaw(bytes) => bytes.len(),
             Self::LegacyAnalyzed(analyzed) => analyzed.original_len(),
             Self::Eof(eof) => eof.size(),
+            Self::Eip7702(eip7702) => eip7702.raw().len(),
         }
     }
 
@@ -190,4 +254,37 @@ mod tests {
             panic!("Original bytecode is not Eof");
         }
     }
+
+    #[test]
+    fn derived_analysis_is_not_compared() {
+        let analyzed = LegacyAnalyzedBytecode::default();
+        let with_blocks = analyzed
+            .clone()
+            .with_gas_blocks(GasBlocks::default())
+            .with_decoded(Arc::new(DecodedCode::default()));
+        assert_eq!(analyzed, with_blocks);
+        let set: crate::HashSet<_> = [Bytecode::LegacyAnalyzed(analyzed)].into_iter().collect();
+        assert!(set.contains(&Bytecode::LegacyAnalyzed(with_blocks)));
+
+        let mut eof = Eof::default();
+        let plain = eof.clone();
+        eof.set_decoded(vec![Arc::new(DecodedCode::default())]);
+        assert!(eof.decoded().is_some());
+        assert_eq!(eof, plain);
+    }
+
+    #[test]
+    fn new_raw_detects_eip7702() {
+        let address = Address::new([0x01; 20]);
+        let designator = Bytecode::new_eip7702(address);
+        let bytecode = Bytecode::new_raw(designator.original_bytes());
+        assert_eq!(bytecode, designator);
+        assert_eq!(bytecode.eip7702().map(|b| b.address()), Some(address));
+        assert_eq!(bytecode.len(), 23);
+
+        // version other than zero is not a delegation designator.
+        let mut raw = designator.original_bytes().to_vec();
+        raw[2] = 1;
+        assert!(!Bytecode::new_raw(raw.into()).is_eip7702());
+    }
 }
@@ -0,0 +1,50 @@
+use crate::U256;
+use std::vec::Vec;
+
+/// Instruction of the [`DecodedCode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DecodedInstruction {
+    /// Opcode of the instruction, its handler is looked up in the instruction table.
+    pub opcode: u8,
+    /// Immediate decoded ahead of the execution, its meaning depends on the opcode:
+    ///
+    /// * `PUSH1`-`PUSH32`: index of the value in [`DecodedCode::push_values`].
+    /// * `JUMP`, `JUMPI`, `RJUMP` and `RJUMPI`: index of the target instruction, or
+    ///   [`DecodedInstruction::NO_TARGET`] if the target is not known ahead of the execution.
+    /// * Unused for other opcodes.
+    pub immediate: u32,
+}
+
+impl DecodedInstruction {
+    /// Immediate of the jump with the target that is not known ahead of the execution.
+    pub const NO_TARGET: u32 = u32::MAX;
+}
+
+/// Code lowered into a stream of instructions with the immediates decoded ahead of the
+/// execution.
+///
+/// The byte offset of every instruction is kept in [`DecodedCode::pcs`], so the program
+/// counter can be restored for the inspectors and the instructions that read the code.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DecodedCode {
+    /// Instructions in the code order.
+    pub instructions: Vec<DecodedInstruction>,
+    /// Values of the `PUSH` instructions, widened to [`U256`].
+    pub push_values: Vec<U256>,
+    /// Byte offset of every instruction, in the code order.
+    pub pcs: Vec<u32>,
+}
+
+impl DecodedCode {
+    /// Returns the index of the instruction at the byte offset `pc`.
+    #[inline]
+    pub fn index_of(&self, pc: usize) -> Option<usize> {
+        self.pcs.binary_search(&(pc as u32)).ok()
+    }
+
+    /// Returns the byte offset of the instruction at `index`.
+    #[inline]
+    pub fn pc(&self, index: usize) -> usize {
+        self.pcs[index] as usize
+    }
+}
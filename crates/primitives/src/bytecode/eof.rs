@@ -7,9 +7,12 @@ pub use body::EofBody;
 pub use header::EofHeader;
 pub use types_section::TypesSection;
 
-use crate::{b256, bytes, Bytes, B256};
-use core::cmp::min;
-use std::{vec, vec::Vec};
+use crate::{b256, bytes, Bytes, DecodedCode, B256};
+use core::{
+    cmp::min,
+    hash::{Hash, Hasher},
+};
+use std::{sync::Arc, vec, vec::Vec};
 
 /// Hash of EF00 bytes that is used for EXTCODEHASH when called from legacy bytecode.
 pub const EOF_MAGIC_HASH: B256 =
@@ -28,12 +31,34 @@ pub static EOF_MAGIC_BYTES: Bytes = bytes!("ef00");
 ///
 /// If there is a need to create new EOF from scratch, it is recommended to use `EofBody` and
 /// use `encode` function to create full [`Eof`] object.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+///
+/// Pre-decoded code sections are derived from the code, so they are not compared, hashed or
+/// serialized.
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Eof {
     pub header: EofHeader,
     pub body: EofBody,
     pub raw: Bytes,
+    /// Pre-decoded code sections, if decoded.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    decoded: Option<Vec<Arc<DecodedCode>>>,
+}
+
+impl PartialEq for Eof {
+    fn eq(&self, other: &Self) -> bool {
+        self.header == other.header && self.body == other.body && self.raw == other.raw
+    }
+}
+
+impl Eq for Eof {}
+
+impl Hash for Eof {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.header.hash(state);
+        self.body.hash(state);
+        self.raw.hash(state);
+    }
 }
 
 impl Default for Eof {
@@ -78,6 +103,16 @@ impl Eof {
         &self.body.data_section
     }
 
+    /// Returns the pre-decoded code sections, if decoded.
+    pub fn decoded(&self) -> Option<&[Arc<DecodedCode>]> {
+        self.decoded.as_deref()
+    }
+
+    /// Sets the pre-decoded code sections, one for every code section.
+    pub fn set_decoded(&mut self, decoded: Vec<Arc<DecodedCode>>) {
+        self.decoded = Some(decoded);
+    }
+
     /// Slow encode EOF bytes.
     pub fn encode_slow(&self) -> Bytes {
         let mut buffer: Vec<u8> = Vec::with_capacity(self.size());
@@ -101,6 +136,7 @@ impl Eof {
                 header,
                 body,
                 raw: eof,
+                decoded: None,
             },
             dangling_data,
         ))
@@ -110,7 +146,12 @@ impl Eof {
     pub fn decode(raw: Bytes) -> Result<Self, EofDecodeError> {
         let (header, _) = EofHeader::decode(&raw)?;
         let body = EofBody::decode(&raw, &header)?;
-        Ok(Self { header, body, raw })
+        Ok(Self {
+            header,
+            body,
+            raw,
+            decoded: None,
+        })
     }
 }
 
@@ -43,6 +43,7 @@ impl EofBody {
             header,
             body: self,
             raw: buffer.into(),
+            decoded: None,
         }
     }
 
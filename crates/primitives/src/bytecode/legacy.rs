@@ -1,13 +1,19 @@
+mod gas_blocks;
 mod jump_map;
 
+pub use gas_blocks::{GasBlock, GasBlocks};
 pub use jump_map::JumpTable;
 
-use crate::Bytes;
+use crate::{Bytes, DecodedCode};
 use bitvec::{bitvec, order::Lsb0};
+use core::hash::{Hash, Hasher};
 use std::sync::Arc;
 
 /// Legacy analyzed
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+///
+/// Gas blocks and the pre-decoded code are derived from the code, so they are not compared,
+/// hashed or serialized.
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LegacyAnalyzedBytecode {
     /// Bytecode with 32 zero bytes padding.
@@ -16,6 +22,30 @@ pub struct LegacyAnalyzedBytecode {
     original_len: usize,
     /// Jump table.
     jump_table: JumpTable,
+    /// Basic blocks with precomputed static gas, if analysed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    gas_blocks: Option<GasBlocks>,
+    /// Pre-decoded code, if decoded.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    decoded: Option<Arc<DecodedCode>>,
+}
+
+impl PartialEq for LegacyAnalyzedBytecode {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytecode == other.bytecode
+            && self.original_len == other.original_len
+            && self.jump_table == other.jump_table
+    }
+}
+
+impl Eq for LegacyAnalyzedBytecode {}
+
+impl Hash for LegacyAnalyzedBytecode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bytecode.hash(state);
+        self.original_len.hash(state);
+        self.jump_table.hash(state);
+    }
 }
 
 impl Default for LegacyAnalyzedBytecode {
@@ -25,6 +55,8 @@ impl Default for LegacyAnalyzedBytecode {
             bytecode: Bytes::from_static(&[0]),
             original_len: 0,
             jump_table: JumpTable(Arc::new(bitvec![u8, Lsb0; 0])),
+            gas_blocks: None,
+            decoded: None,
         }
     }
 }
@@ -36,9 +68,23 @@ impl LegacyAnalyzedBytecode {
             bytecode,
             original_len,
             jump_table,
+            gas_blocks: None,
+            decoded: None,
         }
     }
 
+    /// Sets the pre-decoded code of the bytecode.
+    pub fn with_decoded(mut self, decoded: Arc<DecodedCode>) -> Self {
+        self.decoded = Some(decoded);
+        self
+    }
+
+    /// Sets the basic blocks of the bytecode.
+    pub fn with_gas_blocks(mut self, gas_blocks: GasBlocks) -> Self {
+        self.gas_blocks = Some(gas_blocks);
+        self
+    }
+
     /// Returns a reference to the bytecode.
     ///
     /// The bytecode is padded with 32 zero bytes.
@@ -65,4 +111,14 @@ impl LegacyAnalyzedBytecode {
     pub fn jump_table(&self) -> &JumpTable {
         &self.jump_table
     }
+
+    /// Basic blocks of analyzed bytes, if analysed.
+    pub fn gas_blocks(&self) -> Option<&GasBlocks> {
+        self.gas_blocks.as_ref()
+    }
+
+    /// Pre-decoded code, if decoded.
+    pub fn decoded(&self) -> Option<&Arc<DecodedCode>> {
+        self.decoded.as_ref()
+    }
 }
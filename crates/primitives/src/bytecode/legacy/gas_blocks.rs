@@ -0,0 +1,49 @@
+use std::{sync::Arc, vec::Vec};
+
+/// Basic block of the legacy bytecode with its precomputed static gas and stack requirements.
+///
+/// Block starts at the beginning of the code, at a `JUMPDEST` or after an instruction that
+/// ends the previous block, and ends with a jump, a terminating instruction or an instruction
+/// that observes the remaining gas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasBlock {
+    /// Offset of the first instruction.
+    pub start: u32,
+    /// Offset of the last instruction.
+    pub last: u32,
+    /// Sum of the static gas of the instructions.
+    pub static_gas: u64,
+    /// Stack height required for no instruction of the block to underflow.
+    pub stack_required: u16,
+    /// Maximum stack height increase over the height at the start of the block.
+    pub stack_max_growth: u16,
+}
+
+/// Basic blocks of the legacy bytecode, ordered by the offset.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasBlocks(pub Arc<Vec<GasBlock>>);
+
+impl GasBlocks {
+    /// Returns the blocks.
+    #[inline]
+    pub fn as_slice(&self) -> &[GasBlock] {
+        &self.0
+    }
+
+    /// Returns the index of the block that starts at `pc`.
+    ///
+    /// Block following `hint` is checked first, as it is the next block when execution
+    /// falls through.
+    #[inline]
+    pub fn find(&self, pc: usize, hint: usize) -> Option<usize> {
+        match self.0.get(hint + 1) {
+            Some(block) if block.start as usize == pc => Some(hint + 1),
+            _ => self
+                .0
+                .binary_search_by_key(&pc, |block| block.start as usize)
+                .ok(),
+        }
+    }
+}
@@ -26,6 +26,20 @@ pub const BLOCKHASH_SERVE_WINDOW: usize = 8192;
 /// This is named `HISTORY_STORAGE_ADDRESS` in the EIP.
 pub const BLOCKHASH_STORAGE_ADDRESS: Address = address!("25a219378dad9b3503c8268c9ca836a52427a4fb");
 
+/// Address of the system caller.
+///
+/// System calls are executed from this address at the start of the block.
+/// The caller is not charged for gas and its nonce is not increased.
+pub const SYSTEM_ADDRESS: Address = address!("fffffffffffffffffffffffffffffffffffffffe");
+
+/// Gas limit given to the system calls.
+pub const SYSTEM_CALL_GAS_LIMIT: u64 = 30_000_000;
+
+/// EIP-4788: Beacon block root in the EVM
+///
+/// The address of the beacon roots contract.
+pub const BEACON_ROOTS_ADDRESS: Address = address!("000f3df6d732807ef1319fb7b8bb8522d0beac02");
+
 /// EIP-3860: Limit and meter initcode
 ///
 /// Limit of maximum initcode size is `2 * MAX_CODE_SIZE`.
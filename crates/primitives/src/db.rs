@@ -1,7 +1,12 @@
-use crate::{Account, AccountInfo, Address, Bytecode, HashMap, B256, U256};
+use crate::{Account, AccountInfo, Address, Bytecode, HashMap, B256, KECCAK_EMPTY, U256};
 use auto_impl::auto_impl;
+use std::vec::Vec;
 
+#[cfg(feature = "asyncdb")]
+pub mod async_db;
 pub mod components;
+#[cfg(feature = "asyncdb")]
+pub use async_db::{DatabaseAsync, DatabaseAsyncRef, WrapDatabaseAsync};
 pub use components::{
     BlockHash, BlockHashRef, DatabaseComponentError, DatabaseComponents, State, StateRef,
 };
@@ -23,6 +28,40 @@ pub trait Database {
 
     /// Get block hash by block number.
     fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error>;
+
+    /// Get code hash of the account, [`KECCAK_EMPTY`] if the account does not exist.
+    ///
+    /// Default implementation reads the whole account with [`Database::basic`].
+    fn code_hash(&mut self, address: Address) -> Result<B256, Self::Error> {
+        Ok(self
+            .basic(address)?
+            .map_or(KECCAK_EMPTY, |info| info.code_hash))
+    }
+
+    /// Get basic account information of many accounts, in the given order.
+    ///
+    /// Default implementation calls [`Database::basic`] for each account. Databases with
+    /// expensive round trips should override it to fetch the accounts in one batch.
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        addresses
+            .iter()
+            .map(|address| self.basic(*address))
+            .collect()
+    }
+
+    /// Get many storage values, in the given order.
+    ///
+    /// Default implementation calls [`Database::storage`] for each slot. Databases with
+    /// expensive round trips should override it to fetch the slots in one batch.
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        slots
+            .iter()
+            .map(|(address, index)| self.storage(*address, *index))
+            .collect()
+    }
 }
 
 /// EVM database commit interface.
@@ -54,6 +93,29 @@ pub trait DatabaseRef {
 
     /// Get block hash by block number.
     fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error>;
+
+    /// Get basic account information of many accounts, in the given order.
+    ///
+    /// Default implementation calls [`DatabaseRef::basic_ref`] for each account.
+    fn basic_many_ref(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        addresses
+            .iter()
+            .map(|address| self.basic_ref(*address))
+            .collect()
+    }
+
+    /// Get many storage values, in the given order.
+    ///
+    /// Default implementation calls [`DatabaseRef::storage_ref`] for each slot.
+    fn storage_many_ref(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        slots
+            .iter()
+            .map(|(address, index)| self.storage_ref(*address, *index))
+            .collect()
+    }
 }
 
 /// Wraps a [`DatabaseRef`] to provide a [`Database`] implementation.
@@ -95,6 +157,19 @@ impl<T: DatabaseRef> Database for WrapDatabaseRef<T> {
     fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
         self.0.block_hash_ref(number)
     }
+
+    #[inline]
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        self.0.basic_many_ref(addresses)
+    }
+
+    #[inline]
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        self.0.storage_many_ref(slots)
+    }
 }
 
 impl<T: DatabaseRef + DatabaseCommit> DatabaseCommit for WrapDatabaseRef<T> {
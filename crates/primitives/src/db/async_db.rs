@@ -0,0 +1,376 @@
+//! Async database interface and the adapter to the sync [`Database`].
+
+use crate::{
+    db::{Database, DatabaseRef},
+    AccountInfo, Address, Bytecode, B256, U256,
+};
+use core::future::Future;
+use std::vec::Vec;
+use tokio::runtime::{Builder, Handle, Runtime, RuntimeFlavor};
+
+/// The async EVM database interface.
+///
+/// Contains the same methods as [`Database`], but returns futures.
+///
+/// Use [`WrapDatabaseAsync`] to provide [`Database`] implementation for a type
+/// that only implements this trait.
+pub trait DatabaseAsync {
+    /// The database error type.
+    type Error: Send;
+
+    /// Get basic account information.
+    fn basic_async(
+        &mut self,
+        address: Address,
+    ) -> impl Future<Output = Result<Option<AccountInfo>, Self::Error>> + Send;
+
+    /// Get account code by its hash.
+    fn code_by_hash_async(
+        &mut self,
+        code_hash: B256,
+    ) -> impl Future<Output = Result<Bytecode, Self::Error>> + Send;
+
+    /// Get storage value of address at index.
+    fn storage_async(
+        &mut self,
+        address: Address,
+        index: U256,
+    ) -> impl Future<Output = Result<U256, Self::Error>> + Send;
+
+    /// Get block hash by block number.
+    fn block_hash_async(
+        &mut self,
+        number: U256,
+    ) -> impl Future<Output = Result<B256, Self::Error>> + Send;
+}
+
+/// The async EVM database interface.
+///
+/// Contains the same methods as [`DatabaseAsync`], but with `&self` receivers instead of
+/// `&mut self`.
+///
+/// Use [`WrapDatabaseAsync`] to provide [`DatabaseRef`] implementation for a type
+/// that only implements this trait.
+pub trait DatabaseAsyncRef {
+    /// The database error type.
+    type Error: Send;
+
+    /// Get basic account information.
+    fn basic_async_ref(
+        &self,
+        address: Address,
+    ) -> impl Future<Output = Result<Option<AccountInfo>, Self::Error>> + Send;
+
+    /// Get account code by its hash.
+    fn code_by_hash_async_ref(
+        &self,
+        code_hash: B256,
+    ) -> impl Future<Output = Result<Bytecode, Self::Error>> + Send;
+
+    /// Get storage value of address at index.
+    fn storage_async_ref(
+        &self,
+        address: Address,
+        index: U256,
+    ) -> impl Future<Output = Result<U256, Self::Error>> + Send;
+
+    /// Get block hash by block number.
+    fn block_hash_async_ref(
+        &self,
+        number: U256,
+    ) -> impl Future<Output = Result<B256, Self::Error>> + Send;
+    /// Get basic account information of many accounts, in the given order.
+    ///
+    /// Default implementation awaits [`DatabaseAsyncRef::basic_async_ref`] for each account.
+    fn basic_many_async_ref(
+        &self,
+        addresses: &[Address],
+    ) -> impl Future<Output = Result<Vec<Option<AccountInfo>>, Self::Error>> + Send {
+        let futures = addresses
+            .iter()
+            .map(|address| self.basic_async_ref(*address))
+            .collect::<Vec<_>>();
+        async move {
+            let mut infos = Vec::with_capacity(futures.len());
+            for future in futures {
+                infos.push(future.await?);
+            }
+            Ok(infos)
+        }
+    }
+
+    /// Get many storage values, in the given order.
+    ///
+    /// Default implementation awaits [`DatabaseAsyncRef::storage_async_ref`] for each slot.
+    fn storage_many_async_ref(
+        &self,
+        slots: &[(Address, U256)],
+    ) -> impl Future<Output = Result<Vec<U256>, Self::Error>> + Send {
+        let futures = slots
+            .iter()
+            .map(|(address, index)| self.storage_async_ref(*address, *index))
+            .collect::<Vec<_>>();
+        async move {
+            let mut values = Vec::with_capacity(futures.len());
+            for future in futures {
+                values.push(future.await?);
+            }
+            Ok(values)
+        }
+    }
+}
+
+/// Wraps a [`DatabaseAsync`] or [`DatabaseAsyncRef`] to provide [`Database`] and
+/// [`DatabaseRef`] implementations.
+///
+/// Futures are driven to completion on the tokio runtime, blocking the current thread.
+/// When called from a worker of a multi-threaded runtime, the worker is moved out of the
+/// runtime with [`tokio::task::block_in_place`] first, so it doesn't panic. A current-thread
+/// runtime can't be blocked on from its own thread, so its futures are driven by a new
+/// runtime on a scoped thread instead.
+#[derive(Debug)]
+pub struct WrapDatabaseAsync<T> {
+    db: T,
+    rt: HandleOrRuntime,
+}
+
+impl<T> WrapDatabaseAsync<T> {
+    /// Wraps the database, using the handle of the current runtime.
+    ///
+    /// Returns `None` if there is no current runtime. Use [`with_runtime`](Self::with_runtime)
+    /// in that case.
+    pub fn new(db: T) -> Option<Self> {
+        let handle = Handle::try_current().ok()?;
+        Some(Self::with_handle(db, handle))
+    }
+
+    /// Wraps the database, using the given runtime.
+    pub fn with_runtime(db: T, runtime: Runtime) -> Self {
+        Self {
+            db,
+            rt: HandleOrRuntime::Runtime(runtime),
+        }
+    }
+
+    /// Wraps the database, using the given runtime handle.
+    pub fn with_handle(db: T, handle: Handle) -> Self {
+        Self {
+            db,
+            rt: HandleOrRuntime::Handle(handle),
+        }
+    }
+
+    /// Returns reference to the wrapped database.
+    pub fn db(&self) -> &T {
+        &self.db
+    }
+
+    /// Returns mutable reference to the wrapped database.
+    pub fn db_mut(&mut self) -> &mut T {
+        &mut self.db
+    }
+
+    /// Returns the wrapped database.
+    pub fn into_inner(self) -> T {
+        self.db
+    }
+}
+
+impl<T: DatabaseAsync> Database for WrapDatabaseAsync<T> {
+    type Error = T::Error;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.rt.block_on(self.db.basic_async(address))
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.rt.block_on(self.db.code_by_hash_async(code_hash))
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.rt.block_on(self.db.storage_async(address, index))
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.rt.block_on(self.db.block_hash_async(number))
+    }
+}
+
+impl<T: DatabaseAsyncRef> DatabaseRef for WrapDatabaseAsync<T> {
+    type Error = T::Error;
+
+    #[inline]
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.rt.block_on(self.db.basic_async_ref(address))
+    }
+
+    #[inline]
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.rt.block_on(self.db.code_by_hash_async_ref(code_hash))
+    }
+
+    #[inline]
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.rt.block_on(self.db.storage_async_ref(address, index))
+    }
+
+    #[inline]
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.rt.block_on(self.db.block_hash_async_ref(number))
+    }
+    #[inline]
+    fn basic_many_ref(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        self.rt.block_on(self.db.basic_many_async_ref(addresses))
+    }
+
+    #[inline]
+    fn storage_many_ref(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        self.rt.block_on(self.db.storage_many_async_ref(slots))
+    }
+}
+
+/// Runtime that the futures are blocked on.
+#[derive(Debug)]
+enum HandleOrRuntime {
+    Handle(Handle),
+    Runtime(Runtime),
+}
+
+impl HandleOrRuntime {
+    #[inline]
+    fn block_on<F>(&self, f: F) -> F::Output
+    where
+        F: Future + Send,
+        F::Output: Send,
+    {
+        let handle = match self {
+            Self::Runtime(runtime) if Handle::try_current().is_err() => return runtime.block_on(f),
+            Self::Runtime(runtime) => runtime.handle(),
+            Self::Handle(handle) => handle,
+        };
+        let is_current_thread =
+            |handle: &Handle| handle.runtime_flavor() == RuntimeFlavor::CurrentThread;
+        if is_current_thread(handle) || Handle::try_current().is_ok_and(|h| is_current_thread(&h)) {
+            // The handle of a current-thread runtime doesn't drive its IO and time drivers
+            // and can't be blocked on from within the runtime, so drive the future with a
+            // new runtime on a scoped thread.
+            std::thread::scope(move |scope| {
+                scope
+                    .spawn(move || {
+                        Builder::new_current_thread()
+                            .enable_all()
+                            .build()
+                            .expect("failed to build the runtime")
+                            .block_on(f)
+                    })
+                    .join()
+                    .expect("runtime thread panicked")
+            })
+        } else {
+            // Outside of the runtime this simply calls the closure.
+            tokio::task::block_in_place(move || handle.block_on(f))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{address, HashMap};
+    use core::convert::Infallible;
+
+    #[derive(Default)]
+    struct AsyncDB {
+        accounts: HashMap<Address, AccountInfo>,
+    }
+
+    impl DatabaseAsyncRef for AsyncDB {
+        type Error = Infallible;
+
+        async fn basic_async_ref(
+            &self,
+            address: Address,
+        ) -> Result<Option<AccountInfo>, Infallible> {
+            tokio::task::yield_now().await;
+            Ok(self.accounts.get(&address).cloned())
+        }
+
+        async fn code_by_hash_async_ref(&self, _code_hash: B256) -> Result<Bytecode, Infallible> {
+            Ok(Bytecode::default())
+        }
+
+        async fn storage_async_ref(
+            &self,
+            _address: Address,
+            index: U256,
+        ) -> Result<U256, Infallible> {
+            Ok(index)
+        }
+
+        async fn block_hash_async_ref(&self, _number: U256) -> Result<B256, Infallible> {
+            Ok(B256::ZERO)
+        }
+    }
+
+    fn db() -> AsyncDB {
+        let mut db = AsyncDB::default();
+        db.accounts.insert(
+            address!("1000000000000000000000000000000000000001"),
+            AccountInfo::from_balance(U256::from(1)),
+        );
+        db
+    }
+
+    #[test]
+    fn block_on_runtime() {
+        let address = address!("1000000000000000000000000000000000000001");
+        let runtime = Builder::new_multi_thread().enable_all().build().unwrap();
+        assert!(WrapDatabaseAsync::new(db()).is_none());
+
+        // from within the runtime worker, using the current handle.
+        let wrapped = runtime
+            .block_on(runtime.spawn(async move {
+                let db = WrapDatabaseAsync::new(db()).unwrap();
+                assert_eq!(db.storage_ref(address, U256::from(2)), Ok(U256::from(2)));
+                db
+            }))
+            .unwrap();
+        assert_eq!(
+            wrapped.basic_ref(address).unwrap().unwrap().balance,
+            U256::from(1)
+        );
+
+        // from outside of the runtime, owning it.
+        let wrapped = WrapDatabaseAsync::with_runtime(wrapped.into_inner(), runtime);
+        assert_eq!(
+            wrapped.basic_ref(address).unwrap().unwrap().balance,
+            U256::from(1)
+        );
+
+        // from within a current-thread runtime, using the current handle.
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let wrapped = runtime.block_on(async {
+            let db = WrapDatabaseAsync::new(db()).unwrap();
+            assert_eq!(
+                db.basic_ref(address).unwrap().unwrap().balance,
+                U256::from(1)
+            );
+            db
+        });
+
+        // from outside of the current-thread runtime, using its handle.
+        let wrapped =
+            WrapDatabaseAsync::with_handle(wrapped.into_inner(), runtime.handle().clone());
+        assert_eq!(
+            wrapped.storage_ref(address, U256::from(3)),
+            Ok(U256::from(3))
+        );
+    }
+}
@@ -9,6 +9,7 @@ use crate::{
     db::{Database, DatabaseRef},
     Account, AccountInfo, Address, Bytecode, HashMap, B256, U256,
 };
+use std::vec::Vec;
 
 use super::DatabaseCommit;
 
@@ -48,6 +49,17 @@ impl<S: State, BH: BlockHash> Database for DatabaseComponents<S, BH> {
             .block_hash(number)
             .map_err(Self::Error::BlockHash)
     }
+
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        self.state.basic_many(addresses).map_err(Self::Error::State)
+    }
+
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        self.state.storage_many(slots).map_err(Self::Error::State)
+    }
 }
 
 impl<S: StateRef, BH: BlockHashRef> DatabaseRef for DatabaseComponents<S, BH> {
@@ -74,6 +86,17 @@ impl<S: StateRef, BH: BlockHashRef> DatabaseRef for DatabaseComponents<S, BH> {
             .block_hash(number)
             .map_err(Self::Error::BlockHash)
     }
+
+    fn basic_many_ref(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        self.state.basic_many(addresses).map_err(Self::Error::State)
+    }
+
+    fn storage_many_ref(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        self.state.storage_many(slots).map_err(Self::Error::State)
+    }
 }
 
 impl<S: DatabaseCommit, BH: BlockHashRef> DatabaseCommit for DatabaseComponents<S, BH> {
@@ -4,7 +4,7 @@
 use crate::{AccountInfo, Address, Bytecode, B256, U256};
 use auto_impl::auto_impl;
 use core::ops::Deref;
-use std::sync::Arc;
+use std::{sync::Arc, vec::Vec};
 
 #[auto_impl(&mut, Box)]
 pub trait State {
@@ -18,6 +18,29 @@ pub trait State {
 
     /// Get storage value of address at index.
     fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error>;
+
+    /// Get basic account information of many accounts, in the given order.
+    ///
+    /// Default implementation calls [`State::basic`] for each account.
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        addresses
+            .iter()
+            .map(|address| self.basic(*address))
+            .collect()
+    }
+
+    /// Get many storage values, in the given order.
+    ///
+    /// Default implementation calls [`State::storage`] for each slot.
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        slots
+            .iter()
+            .map(|(address, index)| self.storage(*address, *index))
+            .collect()
+    }
 }
 
 #[auto_impl(&, &mut, Box, Rc, Arc)]
@@ -32,6 +55,26 @@ pub trait StateRef {
 
     /// Get storage value of address at index.
     fn storage(&self, address: Address, index: U256) -> Result<U256, Self::Error>;
+
+    /// Get basic account information of many accounts, in the given order.
+    ///
+    /// Default implementation calls [`StateRef::basic`] for each account.
+    fn basic_many(&self, addresses: &[Address]) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        addresses
+            .iter()
+            .map(|address| self.basic(*address))
+            .collect()
+    }
+
+    /// Get many storage values, in the given order.
+    ///
+    /// Default implementation calls [`StateRef::storage`] for each slot.
+    fn storage_many(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        slots
+            .iter()
+            .map(|(address, index)| self.storage(*address, *index))
+            .collect()
+    }
 }
 
 impl<T> State for &T
@@ -51,6 +94,17 @@ where
     fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
         StateRef::storage(*self, address, index)
     }
+
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        StateRef::basic_many(*self, addresses)
+    }
+
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        StateRef::storage_many(*self, slots)
+    }
 }
 
 impl<T> State for Arc<T>
@@ -70,4 +124,15 @@ where
     fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
         self.deref().storage(address, index)
     }
+
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        self.deref().basic_many(addresses)
+    }
+
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        self.deref().storage_many(slots)
+    }
 }
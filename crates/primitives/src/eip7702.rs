@@ -0,0 +1,248 @@
+//! EIP-7702: Set EOA account code.
+//!
+//! Types of the authorization list carried by set-code transactions. Recovering the
+//! authority of a signed authorization needs secp256k1 and is done by the caller
+//! through [`AuthorizationList::recovered_authorizations`].
+
+use crate::{keccak256, Address, B256, U256};
+use alloy_rlp::{Encodable, RlpDecodable, RlpEncodable};
+use core::ops::Deref;
+use std::vec::Vec;
+
+/// Magic byte prepended to the RLP encoded authorization before hashing.
+pub const MAGIC: u8 = 0x05;
+
+/// Base cost of updating authorized account.
+pub const PER_AUTH_BASE_COST: u64 = 12500;
+
+/// Cost of creating authorized account that was previously empty.
+pub const PER_EMPTY_ACCOUNT_COST: u64 = 25000;
+
+/// Half of the secp256k1 curve order. Signatures with a larger `s` value are rejected.
+pub const SECP256K1N_HALF: U256 = U256::from_be_bytes([
+    0x7F, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+    0x5D, 0x57, 0x6E, 0x73, 0x57, 0xA4, 0x50, 0x1D, 0xDF, 0xE9, 0x2F, 0x46, 0x68, 0x1B, 0x20, 0xA0,
+]);
+
+/// Unsigned authorization tuple `[chain_id, address, nonce]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Authorization {
+    /// Chain id the authorization is valid on. Zero means any chain.
+    pub chain_id: U256,
+    /// Address whose code the authority delegates to.
+    pub address: Address,
+    /// Expected nonce of the authority.
+    pub nonce: u64,
+}
+
+impl Authorization {
+    /// Returns the hash that is signed by the authority:
+    /// `keccak256(MAGIC || rlp([chain_id, address, nonce]))`.
+    pub fn signature_hash(&self) -> B256 {
+        let mut buf = Vec::with_capacity(1 + self.length());
+        buf.push(MAGIC);
+        self.encode(&mut buf);
+        keccak256(buf)
+    }
+
+    /// Attaches signature to the authorization.
+    pub fn into_signed(self, y_parity: u8, r: U256, s: U256) -> SignedAuthorization {
+        SignedAuthorization {
+            inner: self,
+            y_parity,
+            r,
+            s,
+        }
+    }
+
+    /// Attaches already recovered authority to the authorization.
+    pub fn into_recovered(self, authority: Option<Address>) -> RecoveredAuthorization {
+        RecoveredAuthorization {
+            inner: self,
+            authority,
+        }
+    }
+}
+
+/// Signed authorization as it is found inside of the transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignedAuthorization {
+    /// Signed authorization.
+    pub inner: Authorization,
+    /// Signature y parity.
+    pub y_parity: u8,
+    /// Signature `r` value.
+    pub r: U256,
+    /// Signature `s` value.
+    pub s: U256,
+}
+
+impl Deref for SignedAuthorization {
+    type Target = Authorization;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl SignedAuthorization {
+    /// Returns the signed authorization.
+    pub fn inner(&self) -> &Authorization {
+        &self.inner
+    }
+
+    /// Returns true if signature values are in the range allowed by EIP-2:
+    /// `y_parity` is 0 or 1 and `s` is not larger than half of the curve order.
+    pub fn has_valid_signature_values(&self) -> bool {
+        self.y_parity <= 1 && self.s <= SECP256K1N_HALF
+    }
+
+    /// Returns `r || s` signature bytes used for public key recovery.
+    pub fn signature_bytes(&self) -> [u8; 64] {
+        let mut sig = [0u8; 64];
+        sig[..32].copy_from_slice(&self.r.to_be_bytes::<32>());
+        sig[32..].copy_from_slice(&self.s.to_be_bytes::<32>());
+        sig
+    }
+}
+
+/// Authorization with the recovered authority.
+///
+/// Authority is `None` if it could not be recovered from the signature.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecoveredAuthorization {
+    /// Recovered authorization.
+    pub inner: Authorization,
+    /// Recovered authority.
+    pub authority: Option<Address>,
+}
+
+impl Deref for RecoveredAuthorization {
+    type Target = Authorization;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl RecoveredAuthorization {
+    /// Returns the recovered authorization.
+    pub fn inner(&self) -> &Authorization {
+        &self.inner
+    }
+
+    /// Returns the recovered authority, if any.
+    pub fn authority(&self) -> Option<Address> {
+        self.authority
+    }
+}
+
+/// Authorization list of the EIP-7702 transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AuthorizationList {
+    /// Authorizations with signatures, authority is recovered during execution.
+    Signed(Vec<SignedAuthorization>),
+    /// Authorizations with already recovered authorities.
+    Recovered(Vec<RecoveredAuthorization>),
+}
+
+impl From<Vec<SignedAuthorization>> for AuthorizationList {
+    fn from(signed: Vec<SignedAuthorization>) -> Self {
+        Self::Signed(signed)
+    }
+}
+
+impl From<Vec<RecoveredAuthorization>> for AuthorizationList {
+    fn from(recovered: Vec<RecoveredAuthorization>) -> Self {
+        Self::Recovered(recovered)
+    }
+}
+
+impl AuthorizationList {
+    /// Returns number of authorizations in the list.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Signed(signed) => signed.len(),
+            Self::Recovered(recovered) => recovered.len(),
+        }
+    }
+
+    /// Returns true if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns authorizations with recovered authorities.
+    ///
+    /// `recover` is called for every signed authorization that has signature values
+    /// in the valid range; authorizations with invalid values get `None` as authority.
+    pub fn recovered_authorizations<F>(&self, mut recover: F) -> Vec<RecoveredAuthorization>
+    where
+        F: FnMut(&SignedAuthorization) -> Option<Address>,
+    {
+        match self {
+            Self::Signed(signed) => signed
+                .iter()
+                .map(|auth| {
+                    let authority = if auth.has_valid_signature_values() {
+                        recover(auth)
+                    } else {
+                        None
+                    };
+                    auth.inner.clone().into_recovered(authority)
+                })
+                .collect(),
+            Self::Recovered(recovered) => recovered.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{address, b256, uint};
+
+    #[test]
+    fn signature_hash() {
+        let auth = Authorization {
+            chain_id: U256::from(1),
+            address: address!("0000000000000000000000000000000000000001"),
+            nonce: 1,
+        };
+        // keccak256(0x05 || 0xd7 0x01 0x94 <address> 0x01)
+        let mut preimage = vec![MAGIC, 0xd7, 0x01, 0x94];
+        preimage.extend_from_slice(auth.address.as_slice());
+        preimage.push(0x01);
+        assert_eq!(auth.signature_hash(), keccak256(preimage));
+    }
+
+    #[test]
+    fn invalid_signature_values_are_not_recovered() {
+        let auth = Authorization::default();
+        let high_s = auth.clone().into_signed(
+            0,
+            U256::from(1),
+            uint!(0x7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF5D576E7357A4501DDFE92F46681B20A1_U256),
+        );
+        let bad_parity = auth.clone().into_signed(2, U256::from(1), U256::from(1));
+        let valid = auth.into_signed(1, U256::from(1), U256::from(1));
+        let authority = address!("0000000000000000000000000000000000000002");
+
+        let list = AuthorizationList::from(vec![high_s, bad_parity, valid]);
+        let recovered = list.recovered_authorizations(|_| Some(authority));
+        assert_eq!(
+            recovered.iter().map(|a| a.authority()).collect::<Vec<_>>(),
+            vec![None, None, Some(authority)]
+        );
+        assert_eq!(
+            SECP256K1N_HALF,
+            U256::from_be_bytes(
+                b256!("7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0").0
+            )
+        );
+    }
+}
@@ -3,9 +3,9 @@ pub mod handler_cfg;
 pub use handler_cfg::{CfgEnvWithHandlerCfg, EnvWithHandlerCfg, HandlerCfg};
 
 use crate::{
-    calc_blob_gasprice, Account, Address, Bytes, InvalidHeader, InvalidTransaction, Spec, SpecId,
-    B256, GAS_PER_BLOB, KECCAK_EMPTY, MAX_BLOB_NUMBER_PER_BLOCK, MAX_INITCODE_SIZE, U256,
-    VERSIONED_HASH_VERSION_KZG,
+    calc_blob_gasprice, Account, Address, AuthorizationList, Bytes, InvalidHeader,
+    InvalidTransaction, Spec, SpecId, B256, GAS_PER_BLOB, KECCAK_EMPTY, MAX_BLOB_NUMBER_PER_BLOCK,
+    MAX_INITCODE_SIZE, U256, VERSIONED_HASH_VERSION_KZG,
 };
 use core::cmp::{min, Ordering};
 use core::hash::Hash;
@@ -189,6 +189,29 @@ impl Env {
             }
         }
 
+        // EIP-7702: Set EOA account code
+        if SPEC::enabled(SpecId::PRAGUE) {
+            // Presence of authorization list means that this is set-code transaction.
+            if let Some(auth_list) = &self.tx.authorization_list {
+                // set-code transaction can't be a create transaction.
+                if self.tx.transact_to.is_create() {
+                    return Err(InvalidTransaction::Eip7702CreateTransaction);
+                }
+
+                // there must be at least one authorization.
+                if auth_list.is_empty() {
+                    return Err(InvalidTransaction::EmptyAuthorizationList);
+                }
+
+                // set-code transaction is not a blob transaction.
+                if self.tx.max_fee_per_blob_gas.is_some() {
+                    return Err(InvalidTransaction::AuthorizationListInvalidFields);
+                }
+            }
+        } else if self.tx.authorization_list.is_some() {
+            return Err(InvalidTransaction::AuthorizationListNotSupported);
+        }
+
         Ok(())
     }
 
@@ -201,8 +224,19 @@ impl Env {
         // EIP-3607: Reject transactions from senders with deployed code
         // This EIP is introduced after london but there was no collision in past
         // so we can leave it enabled always
+        //
+        // EIP-7702: Senders with a delegation designator as code are allowed.
+        // Code needs to be loaded for the designator to be recognised.
         if !self.cfg.is_eip3607_disabled() && account.info.code_hash != KECCAK_EMPTY {
-            return Err(InvalidTransaction::RejectCallerWithCode);
+            let is_delegated = SPEC::enabled(SpecId::PRAGUE)
+                && account
+                    .info
+                    .code
+                    .as_ref()
+                    .is_some_and(|code| code.is_eip7702());
+            if !is_delegated {
+                return Err(InvalidTransaction::RejectCallerWithCode);
+            }
         }
 
         // Check that the transaction's nonce is correct
@@ -267,6 +301,15 @@ pub struct CfgEnv {
     ///
     /// Default: Analyse
     pub perf_analyse_created_bytecodes: AnalysisKind,
+    /// Bytecode that is called is analysed this way once per transaction, and the analysed
+    /// bytecode is reused by the following calls.
+    ///
+    /// With [`AnalysisKind::Raw`] the bytecode is analysed on every call and is not kept.
+    /// `None` analyses it as [`CfgEnv::perf_analyse_created_bytecodes`], see
+    /// [`CfgEnv::called_bytecodes_analysis`].
+    ///
+    /// Default: None
+    pub perf_analyse_called_bytecodes: Option<AnalysisKind>,
     /// If some it will effects EIP-170: Contract code size limit. Useful to increase this because of tests.
     /// By default it is 0x6000 (~25kb).
     pub limit_contract_code_size: Option<usize>,
@@ -312,6 +355,13 @@ impl CfgEnv {
         self
     }
 
+    /// Returns the analysis of the called bytecode, see [`CfgEnv::perf_analyse_called_bytecodes`].
+    pub fn called_bytecodes_analysis(&self) -> &AnalysisKind {
+        self.perf_analyse_called_bytecodes
+            .as_ref()
+            .unwrap_or(&self.perf_analyse_created_bytecodes)
+    }
+
     #[cfg(feature = "optional_eip3607")]
     pub fn is_eip3607_disabled(&self) -> bool {
         self.disable_eip3607
@@ -378,6 +428,7 @@ impl Default for CfgEnv {
         Self {
             chain_id: 1,
             perf_analyse_created_bytecodes: AnalysisKind::default(),
+            perf_analyse_called_bytecodes: None,
             limit_contract_code_size: None,
             #[cfg(feature = "c-kzg")]
             kzg_settings: crate::kzg::EnvKzgSettings::Default,
@@ -438,6 +489,14 @@ pub struct BlockEnv {
     ///
     /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
     pub blob_excess_gas_and_price: Option<BlobExcessGasAndPrice>,
+    /// Root of the parent beacon block.
+    ///
+    /// Stored in the beacon roots contract at the start of the block.
+    ///
+    /// Incorporated as part of the Cancun upgrade via [EIP-4788].
+    ///
+    /// [EIP-4788]: https://eips.ethereum.org/EIPS/eip-4788
+    pub parent_beacon_block_root: Option<B256>,
 }
 
 impl BlockEnv {
@@ -488,6 +547,92 @@ impl Default for BlockEnv {
             difficulty: U256::ZERO,
             prevrandao: Some(B256::ZERO),
             blob_excess_gas_and_price: Some(BlobExcessGasAndPrice::new(0)),
+            parent_beacon_block_root: None,
+        }
+    }
+}
+
+/// Overrides of the [`BlockEnv`] fields, in the shape of the geth `eth_call` block overrides.
+///
+/// Fields that are `None` are not changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct BlockOverrides {
+    /// Overrides the block number.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub number: Option<U256>,
+    /// Overrides the difficulty.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub difficulty: Option<U256>,
+    /// Overrides the timestamp.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub time: Option<U256>,
+    /// Overrides the block gas limit.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub gas_limit: Option<U256>,
+    /// Overrides the coinbase.
+    #[cfg_attr(
+        feature = "serde",
+        serde(alias = "coinbase", skip_serializing_if = "Option::is_none")
+    )]
+    pub fee_recipient: Option<Address>,
+    /// Overrides the prevrandao.
+    #[cfg_attr(
+        feature = "serde",
+        serde(alias = "random", skip_serializing_if = "Option::is_none")
+    )]
+    pub prev_randao: Option<B256>,
+    /// Overrides the base fee.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub base_fee_per_gas: Option<U256>,
+    /// Overrides the blob gas price.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub blob_base_fee: Option<U256>,
+}
+
+impl BlockEnv {
+    /// Applies the overrides to the block environment.
+    ///
+    /// Blob base fee override replaces the blob gas price, the excess blob gas is kept.
+    pub fn apply_overrides(&mut self, overrides: &BlockOverrides) {
+        let BlockOverrides {
+            number,
+            difficulty,
+            time,
+            gas_limit,
+            fee_recipient,
+            prev_randao,
+            base_fee_per_gas,
+            blob_base_fee,
+        } = *overrides;
+        if let Some(number) = number {
+            self.number = number;
+        }
+        if let Some(difficulty) = difficulty {
+            self.difficulty = difficulty;
+        }
+        if let Some(time) = time {
+            self.timestamp = time;
+        }
+        if let Some(gas_limit) = gas_limit {
+            self.gas_limit = gas_limit;
+        }
+        if let Some(fee_recipient) = fee_recipient {
+            self.coinbase = fee_recipient;
+        }
+        if let Some(prev_randao) = prev_randao {
+            self.prevrandao = Some(prev_randao);
+        }
+        if let Some(base_fee_per_gas) = base_fee_per_gas {
+            self.basefee = base_fee_per_gas;
+        }
+        if let Some(blob_base_fee) = blob_base_fee {
+            let excess_blob_gas = self.get_blob_excess_gas().unwrap_or_default();
+            self.blob_excess_gas_and_price = Some(BlobExcessGasAndPrice {
+                excess_blob_gas,
+                blob_gasprice: blob_base_fee.saturating_to(),
+            });
         }
     }
 }
@@ -549,20 +694,82 @@ pub struct TxEnv {
     /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
     pub max_fee_per_blob_gas: Option<U256>,
 
+    /// List of authorizations, that contains the signature that authorizes this
+    /// caller to place the code to signer account.
+    ///
+    /// Set EOA account code for one transaction
+    ///
+    /// [EIP-Set EOA account code for one transaction](https://eips.ethereum.org/EIPS/eip-7702)
+    pub authorization_list: Option<AuthorizationList>,
+
     #[cfg_attr(feature = "serde", serde(flatten))]
     #[cfg(feature = "optimism")]
     /// Optimism fields.
     pub optimism: OptimismFields,
 }
 
+/// Transaction type as defined in [EIP-2718].
+///
+/// [EIP-2718]: https://eips.ethereum.org/EIPS/eip-2718
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TxType {
+    /// Legacy transaction.
+    #[default]
     Legacy,
+    /// Access list transaction, [EIP-2930](https://eips.ethereum.org/EIPS/eip-2930).
+    Eip2930,
+    /// Dynamic fee transaction, [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559).
     Eip1559,
+    /// Blob transaction, [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844).
     BlobTx,
+    /// Initcode transaction that creates EOF contract.
     EofCreate,
+    /// Set code transaction, [EIP-7702](https://eips.ethereum.org/EIPS/eip-7702).
+    Eip7702,
+}
+
+impl TxType {
+    /// Returns the EIP-2718 type byte of the transaction.
+    #[inline]
+    pub const fn ty(&self) -> u8 {
+        match self {
+            Self::Legacy => 0x00,
+            Self::Eip2930 => 0x01,
+            Self::Eip1559 => 0x02,
+            Self::BlobTx => 0x03,
+            Self::Eip7702 => 0x04,
+            Self::EofCreate => 0x06,
+        }
+    }
+
+    /// Returns true if the transaction is a legacy transaction without type byte.
+    #[inline]
+    pub const fn is_legacy(&self) -> bool {
+        matches!(self, Self::Legacy)
+    }
 }
 
 impl TxEnv {
+    /// Returns the type of the transaction derived from the fields that are set.
+    ///
+    /// Access list transaction with an empty access list can't be distinguished from
+    /// the legacy transaction and is reported as [`TxType::Legacy`].
+    #[inline]
+    pub fn tx_type(&self) -> TxType {
+        if self.authorization_list.is_some() {
+            TxType::Eip7702
+        } else if !self.blob_hashes.is_empty() || self.max_fee_per_blob_gas.is_some() {
+            TxType::BlobTx
+        } else if self.gas_priority_fee.is_some() {
+            TxType::Eip1559
+        } else if !self.access_list.is_empty() {
+            TxType::Eip2930
+        } else {
+            TxType::Legacy
+        }
+    }
+
     /// See [EIP-4844], [`Env::calc_data_fee`], and [`Env::calc_max_data_fee`].
     ///
     /// [EIP-4844]: https://eips.ethereum.org/EIPS/eip-4844
@@ -593,6 +800,7 @@ impl Default for TxEnv {
             access_list: Vec::new(),
             blob_hashes: Vec::new(),
             max_fee_per_blob_gas: None,
+            authorization_list: None,
             #[cfg(feature = "optimism")]
             optimism: OptimismFields::default(),
         }
@@ -707,14 +915,26 @@ pub enum CreateScheme {
 }
 
 /// What bytecode analysis to perform.
+///
+/// New kinds of the analysis can be added, so the enum is not exhaustive.
 #[derive(Clone, Default, Debug, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum AnalysisKind {
     /// Do not perform bytecode analysis.
     Raw,
     /// Perform bytecode analysis.
     #[default]
     Analyse,
+    /// Perform bytecode analysis and precompute the static gas of the basic blocks of legacy
+    /// bytecode, so it is charged once per block.
+    ///
+    /// Blocks are computed for the mainnet instructions and should not be used with the custom
+    /// instructions that charge different static gas.
+    GasBlocks,
+    /// Perform bytecode analysis and pre-decode legacy and EOF code into a stream of
+    /// instructions with the decoded immediates and the resolved static jumps.
+    Decoded,
 }
 
 #[cfg(test)]
@@ -741,4 +961,41 @@ mod tests {
             Err(InvalidTransaction::AccessListNotSupported)
         );
     }
+
+    #[test]
+    fn test_validate_tx_authorization_list() {
+        let mut env = Env::default();
+        env.tx.authorization_list = Some(AuthorizationList::Signed(vec![]));
+        assert_eq!(
+            env.validate_tx::<crate::CancunSpec>(),
+            Err(InvalidTransaction::AuthorizationListNotSupported)
+        );
+        assert_eq!(
+            env.validate_tx::<crate::PragueSpec>(),
+            Err(InvalidTransaction::EmptyAuthorizationList)
+        );
+
+        env.tx.transact_to = TransactTo::Create;
+        assert_eq!(
+            env.validate_tx::<crate::PragueSpec>(),
+            Err(InvalidTransaction::Eip7702CreateTransaction)
+        );
+    }
+
+    #[test]
+    fn apply_block_overrides() {
+        let mut block = BlockEnv::default();
+        block.set_blob_excess_gas_and_price(10);
+        block.apply_overrides(&BlockOverrides {
+            time: Some(U256::from(100)),
+            fee_recipient: Some(Address::with_last_byte(1)),
+            blob_base_fee: Some(U256::from(7)),
+            ..Default::default()
+        });
+        assert_eq!(block.timestamp, U256::from(100));
+        assert_eq!(block.coinbase, Address::with_last_byte(1));
+        assert_eq!(block.get_blob_gasprice(), Some(7));
+        assert_eq!(block.get_blob_excess_gas(), Some(10));
+        assert_eq!(block.number, U256::ZERO);
+    }
 }
@@ -10,22 +10,26 @@ extern crate alloc as std;
 mod bytecode;
 mod constants;
 pub mod db;
+pub mod eip7702;
 pub mod env;
 
 #[cfg(feature = "c-kzg")]
 pub mod kzg;
 pub mod precompile;
+pub mod receipt;
 pub mod result;
 pub mod specification;
 pub mod state;
+pub mod trie;
 pub mod utilities;
 pub use alloy_primitives::{
-    self, address, b256, bytes, fixed_bytes, hex, hex_literal, ruint, uint, Address, Bytes,
-    FixedBytes, Log, LogData, B256, I256, U256,
+    self, address, b256, bytes, fixed_bytes, hex, hex_literal, ruint, uint, Address, Bloom,
+    BloomInput, Bytes, FixedBytes, Log, LogData, B256, I256, U256,
 };
 pub use bitvec;
 pub use bytecode::*;
 pub use constants::*;
+pub use eip7702::{Authorization, AuthorizationList, RecoveredAuthorization, SignedAuthorization};
 pub use env::*;
 
 cfg_if::cfg_if! {
@@ -40,6 +44,7 @@ cfg_if::cfg_if! {
 #[cfg(feature = "c-kzg")]
 pub use kzg::{EnvKzgSettings, KzgSettings};
 pub use precompile::*;
+pub use receipt::*;
 pub use result::*;
 pub use specification::*;
 pub use state::*;
@@ -0,0 +1,194 @@
+//! Transaction receipt, logs bloom and receipts root.
+
+use crate::{keccak256, trie::ordered_trie_root, Bloom, ExecutionResult, Log, TxType, B256};
+use alloy_rlp::{Encodable, Header};
+use std::vec::Vec;
+
+/// Receipt of the executed transaction.
+///
+/// Only post-Byzantium receipts are supported, status is used instead of the intermediate
+/// state root.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Receipt {
+    /// Type of the transaction.
+    pub tx_type: TxType,
+    /// Status of the transaction, true if it was successful.
+    ///
+    /// [EIP-658](https://eips.ethereum.org/EIPS/eip-658)
+    pub success: bool,
+    /// Gas used by the transactions in the block up to and including this one.
+    pub cumulative_gas_used: u64,
+    /// Logs emitted by the transaction.
+    pub logs: Vec<Log>,
+    /// Bloom filter of the logs.
+    pub logs_bloom: Bloom,
+}
+
+impl Receipt {
+    /// Creates new receipt and calculates the bloom of the logs.
+    pub fn new(tx_type: TxType, success: bool, cumulative_gas_used: u64, logs: Vec<Log>) -> Self {
+        let logs_bloom = logs_bloom(&logs);
+        Self {
+            tx_type,
+            success,
+            cumulative_gas_used,
+            logs,
+            logs_bloom,
+        }
+    }
+
+    /// Creates new receipt from the result of the execution.
+    ///
+    /// `cumulative_gas_used` must already include the gas used by this transaction.
+    pub fn from_result(tx_type: TxType, result: ExecutionResult, cumulative_gas_used: u64) -> Self {
+        let success = result.is_success();
+        Self::new(tx_type, success, cumulative_gas_used, result.into_logs())
+    }
+
+    /// Length of the RLP list payload: `[status, cumulative_gas_used, logs_bloom, logs]`.
+    fn fields_len(&self) -> usize {
+        self.success.length()
+            + self.cumulative_gas_used.length()
+            + self.logs_bloom.length()
+            + alloy_rlp::list_length(&self.logs)
+    }
+
+    /// Encodes the receipt as it is found in the receipts trie.
+    ///
+    /// Typed receipts are prefixed with the transaction type byte as defined in
+    /// [EIP-2718](https://eips.ethereum.org/EIPS/eip-2718).
+    pub fn encode_2718(&self, out: &mut Vec<u8>) {
+        if !self.tx_type.is_legacy() {
+            out.push(self.tx_type.ty());
+        }
+        Header {
+            list: true,
+            payload_length: self.fields_len(),
+        }
+        .encode(out);
+        self.success.encode(out);
+        self.cumulative_gas_used.encode(out);
+        self.logs_bloom.encode(out);
+        alloy_rlp::encode_list(&self.logs, out);
+    }
+
+    /// Returns the EIP-2718 encoded receipt.
+    pub fn encoded_2718(&self) -> Vec<u8> {
+        let payload_length = self.fields_len();
+        let mut out =
+            Vec::with_capacity(1 + alloy_rlp::length_of_length(payload_length) + payload_length);
+        self.encode_2718(&mut out);
+        out
+    }
+}
+
+/// Calculates the bloom filter of the logs.
+pub fn logs_bloom<'a>(logs: impl IntoIterator<Item = &'a Log>) -> Bloom {
+    let mut bloom = Bloom::ZERO;
+    for log in logs {
+        bloom.accrue_log(log);
+    }
+    bloom
+}
+
+/// Calculates the bloom filter of the block by combining blooms of the receipts.
+pub fn receipts_bloom<'a>(receipts: impl IntoIterator<Item = &'a Receipt>) -> Bloom {
+    let mut bloom = Bloom::ZERO;
+    for receipt in receipts {
+        bloom.accrue_bloom(&receipt.logs_bloom);
+    }
+    bloom
+}
+
+/// Returns the hash of the RLP encoded list of logs.
+///
+/// Used by the state tests to compare logs of the execution.
+pub fn logs_rlp_hash(logs: &[Log]) -> B256 {
+    let mut out = Vec::with_capacity(alloy_rlp::list_length(logs));
+    alloy_rlp::encode_list(logs, &mut out);
+    keccak256(&out)
+}
+
+/// Calculates the receipts root of the block.
+pub fn receipts_root(receipts: &[Receipt]) -> B256 {
+    ordered_trie_root(receipts.iter().map(Receipt::encoded_2718))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{address, b256, bytes, hex, trie::EMPTY_ROOT_HASH, BloomInput, LogData};
+
+    fn log() -> Log {
+        Log {
+            address: address!("0000000000000000000000000000000000000011"),
+            data: LogData::new_unchecked(
+                vec![
+                    b256!("000000000000000000000000000000000000000000000000000000000000dead"),
+                    b256!("000000000000000000000000000000000000000000000000000000000000beef"),
+                ],
+                bytes!("0100ff"),
+            ),
+        }
+    }
+
+    #[test]
+    fn bloom_contains_log() {
+        let log = log();
+        let receipt = Receipt::new(TxType::Eip1559, true, 21000, vec![log.clone()]);
+        assert!(receipt.logs_bloom.contains_log(&log));
+        assert!(receipt
+            .logs_bloom
+            .contains_input(BloomInput::Raw(log.address.as_slice())));
+        assert_eq!(receipts_bloom([&receipt]), receipt.logs_bloom);
+        assert_eq!(logs_bloom(&[]), Bloom::ZERO);
+    }
+
+    #[test]
+    fn encode_legacy_receipt() {
+        let receipt = Receipt::new(TxType::Legacy, false, 0x1, vec![log()]);
+        let encoded = receipt.encoded_2718();
+        assert_eq!(encoded[0], 0xf9);
+        assert_eq!(&encoded[3..5], &hex!("8001"));
+
+        let typed = Receipt {
+            tx_type: TxType::Eip1559,
+            ..receipt
+        };
+        let typed_encoded = typed.encoded_2718();
+        assert_eq!(typed_encoded[0], 0x02);
+        assert_eq!(&typed_encoded[1..], &encoded[..]);
+    }
+
+    #[test]
+    fn receipts_root_of_block() {
+        assert_eq!(receipts_root(&[]), EMPTY_ROOT_HASH);
+
+        // Block with a single value transfer.
+        let transfer = Receipt::new(TxType::Legacy, true, 21000, vec![]);
+        assert_eq!(
+            receipts_root(core::slice::from_ref(&transfer)),
+            b256!("056b23fbba480696b65fe5a59b8f2148a1299103c4f57df839233af2cf4ca2d2")
+        );
+
+        let log = Log {
+            address: address!("0000000000000000000000000000000000000011"),
+            data: LogData::new_unchecked(
+                vec![b256!(
+                    "000000000000000000000000000000000000000000000000000000000000dead"
+                )],
+                bytes!("0100ff"),
+            ),
+        };
+        let receipts = [
+            transfer,
+            Receipt::new(TxType::Eip1559, false, 50000, vec![log.clone()]),
+            Receipt::new(TxType::BlobTx, true, 90000, vec![log]),
+        ];
+        assert_eq!(
+            receipts_root(&receipts),
+            b256!("9f5e0f08c29bd75f4df6be3c66ac8eb3c04609200a9ec3f37250164bb24d0824")
+        );
+    }
+}
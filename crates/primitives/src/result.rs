@@ -98,6 +98,14 @@ impl ExecutionResult {
             | Self::Halt { gas_used, .. } => gas_used,
         }
     }
+
+    /// Returns the gas refunded if execution is successful, or zero otherwise.
+    pub fn gas_refunded(&self) -> u64 {
+        match *self {
+            Self::Success { gas_refunded, .. } => gas_refunded,
+            _ => 0,
+        }
+    }
 }
 
 /// Output of a transaction execution.
@@ -152,6 +160,22 @@ pub enum EVMError<DBError> {
     Precompile(String),
 }
 
+impl<DBError> EVMError<DBError> {
+    /// Maps the database error with the given function, leaving other errors unchanged.
+    pub fn map_db_err<F, E>(self, op: F) -> EVMError<E>
+    where
+        F: FnOnce(DBError) -> E,
+    {
+        match self {
+            Self::Transaction(e) => EVMError::Transaction(e),
+            Self::Header(e) => EVMError::Header(e),
+            Self::Database(e) => EVMError::Database(op(e)),
+            Self::Custom(e) => EVMError::Custom(e),
+            Self::Precompile(e) => EVMError::Precompile(e),
+        }
+    }
+}
+
 #[cfg(feature = "std")]
 impl<DBError: std::error::Error + 'static> std::error::Error for EVMError<DBError> {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
@@ -252,6 +276,15 @@ pub enum InvalidTransaction {
     BlobVersionNotSupported,
     /// EOF crate should have `to` address
     EofCrateShouldHaveToAddress,
+    /// Authorization list is not supported before the Prague hardfork.
+    AuthorizationListNotSupported,
+    /// EIP-7702 transaction has invalid fields set.
+    AuthorizationListInvalidFields,
+    /// Empty Authorization List is not allowed.
+    EmptyAuthorizationList,
+    /// EIP-7702 transaction can't be a create transaction.
+    /// `to` must be present
+    Eip7702CreateTransaction,
     /// System transactions are not supported post-regolith hardfork.
     ///
     /// Before the Regolith hardfork, there was a special field in the `Deposit` transaction
@@ -343,6 +376,14 @@ impl fmt::Display for InvalidTransaction {
             }
             Self::BlobVersionNotSupported => write!(f, "blob version not supported"),
             Self::EofCrateShouldHaveToAddress => write!(f, "EOF crate should have `to` address"),
+            Self::AuthorizationListNotSupported => {
+                write!(f, "authorization list not supported")
+            }
+            Self::AuthorizationListInvalidFields => {
+                write!(f, "authorization list tx has invalid fields")
+            }
+            Self::EmptyAuthorizationList => write!(f, "empty authorization list"),
+            Self::Eip7702CreateTransaction => write!(f, "EIP-7702 create transaction"),
             #[cfg(feature = "optimism")]
             Self::DepositSystemTxPostRegolith => {
                 write!(
@@ -369,6 +410,8 @@ pub enum InvalidHeader {
     PrevrandaoNotSet,
     /// `excess_blob_gas` is not set for Cancun and above.
     ExcessBlobGasNotSet,
+    /// `parent_beacon_block_root` is not set for Cancun and above.
+    ParentBeaconBlockRootNotSet,
 }
 
 #[cfg(feature = "std")]
@@ -379,6 +422,7 @@ impl fmt::Display for InvalidHeader {
         match self {
             Self::PrevrandaoNotSet => write!(f, "`prevrandao` not set"),
             Self::ExcessBlobGasNotSet => write!(f, "`excess_blob_gas` not set"),
+            Self::ParentBeaconBlockRootNotSet => write!(f, "`parent_beacon_block_root` not set"),
         }
     }
 }
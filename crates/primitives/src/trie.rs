@@ -0,0 +1,304 @@
+//! Merkle Patricia Trie root calculation.
+//!
+//! Builds the root of the trie in a single pass over the sorted keys, without storing
+//! intermediate nodes. Used for receipts root and other ordered tries of the block,
+//! and for the state and storage roots.
+
+use crate::{keccak256, AccountInfo, B256, U256};
+use alloy_rlp::{Encodable, Header, RlpEncodable, EMPTY_STRING_CODE};
+use std::{collections::BTreeMap, vec::Vec};
+
+/// Root hash of an empty trie: `keccak256(rlp(""))`.
+pub const EMPTY_ROOT_HASH: B256 =
+    crate::b256!("56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421");
+
+/// Account as it is stored in the state trie.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, RlpEncodable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrieAccount {
+    /// Nonce of the account.
+    pub nonce: u64,
+    /// Balance of the account.
+    pub balance: U256,
+    /// Root of the storage trie of the account.
+    pub storage_root: B256,
+    /// Hash of the account code.
+    pub code_hash: B256,
+}
+
+impl TrieAccount {
+    /// Creates new trie account from account info and the storage root.
+    pub fn new(info: &AccountInfo, storage_root: B256) -> Self {
+        Self {
+            nonce: info.nonce,
+            balance: info.balance,
+            storage_root,
+            code_hash: info.code_hash,
+        }
+    }
+
+    /// Returns the RLP encoded account, the value of the leaf in the state trie.
+    pub fn encoded(&self) -> Vec<u8> {
+        alloy_rlp::encode(self)
+    }
+}
+
+/// Calculates the storage root of the account.
+///
+/// Slots with zero value are not part of the storage trie and are skipped.
+pub fn storage_root<I>(storage: I) -> B256
+where
+    I: IntoIterator<Item = (U256, U256)>,
+{
+    sec_trie_root(
+        storage
+            .into_iter()
+            .filter(|(_, value)| *value != U256::ZERO)
+            .map(|(slot, value)| (slot.to_be_bytes::<32>(), alloy_rlp::encode(value))),
+    )
+}
+
+/// Calculates the root of the trie where the key of each value is `rlp(index)`.
+///
+/// This is the trie used for transactions, receipts and withdrawals roots of the block.
+pub fn ordered_trie_root<I, V>(values: I) -> B256
+where
+    I: IntoIterator<Item = V>,
+    V: AsRef<[u8]>,
+{
+    trie_root(values.into_iter().enumerate().map(|(index, value)| {
+        let mut key = Vec::with_capacity(index.length());
+        index.encode(&mut key);
+        (key, value)
+    }))
+}
+
+/// Calculates the root of the secure trie where keys are hashed with `keccak256`.
+///
+/// This is the trie used for the state and storage roots.
+pub fn sec_trie_root<I, K, V>(input: I) -> B256
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    trie_root(
+        input
+            .into_iter()
+            .map(|(key, value)| (keccak256(key.as_ref()), value)),
+    )
+}
+
+/// Calculates the root of the trie from key value pairs.
+///
+/// Input does not need to be sorted. If a key is repeated the last value is used.
+pub fn trie_root<I, K, V>(input: I) -> B256
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    let sorted = input
+        .into_iter()
+        .map(|(key, value)| (key.as_ref().to_vec(), value))
+        .collect::<BTreeMap<_, _>>();
+    let items = sorted
+        .iter()
+        .map(|(key, value)| (to_nibbles(key), value.as_ref()))
+        .collect::<Vec<_>>();
+
+    let mut root = Vec::new();
+    encode_node(&items, 0, &mut root);
+    keccak256(root)
+}
+
+/// Splits bytes into nibbles.
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Encodes nibbles with the hex prefix used for leaf and extension node paths.
+fn hex_prefix(nibbles: &[u8], is_leaf: bool) -> Vec<u8> {
+    let flag = if is_leaf { 2 } else { 0 };
+    let mut out = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        out.push(((flag + 1) << 4) | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        out.push(flag << 4);
+        nibbles
+    };
+    out.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    out
+}
+
+/// Writes the RLP list with the given already encoded payload.
+fn encode_list(payload: &[u8], out: &mut Vec<u8>) {
+    Header {
+        list: true,
+        payload_length: payload.len(),
+    }
+    .encode(out);
+    out.extend_from_slice(payload);
+}
+
+/// Writes the reference to the child node: node itself if shorter than 32 bytes,
+/// hash of the node otherwise.
+fn encode_reference(node: &[u8], out: &mut Vec<u8>) {
+    if node.len() < 32 {
+        out.extend_from_slice(node);
+    } else {
+        keccak256(node).encode(out);
+    }
+}
+
+/// Encodes the node that contains all `items`. All items share the first `depth` nibbles.
+fn encode_node(items: &[(Vec<u8>, &[u8])], depth: usize, out: &mut Vec<u8>) {
+    let mut payload = Vec::new();
+    match items {
+        [] => out.push(EMPTY_STRING_CODE),
+        [(key, value)] => {
+            hex_prefix(&key[depth..], true)
+                .as_slice()
+                .encode(&mut payload);
+            value.encode(&mut payload);
+            encode_list(&payload, out);
+        }
+        [(first, _), .., (last, _)] => {
+            // items are sorted so common prefix of first and last key is shared by all.
+            let shared = first[depth..]
+                .iter()
+                .zip(&last[depth..])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if shared > 0 {
+                let mut child = Vec::new();
+                encode_node(items, depth + shared, &mut child);
+                hex_prefix(&first[depth..depth + shared], false)
+                    .as_slice()
+                    .encode(&mut payload);
+                encode_reference(&child, &mut payload);
+                encode_list(&payload, out);
+                return;
+            }
+
+            // Branch node. Key that ends at this depth is the value of the branch and,
+            // being the shortest, it is always the first one.
+            let (value, mut rest) = if first.len() == depth {
+                (Some(items[0].1), &items[1..])
+            } else {
+                (None, items)
+            };
+            for nibble in 0..16u8 {
+                let split = rest
+                    .iter()
+                    .position(|(key, _)| key[depth] != nibble)
+                    .unwrap_or(rest.len());
+                let (children, tail) = rest.split_at(split);
+                rest = tail;
+                if children.is_empty() {
+                    payload.push(EMPTY_STRING_CODE);
+                } else {
+                    let mut child = Vec::new();
+                    encode_node(children, depth + 1, &mut child);
+                    encode_reference(&child, &mut payload);
+                }
+            }
+            match value {
+                Some(value) => value.encode(&mut payload),
+                None => payload.push(EMPTY_STRING_CODE),
+            }
+            encode_list(&payload, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{address, b256, hex, KECCAK_EMPTY};
+
+    #[test]
+    fn empty_root() {
+        assert_eq!(trie_root::<_, &[u8], &[u8]>([]), EMPTY_ROOT_HASH);
+        assert_eq!(ordered_trie_root::<_, &[u8]>([]), EMPTY_ROOT_HASH);
+        assert_eq!(keccak256([EMPTY_STRING_CODE]), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn hex_prefix_encoding() {
+        assert_eq!(hex_prefix(&[1, 2, 3, 4, 5], false), vec![0x11, 0x23, 0x45]);
+        assert_eq!(
+            hex_prefix(&[0, 1, 2, 3, 4, 5], false),
+            vec![0x00, 0x01, 0x23, 0x45]
+        );
+        assert_eq!(
+            hex_prefix(&[0, 15, 1, 12, 11, 8], true),
+            vec![0x20, 0x0f, 0x1c, 0xb8]
+        );
+        assert_eq!(
+            hex_prefix(&[15, 1, 12, 11, 8], true),
+            vec![0x3f, 0x1c, 0xb8]
+        );
+    }
+
+    #[test]
+    fn known_roots() {
+        // Example from the Ethereum wiki: `do`, `dog`, `doge` and `horse`.
+        let root = trie_root([
+            (&b"do"[..], &b"verb"[..]),
+            (b"dog", b"puppy"),
+            (b"doge", b"coin"),
+            (b"horse", b"stallion"),
+        ]);
+        assert_eq!(
+            root,
+            b256!("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+        );
+
+        // Later values override earlier ones and input order does not matter.
+        let root = trie_root([
+            (&b"horse"[..], &b"stallion"[..]),
+            (b"doge", b"coin"),
+            (b"dog", b"puppy"),
+            (b"do", b"noun"),
+            (b"do", b"verb"),
+        ]);
+        assert_eq!(
+            root,
+            b256!("5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84")
+        );
+
+        let root = trie_root([(hex!("0045"), hex!("0123456789"))]);
+        assert_eq!(
+            root,
+            b256!("fa8e0678be0c95c8a5700e8208225681f42d1beb4c74f3b1c7669ab45654eb60")
+        );
+    }
+
+    #[test]
+    fn account_and_storage_root() {
+        assert_eq!(storage_root([(U256::from(1), U256::ZERO)]), EMPTY_ROOT_HASH);
+
+        let storage = [(U256::from(1), U256::from(2)), (U256::from(2), U256::ZERO)];
+        let root = storage_root(storage);
+        assert_eq!(
+            root,
+            sec_trie_root([(U256::from(1).to_be_bytes::<32>(), [0x02])])
+        );
+
+        let info = AccountInfo {
+            balance: U256::from(10),
+            nonce: 1,
+            code_hash: KECCAK_EMPTY,
+            code: None,
+        };
+        let account = TrieAccount::new(&info, root);
+        let address = address!("1000000000000000000000000000000000000001");
+        assert_eq!(account.encoded().len(), account.length());
+        assert_eq!(
+            sec_trie_root([(address, account.encoded())]),
+            trie_root([(keccak256(address), account.encoded())])
+        );
+    }
+}
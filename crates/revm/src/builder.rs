@@ -1,3 +1,5 @@
+#[cfg(feature = "std")]
+use crate::BytecodeCache;
 use crate::{
     db::{Database, DatabaseRef, EmptyDB, WrapDatabaseRef},
     handler::register,
@@ -8,6 +10,8 @@ use crate::{
 };
 use core::marker::PhantomData;
 use std::boxed::Box;
+#[cfg(feature = "std")]
+use std::sync::Arc;
 
 /// Evm Builder allows building or modifying EVM.
 /// Note that some of the methods that changes underlying structures
@@ -412,6 +416,19 @@ impl<'a, BuilderStage, EXT, DB: Database> EvmBuilder<'a, BuilderStage, EXT, DB>
         self
     }
 
+    /// Sets the cache of the analysed bytecode, see [`BytecodeCache`].
+    ///
+    /// The cache is kept when the database or the handler is changed, and is shared with
+    /// every other EVM that is built with it.
+    #[cfg(feature = "std")]
+    pub fn with_bytecode_cache(mut self, bytecode_cache: Arc<BytecodeCache>) -> Self {
+        self.context
+            .evm
+            .journaled_state
+            .set_bytecode_cache(Some(bytecode_cache));
+        self
+    }
+
     /// Clears Environment of EVM.
     pub fn with_clear_env(mut self) -> Self {
         self.context.evm.env.clear();
@@ -0,0 +1,399 @@
+//! Analysed bytecode cache shared between EVM instances.
+
+use crate::{
+    interpreter::analysis::to_analysed_with_kind,
+    primitives::{AnalysisKind, Bytecode, HashMap, B256},
+};
+use core::{
+    fmt,
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+use std::{collections::VecDeque, sync::RwLock};
+
+/// Default capacity of the [`BytecodeCache`], in bytes of the cached code.
+pub const DEFAULT_BYTECODE_CACHE_CAPACITY: usize = 64 * 1024 * 1024;
+
+/// Thread-safe cache of the analysed bytecode keyed by the code hash and the [`AnalysisKind`].
+///
+/// Called bytecode is analysed once per transaction, which for large contracts that are called
+/// in every transaction is a noticeable cost. When set on the
+/// [`JournaledState`](crate::JournaledState), the called code is analysed once with
+/// [`CfgEnv::called_bytecodes_analysis`](crate::primitives::CfgEnv::called_bytecodes_analysis)
+/// and shared by every transaction and every EVM that uses the same cache, see
+/// [`EvmBuilder::with_bytecode_cache`](crate::EvmBuilder::with_bytecode_cache).
+///
+/// Cache is bounded by the total length of the cached code. When full, entries are evicted
+/// with the CLOCK algorithm: entries that were hit since the last pass are given another
+/// chance, the rest are evicted in the insertion order.
+pub struct BytecodeCache {
+    /// Maximum total length of the cached code.
+    capacity: usize,
+    inner: RwLock<Inner>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// Code hash and the analysis of the cached bytecode.
+type Key = (B256, AnalysisKind);
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<Key, Entry>,
+    /// Keys in the eviction order.
+    queue: VecDeque<Key>,
+    /// Total length of the cached code.
+    size: usize,
+}
+
+struct Entry {
+    bytecode: Bytecode,
+    size: usize,
+    /// Set on hit, cleared when the entry is passed over by the eviction.
+    referenced: AtomicBool,
+}
+
+/// Counters of the [`BytecodeCache`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BytecodeCacheMetrics {
+    /// Number of lookups that found the code in the cache.
+    pub hits: u64,
+    /// Number of lookups that did not find the code in the cache.
+    pub misses: u64,
+    /// Number of entries evicted to stay within the capacity.
+    pub evictions: u64,
+    /// Number of cached entries.
+    pub entries: usize,
+    /// Total length of the cached code.
+    pub size: usize,
+}
+
+impl BytecodeCacheMetrics {
+    /// Returns the ratio of hits to all lookups, zero if there were no lookups.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+}
+
+impl Default for BytecodeCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_BYTECODE_CACHE_CAPACITY)
+    }
+}
+
+impl BytecodeCache {
+    /// Creates new cache that holds at most `capacity` bytes of code.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: RwLock::new(Inner::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the capacity of the cache in bytes of code.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the bytecode with the given hash analysed with `kind`, counting the hit or
+    /// the miss.
+    pub fn get(&self, code_hash: &B256, kind: &AnalysisKind) -> Option<Bytecode> {
+        let inner = self.inner.read().unwrap();
+        match inner.entries.get(&(*code_hash, kind.clone())) {
+            Some(entry) => {
+                entry.referenced.store(true, Ordering::Relaxed);
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(entry.bytecode.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Inserts the bytecode with the given hash, analysing it with `kind` if it is not.
+    ///
+    /// Returns the analysed bytecode. Bytecode larger than the capacity is not cached.
+    pub fn insert(&self, code_hash: B256, kind: &AnalysisKind, bytecode: Bytecode) -> Bytecode {
+        let bytecode = to_analysed_with_kind(bytecode, kind);
+        let size = bytecode.len();
+        if size > self.capacity {
+            return bytecode;
+        }
+
+        let key = (code_hash, kind.clone());
+        let mut inner = self.inner.write().unwrap();
+        if let Some(entry) = inner.entries.get(&key) {
+            // inserted by another thread in the meantime.
+            return entry.bytecode.clone();
+        }
+        while inner.size + size > self.capacity {
+            let Some(evicted) = inner.queue.pop_front() else {
+                break;
+            };
+            let entry = &inner.entries[&evicted];
+            if entry.referenced.swap(false, Ordering::Relaxed) {
+                inner.queue.push_back(evicted);
+                continue;
+            }
+            let entry = inner.entries.remove(&evicted).unwrap();
+            inner.size -= entry.size;
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+        inner.entries.insert(
+            key.clone(),
+            Entry {
+                bytecode: bytecode.clone(),
+                size,
+                referenced: AtomicBool::new(false),
+            },
+        );
+        inner.queue.push_back(key);
+        inner.size += size;
+        bytecode
+    }
+
+    /// Returns the cached bytecode with the given hash analysed with `kind`, or analyses and
+    /// caches the given one.
+    ///
+    /// Bytecode that is already analysed with `kind`, or that the analysis does not change, is
+    /// returned as-is without the lookup.
+    pub fn get_or_insert(
+        &self,
+        code_hash: B256,
+        kind: &AnalysisKind,
+        bytecode: Bytecode,
+    ) -> Bytecode {
+        if !needs_analysis(&bytecode, kind) {
+            return bytecode;
+        }
+        match self.get(&code_hash, kind) {
+            Some(bytecode) => bytecode,
+            None => self.insert(code_hash, kind, bytecode),
+        }
+    }
+
+    /// Returns the current counters.
+    pub fn metrics(&self) -> BytecodeCacheMetrics {
+        let inner = self.inner.read().unwrap();
+        BytecodeCacheMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: inner.entries.len(),
+            size: inner.size,
+        }
+    }
+
+    /// Removes all entries, keeping the counters.
+    pub fn clear(&self) {
+        *self.inner.write().unwrap() = Inner::default();
+    }
+}
+
+/// Returns whether the analysis with `kind` changes the bytecode.
+fn needs_analysis(bytecode: &Bytecode, kind: &AnalysisKind) -> bool {
+    match (bytecode, kind) {
+        (_, AnalysisKind::Raw) => false,
+        (Bytecode::LegacyRaw(_), _) => true,
+        (Bytecode::LegacyAnalyzed(analyzed), AnalysisKind::GasBlocks) => {
+            analyzed.gas_blocks().is_none()
+        }
+        (Bytecode::LegacyAnalyzed(analyzed), AnalysisKind::Decoded) => analyzed.decoded().is_none(),
+        (Bytecode::Eof(eof), AnalysisKind::Decoded) => eof.decoded().is_none(),
+        _ => false,
+    }
+}
+
+impl fmt::Debug for BytecodeCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BytecodeCache")
+            .field("capacity", &self.capacity)
+            .field("metrics", &self.metrics())
+            .finish()
+    }
+}
+
+/// Caches are equal only if they are the same cache.
+impl PartialEq for BytecodeCache {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self, other)
+    }
+}
+
+impl Eq for BytecodeCache {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{address, hex, AccountInfo, Bytes, Eof, SpecId, TransactTo, TxEnv, U256},
+        Evm,
+    };
+    use std::{sync::Arc, thread};
+
+    fn raw(code: &'static [u8]) -> (B256, Bytecode) {
+        let bytecode = Bytecode::new_raw(Bytes::from_static(code));
+        (bytecode.hash_slow(), bytecode)
+    }
+
+    #[test]
+    fn evicts_unreferenced_first() {
+        let cache = BytecodeCache::new(3);
+        let codes = [raw(&[0x01]), raw(&[0x02]), raw(&[0x03]), raw(&[0x04])];
+        let kind = AnalysisKind::Analyse;
+        for (hash, code) in &codes[..3] {
+            assert!(cache
+                .get_or_insert(*hash, &kind, code.clone())
+                .is_execution_ready());
+        }
+        assert!(cache.get(&codes[0].0, &kind).is_some());
+
+        cache.get_or_insert(codes[3].0, &kind, codes[3].1.clone());
+        assert!(cache.get(&codes[0].0, &kind).is_some());
+        assert!(cache.get(&codes[1].0, &kind).is_none());
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.hits, 2);
+        assert_eq!(metrics.misses, 5);
+        assert_eq!(metrics.evictions, 1);
+        assert_eq!(metrics.entries, 3);
+        assert_eq!(metrics.size, 3);
+    }
+
+    #[test]
+    fn keyed_by_kind() {
+        let cache = BytecodeCache::default();
+        let (hash, code) = raw(&hex!("600160005500"));
+        let analysed = cache.get_or_insert(hash, &AnalysisKind::Analyse, code.clone());
+        assert!(analysed.legacy_gas_blocks().is_none());
+        let with_blocks = cache.get_or_insert(hash, &AnalysisKind::GasBlocks, code.clone());
+        assert!(with_blocks.legacy_gas_blocks().is_some());
+        let decoded = cache.get_or_insert(hash, &AnalysisKind::Decoded, code);
+        assert!(decoded.decoded_code(0).is_some());
+
+        // already analysed bytecode is not looked up.
+        let again = cache.get_or_insert(hash, &AnalysisKind::GasBlocks, with_blocks.clone());
+        assert_eq!(again.legacy_gas_blocks(), with_blocks.legacy_gas_blocks());
+        assert_eq!(
+            cache
+                .get(&hash, &AnalysisKind::GasBlocks)
+                .unwrap()
+                .legacy_gas_blocks(),
+            with_blocks.legacy_gas_blocks()
+        );
+
+        let eof = Bytecode::Eof(Arc::new(Eof::default()));
+        let eof_hash = eof.hash_slow();
+        assert_eq!(
+            cache.get_or_insert(eof_hash, &AnalysisKind::Analyse, eof.clone()),
+            eof
+        );
+        let decoded = cache.get_or_insert(eof_hash, &AnalysisKind::Decoded, eof);
+        assert!(decoded.decoded_code(0).is_some());
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.misses, 4);
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.entries, 4);
+    }
+
+    #[test]
+    fn shared_between_evms() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let contract = address!("1000000000000000000000000000000000000002");
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(10_000_000)));
+        // PUSH1 1, PUSH1 0, SSTORE, STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&hex!("600160005500")));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let cache = Arc::new(BytecodeCache::default());
+        thread::scope(|scope| {
+            for _ in 0..2 {
+                scope.spawn(|| {
+                    let mut evm = Evm::builder()
+                        .with_ref_db(&db)
+                        .with_bytecode_cache(cache.clone())
+                        .modify_tx_env(|tx| {
+                            tx.caller = caller;
+                            tx.transact_to = TransactTo::Call(contract);
+                        })
+                        .build();
+                    for _ in 0..2 {
+                        let result = evm.transact().unwrap();
+                        assert!(result.result.is_success());
+                    }
+                });
+            }
+        });
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics.hits + metrics.misses, 4);
+        assert!(metrics.hits >= 2);
+        assert_eq!(metrics.entries, 1);
+    }
+
+    #[test]
+    fn survives_across_evms() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let contract = address!("1000000000000000000000000000000000000002");
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(10_000_000)));
+        // PUSH1 1, PUSH1 0, SSTORE, STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&hex!("600160005500")));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let cache = Arc::new(BytecodeCache::default());
+        let call = |tx: &mut TxEnv| {
+            tx.caller = caller;
+            tx.transact_to = TransactTo::Call(contract);
+        };
+        let mut evm = Evm::builder()
+            .with_ref_db(&db)
+            .with_bytecode_cache(cache.clone())
+            .modify_tx_env(call)
+            .build();
+        assert!(evm.transact().unwrap().result.is_success());
+        assert_eq!(cache.metrics().misses, 1);
+
+        // kept when the evm is rebuilt with another database and handler.
+        let mut evm = evm
+            .modify()
+            .reset_handler_with_ref_db(&db)
+            .with_spec_id(SpecId::SHANGHAI)
+            .build();
+        assert!(evm.transact().unwrap().result.is_success());
+        assert_eq!(cache.metrics().hits, 1);
+        drop(evm);
+
+        // and shared with a new evm.
+        let mut evm = Evm::builder()
+            .with_ref_db(&db)
+            .with_bytecode_cache(cache.clone())
+            .modify_tx_env(call)
+            .build();
+        assert!(evm.transact().unwrap().result.is_success());
+
+        let metrics = cache.metrics();
+        assert_eq!((metrics.hits, metrics.misses), (2, 1));
+        assert_eq!(metrics.entries, 1);
+    }
+}
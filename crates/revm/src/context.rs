@@ -13,7 +13,10 @@ use revm_interpreter::as_usize_saturated;
 use crate::{
     db::{Database, EmptyDB},
     interpreter::{Host, LoadAccountResult, SStoreResult, SelfDestructResult},
-    primitives::{Address, Bytes, Env, HandlerCfg, Log, B256, BLOCK_HASH_HISTORY, U256},
+    primitives::{
+        Address, Bytes, Env, HandlerCfg, Log, SpecId::PRAGUE, B256, BLOCKHASH_SERVE_WINDOW,
+        BLOCK_HASH_HISTORY, U256,
+    },
 };
 use std::boxed::Box;
 
@@ -121,6 +124,18 @@ impl<EXT, DB: Database> Host for Context<EXT, DB> {
             return Some(B256::ZERO);
         }
 
+        // EIP-2935: Serve historical block hashes from state
+        if self.evm.journaled_state.spec.is_enabled_in(PRAGUE) {
+            if diff <= BLOCKHASH_SERVE_WINDOW {
+                return self
+                    .evm
+                    .block_hash_from_history_storage(number)
+                    .map_err(|e| self.evm.error = Err(e))
+                    .ok();
+            }
+            return Some(B256::ZERO);
+        }
+
         if diff <= BLOCK_HASH_HISTORY {
             return self
                 .evm
@@ -7,7 +7,7 @@ use crate::{
     interpreter::{
         return_ok, CallInputs, Contract, Gas, InstructionResult, Interpreter, InterpreterResult,
     },
-    primitives::{Address, Bytes, EVMError, Env, HashSet, U256},
+    primitives::{Address, Bytecode, Bytes, EVMError, Env, HashSet, U256},
     ContextPrecompiles, FrameOrResult, CALL_STACK_LIMIT,
 };
 use core::{
@@ -167,12 +167,21 @@ impl<DB: Database> EvmContext<DB> {
             return return_result(InstructionResult::CallTooDeep);
         }
 
-        let (account, _) = self
-            .inner
-            .journaled_state
-            .load_code(inputs.bytecode_address, &mut self.inner.db)?;
-        let code_hash = account.info.code_hash();
-        let bytecode = account.info.code.clone().unwrap_or_default();
+        let kind = self.inner.env.cfg.called_bytecodes_analysis();
+        let (mut code_hash, mut bytecode, _) = self.inner.journaled_state.load_analysed_code(
+            inputs.bytecode_address,
+            &mut self.inner.db,
+            kind,
+        )?;
+
+        // EIP-7702: Execute the code of the delegated account.
+        if let Bytecode::Eip7702(eip7702_bytecode) = &bytecode {
+            (code_hash, bytecode, _) = self.inner.journaled_state.load_analysed_code(
+                eip7702_bytecode.delegated_address,
+                &mut self.inner.db,
+                kind,
+            )?;
+        }
 
         // Create subroutine checkpoint
         let checkpoint = self.journaled_state.checkpoint();
@@ -313,10 +322,10 @@ mod tests {
     use super::*;
     use crate::{
         db::{CacheDB, EmptyDB},
-        primitives::{address, Bytecode},
+        primitives::{address, AnalysisKind, Bytecode},
         Frame, JournalEntry,
     };
-    use std::boxed::Box;
+    use std::{boxed::Box, sync::Arc};
     use test_utils::*;
 
     // Tests that the `EVMContext::make_call_frame` function returns an error if the
@@ -402,4 +411,47 @@ mod tests {
         };
         assert_eq!(call_frame.return_memory_range, 0..0,);
     }
+
+    #[test]
+    fn test_make_call_frame_analyses_code_once() {
+        let mut env = Env::default();
+        env.cfg.perf_analyse_called_bytecodes = Some(AnalysisKind::GasBlocks);
+        let mut cdb = CacheDB::new(EmptyDB::default());
+        let by = Bytecode::new_raw(Bytes::from(vec![0x60, 0x00, 0x60, 0x00]));
+        let contract = address!("dead10000000000000000000000000000001dead");
+        cdb.insert_account_info(
+            contract,
+            crate::primitives::AccountInfo {
+                code_hash: by.hash_slow(),
+                code: Some(by.clone()),
+                ..Default::default()
+            },
+        );
+        let mut evm_context = create_cache_db_evm_context(Box::new(env), cdb);
+        let call_inputs = test_utils::create_mock_call_inputs(contract);
+        let frame_code = |evm_context: &mut EvmContext<_>| {
+            let res = evm_context.make_call_frame(&call_inputs);
+            let Ok(FrameOrResult::Frame(Frame::Call(call_frame))) = res else {
+                panic!("Expected FrameOrResult::Frame(Frame::Call(..))");
+            };
+            call_frame.frame_data.interpreter.contract.bytecode
+        };
+        let first = frame_code(&mut evm_context);
+        assert!(first.legacy_gas_blocks().is_some());
+        let second = frame_code(&mut evm_context);
+        // reused, not analysed again.
+        assert!(Arc::ptr_eq(
+            &first.legacy_gas_blocks().unwrap().0,
+            &second.legacy_gas_blocks().unwrap().0
+        ));
+        assert_eq!(evm_context.journaled_state.analysed_code.len(), 1);
+
+        // the account keeps the code as loaded.
+        let account = &evm_context.journaled_state.state[&contract];
+        assert_eq!(account.info.code, Some(by));
+
+        // analysed code is dropped with the transaction.
+        evm_context.journaled_state.finalize();
+        assert!(evm_context.journaled_state.analysed_code.is_empty());
+    }
 }
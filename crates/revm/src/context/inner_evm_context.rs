@@ -1,16 +1,17 @@
 use crate::{
     db::Database,
     interpreter::{
-        analysis::to_analysed, gas, return_ok, Contract, CreateInputs, EOFCreateInputs, Gas,
-        InstructionResult, Interpreter, InterpreterResult, LoadAccountResult, SStoreResult,
+        analysis::to_analysed_with_kind, gas, return_ok, Contract, CreateInputs, EOFCreateInputs,
+        Gas, InstructionResult, Interpreter, InterpreterResult, LoadAccountResult, SStoreResult,
         SelfDestructResult, MAX_CODE_SIZE,
     },
     journaled_state::JournaledState,
     primitives::{
-        keccak256, Account, Address, AnalysisKind, Bytecode, Bytes, CreateScheme, EVMError, Env,
-        Eof, HashSet, Spec,
+        keccak256, Account, Address, Bytecode, Bytes, CreateScheme, EVMError, Env, Eof, HashSet,
+        Spec,
         SpecId::{self, *},
-        B256, EOF_MAGIC_BYTES, EOF_MAGIC_HASH, U256,
+        B256, BLOCKHASH_SERVE_WINDOW, BLOCKHASH_STORAGE_ADDRESS, EOF_MAGIC_BYTES, EOF_MAGIC_HASH,
+        U256,
     },
     FrameOrResult, JournalCheckpoint, CALL_STACK_LIMIT,
 };
@@ -125,6 +126,38 @@ impl<DB: Database> InnerEvmContext<DB> {
         self.db.block_hash(number).map_err(EVMError::Database)
     }
 
+    /// Fetch block hash from the storage of the EIP-2935 history storage contract.
+    ///
+    /// Slot is read from the journal if it is loaded there, so updates made in the current block
+    /// are visible, and from the database otherwise. Neither the account nor the slot is warmed
+    /// or journaled.
+    #[inline]
+    pub fn block_hash_from_history_storage(
+        &mut self,
+        number: U256,
+    ) -> Result<B256, EVMError<DB::Error>> {
+        let slot = number % U256::from(BLOCKHASH_SERVE_WINDOW);
+        let journaled = self
+            .journaled_state
+            .state
+            .get(&BLOCKHASH_STORAGE_ADDRESS)
+            .and_then(|account| account.storage.get(&slot));
+        let value = match journaled {
+            Some(value) => value.present_value,
+            None => {
+                let value = self
+                    .db
+                    .storage(BLOCKHASH_STORAGE_ADDRESS, slot)
+                    .map_err(EVMError::Database)?;
+                if let Some(read_set) = &mut self.journaled_state.read_set {
+                    read_set.record_storage(BLOCKHASH_STORAGE_ADDRESS, slot, value);
+                }
+                value
+            }
+        };
+        Ok(value.into())
+    }
+
     /// Mark account as touched as only touched accounts will be added to state.
     #[inline]
     pub fn touch(&mut self, address: &Address) {
@@ -294,10 +327,14 @@ impl<DB: Database> InnerEvmContext<DB> {
             }
         };
 
+        // fine to clone as it is Bytes.
+        let bytecode = to_analysed_with_kind(
+            Bytecode::Eof(Arc::new(inputs.eof_init_code.clone())),
+            &self.env.cfg.perf_analyse_created_bytecodes,
+        );
         let contract = Contract::new(
             inputs.input.clone(),
-            // fine to clone as it is Bytes.
-            Bytecode::Eof(Arc::new(inputs.eof_init_code.clone())),
+            bytecode,
             None,
             inputs.created_address,
             inputs.caller,
@@ -506,12 +543,10 @@ impl<DB: Database> InnerEvmContext<DB> {
         self.journaled_state.checkpoint_commit();
 
         // Do analysis of bytecode straight away.
-        let bytecode = match self.env.cfg.perf_analyse_created_bytecodes {
-            AnalysisKind::Raw => Bytecode::new_raw(interpreter_result.output.clone()),
-            AnalysisKind::Analyse => {
-                to_analysed(Bytecode::new_raw(interpreter_result.output.clone()))
-            }
-        };
+        let bytecode = to_analysed_with_kind(
+            Bytecode::new_raw(interpreter_result.output.clone()),
+            &self.env.cfg.perf_analyse_created_bytecodes,
+        );
 
         // set code
         self.journaled_state.set_code(address, bytecode);
@@ -1,22 +1,32 @@
 //! [Database] implementations.
 
-//#[cfg(feature = "alloydb")]
-//pub mod alloydb;
+#[cfg(feature = "alloydb")]
+pub mod alloydb;
+pub mod dump;
 pub mod emptydb;
 #[cfg(feature = "ethersdb")]
 pub mod ethersdb;
 pub mod in_memory_db;
+#[cfg(feature = "std")]
+pub mod mv_memory;
+pub mod overlay;
+pub mod state_override;
 pub mod states;
 
 pub use crate::primitives::db::*;
-//#[cfg(feature = "alloydb")]
-//pub use alloydb::AlloyDB;
+#[cfg(feature = "alloydb")]
+pub use alloydb::{AlloyDB, AlloyDBCache, AlloyDBError, AlloyTransport, StateTransport};
+pub use dump::{Alloc, DumpAccount, DumpError, StateDump};
 pub use emptydb::{EmptyDB, EmptyDBTyped};
 #[cfg(feature = "ethersdb")]
 pub use ethersdb::EthersDB;
 pub use in_memory_db::*;
+#[cfg(feature = "std")]
+pub use mv_memory::{MvDatabase, MvDatabaseError, MvMemory};
+pub use overlay::{OverlayAccount, OverlayDB, OverlayDiff, OverlayRevertError, OverlaySnapshot};
+pub use state_override::{AccountOverride, StateOverride, StateOverrideDB, StateOverrideError};
 pub use states::{
     AccountRevert, AccountStatus, BundleAccount, BundleState, CacheState, DBBox,
-    OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox,
+    OriginalValuesKnown, PlainAccount, RevertToSlot, State, StateBuilder, StateDBBox, StateRoot,
     StorageWithOriginalValues, TransitionAccount, TransitionState,
 };
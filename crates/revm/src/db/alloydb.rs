@@ -1,171 +1,613 @@
 use crate::{
-    db::{Database, DatabaseRef},
-    primitives::{AccountInfo, Address, Bytecode, B256, KECCAK_EMPTY, U256},
+    db::{DatabaseAsync, DatabaseAsyncRef},
+    primitives::{AccountInfo, Address, Bytecode, HashMap, B256, KECCAK_EMPTY, U256},
 };
+use alloy_eips::{BlockId, BlockNumberOrTag};
 use alloy_provider::{Network, Provider};
-use alloy_rpc_types::BlockId;
-use alloy_transport::{Transport, TransportError};
-use tokio::runtime::{Builder, Handle};
+use alloy_transport::{Transport, TransportError, TransportErrorKind};
+use core::{fmt, future::Future};
+use futures::future::try_join_all;
+use std::{
+    fs, io,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::RwLock,
+    vec::Vec,
+};
 
-/// An alloy-powered REVM [Database].
+/// Source of the state that [`AlloyDB`] fetches from.
 ///
-/// When accessing the database, it'll use the given provider to fetch the corresponding account's data.
+/// Implemented for alloy providers by [`AlloyTransport`]. Tests can implement it for
+/// an in-process mock instead of querying a node.
+pub trait StateTransport: Send + Sync {
+    /// Error returned by the transport.
+    type Error: Send;
+
+    /// Fetches the account info with its code at the block.
+    fn account(
+        &self,
+        address: Address,
+        block: BlockId,
+    ) -> impl Future<Output = Result<AccountInfo, Self::Error>> + Send;
+
+    /// Fetches the storage slot of the account at the block.
+    fn storage(
+        &self,
+        address: Address,
+        index: U256,
+        block: BlockId,
+    ) -> impl Future<Output = Result<U256, Self::Error>> + Send;
+
+    /// Fetches the hash of the block with the given number.
+    fn block_hash(&self, number: u64) -> impl Future<Output = Result<B256, Self::Error>> + Send;
+}
+
+/// [`StateTransport`] that fetches the state with an alloy [Provider].
 #[derive(Debug, Clone)]
-pub struct AlloyDB<T: Transport + Clone, N: Network, P: Provider<T, N>> {
+pub struct AlloyTransport<T: Transport + Clone, N: Network, P: Provider<T, N>> {
     /// The provider to fetch the data from.
     provider: P,
+    _marker: PhantomData<fn() -> (T, N)>,
+}
+
+impl<T: Transport + Clone, N: Network, P: Provider<T, N>> AlloyTransport<T, N, P> {
+    /// Create a new transport with the given [Provider].
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Transport + Clone, N: Network, P: Provider<T, N>> StateTransport
+    for AlloyTransport<T, N, P>
+{
+    type Error = TransportError;
+
+    async fn account(&self, address: Address, block: BlockId) -> Result<AccountInfo, Self::Error> {
+        let nonce = self.provider.get_transaction_count(address).block_id(block);
+        let balance = self.provider.get_balance(address).block_id(block);
+        let code = self.provider.get_code_at(address).block_id(block);
+        let (nonce, balance, code) = tokio::join!(nonce, balance, code);
+
+        let code = Bytecode::new_raw(code?);
+        Ok(AccountInfo::new(balance?, nonce?, code.hash_slow(), code))
+    }
+
+    async fn storage(
+        &self,
+        address: Address,
+        index: U256,
+        block: BlockId,
+    ) -> Result<U256, Self::Error> {
+        self.provider
+            .get_storage_at(address, index)
+            .block_id(block)
+            .await
+    }
+
+    async fn block_hash(&self, number: u64) -> Result<B256, Self::Error> {
+        self.provider
+            .get_block_by_number(number.into(), false)
+            .await?
+            .and_then(|block| block.header.hash)
+            .ok_or_else(|| TransportErrorKind::custom_str("block not found"))
+    }
+}
+
+/// State fetched by the [`AlloyDB`], it can be persisted on disk per block.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AlloyDBCache {
+    /// Account infos, without the code.
+    pub accounts: HashMap<Address, AccountInfo>,
+    /// Code of the accounts, by its hash.
+    pub contracts: HashMap<B256, Bytecode>,
+    /// Storage slots of the accounts.
+    pub storage: HashMap<Address, HashMap<U256, U256>>,
+    /// Block hashes, by number.
+    pub block_hashes: HashMap<u64, B256>,
+}
+
+/// Error of the [`AlloyDB`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AlloyDBError<E> {
+    /// Error of the [`StateTransport`].
+    Transport(E),
+    /// Code is not cached. Nodes don't serve the code by its hash, so it is only
+    /// known after an account with it is loaded.
+    CodeNotFound(B256),
+}
+
+impl<E: fmt::Display> fmt::Display for AlloyDBError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(err) => write!(f, "transport error: {err}"),
+            Self::CodeNotFound(hash) => write!(f, "code {hash} is not loaded"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for AlloyDBError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(err) => Some(err),
+            Self::CodeNotFound(_) => None,
+        }
+    }
+}
+
+/// An alloy-powered REVM [`DatabaseAsyncRef`].
+///
+/// When accessing the database, it'll use the given [`StateTransport`] to fetch the
+/// corresponding account's data. Fetched data is cached, so every account and storage slot
+/// is requested only once, and [`prefetch_accounts`](Self::prefetch_accounts) and
+/// [`prefetch_storage`](Self::prefetch_storage) fetch many of them concurrently.
+///
+/// Wrap it with [`WrapDatabaseAsync`](crate::db::WrapDatabaseAsync) to use it as the
+/// [`Database`](crate::db::Database).
+///
+/// With [`with_cache_dir`](Self::with_cache_dir) the cache is loaded from and
+/// [flushed](Self::flush_cache) to a file per block, so repeated runs on the same block
+/// don't hit the node.
+#[derive(Debug)]
+pub struct AlloyDB<T: StateTransport> {
+    /// The transport to fetch the data from.
+    transport: T,
     /// The block number on which the queries will be based on.
     block_number: BlockId,
-    _marker: std::marker::PhantomData<fn() -> (T, N)>,
+    /// Fetched state.
+    cache: RwLock<AlloyDBCache>,
+    /// Directory of the on-disk cache.
+    cache_dir: Option<PathBuf>,
 }
 
-impl<T: Transport + Clone, N: Network, P: Provider<T, N>> AlloyDB<T, N, P> {
-    /// Create a new AlloyDB instance, with a [Provider] and a block (Use None for latest).
+impl<T: Transport + Clone, N: Network, P: Provider<T, N>> AlloyDB<AlloyTransport<T, N, P>> {
+    /// Create a new AlloyDB instance, with a [Provider] and a block.
     pub fn new(provider: P, block_number: BlockId) -> Self {
+        Self::with_transport(AlloyTransport::new(provider), block_number)
+    }
+}
+
+impl<T: StateTransport> AlloyDB<T> {
+    /// Create a new AlloyDB instance, with a [`StateTransport`] and a block.
+    pub fn with_transport(transport: T, block_number: BlockId) -> Self {
         Self {
-            provider,
+            transport,
             block_number,
-            _marker: std::marker::PhantomData,
+            cache: RwLock::default(),
+            cache_dir: None,
         }
     }
 
-    /// Internal utility function that allows us to block on a future regardless of the runtime flavor.
-    #[inline]
-    fn block_on<F>(f: F) -> F::Output
-    where
-        F: std::future::Future + Send,
-        F::Output: Send,
-    {
-        match Handle::try_current() {
-            Ok(handle) => match handle.runtime_flavor() {
-                // This is essentially equal to tokio::task::spawn_blocking because tokio doesn't
-                // allow the current_thread runtime to block_in_place.
-                // See <https://docs.rs/tokio/latest/tokio/task/fn.block_in_place.html> for more info.
-                tokio::runtime::RuntimeFlavor::CurrentThread => std::thread::scope(move |s| {
-                    s.spawn(move || {
-                        Builder::new_current_thread()
-                            .enable_all()
-                            .build()
-                            .unwrap()
-                            .block_on(f)
-                    })
-                    .join()
-                    .unwrap()
-                }),
-                _ => tokio::task::block_in_place(move || handle.block_on(f)),
-            },
-            Err(_) => Builder::new_current_thread()
-                .enable_all()
-                .build()
-                .unwrap()
-                .block_on(f),
-        }
+    /// Persist the cache in the given directory, loading the cache of the block if it exists.
+    ///
+    /// Fails if the block is not given by number or hash, as the state of a block tag changes.
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> io::Result<Self> {
+        self.cache_dir = Some(cache_dir.into());
+        self.load_cache()?;
+        Ok(self)
     }
 
     /// Set the block number on which the queries will be based on.
-    pub fn set_block_number(&mut self, block_number: BlockId) {
+    ///
+    /// Cached state is dropped, and the on-disk cache of the block is loaded if enabled.
+    pub fn set_block_number(&mut self, block_number: BlockId) -> io::Result<()> {
         self.block_number = block_number;
+        *self.cache.get_mut().unwrap() = AlloyDBCache::default();
+        self.load_cache()
     }
-}
 
-impl<T: Transport + Clone, N: Network, P: Provider<T, N>> DatabaseRef for AlloyDB<T, N, P> {
-    type Error = TransportError;
+    /// Returns the path of the on-disk cache of the block, `None` if it is not enabled.
+    pub fn cache_path(&self) -> io::Result<Option<PathBuf>> {
+        let Some(cache_dir) = &self.cache_dir else {
+            return Ok(None);
+        };
+        let name = match self.block_number {
+            BlockId::Number(BlockNumberOrTag::Number(number)) => number.to_string(),
+            BlockId::Hash(hash) => hash.block_hash.to_string(),
+            BlockId::Number(tag) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("state at block {tag} can't be cached"),
+                ))
+            }
+        };
+        Ok(Some(Path::new(cache_dir).join(format!("{name}.json"))))
+    }
 
-    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
-        let f = async {
-            let nonce = self
-                .provider
-                .get_transaction_count(address, self.block_number);
-            let balance = self.provider.get_balance(address, self.block_number);
-            let code = self.provider.get_code_at(address, self.block_number);
-            tokio::join!(nonce, balance, code)
+    /// Loads the on-disk cache of the block into the cache, if it exists.
+    fn load_cache(&mut self) -> io::Result<()> {
+        let Some(path) = self.cache_path()? else {
+            return Ok(());
         };
+        match fs::read(path) {
+            Ok(bytes) => {
+                *self.cache.get_mut().unwrap() = serde_json::from_slice(&bytes)?;
+                Ok(())
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
 
-        let (nonce, balance, code) = Self::block_on(f);
+    /// Writes the cache to the disk, if enabled.
+    pub fn flush_cache(&self) -> io::Result<()> {
+        let Some(path) = self.cache_path()? else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let bytes = serde_json::to_vec(&*self.cache.read().unwrap())?;
+        fs::write(path, bytes)
+    }
+
+    /// Returns the copy of the cached state.
+    pub fn cache(&self) -> AlloyDBCache {
+        self.cache.read().unwrap().clone()
+    }
 
-        let balance = balance?;
-        let code = Bytecode::new_raw(code?.0.into());
-        let code_hash = code.hash_slow();
-        let nonce = nonce?;
+    /// Inserts the fetched account into the cache and returns it.
+    fn insert_account(&self, address: Address, mut info: AccountInfo) -> AccountInfo {
+        let mut cache = self.cache.write().unwrap();
+        if let Some(code) = info.code.take() {
+            cache.contracts.insert(info.code_hash, code);
+        }
+        cache.accounts.insert(address, info.clone());
+        info.code = cache.contracts.get(&info.code_hash).cloned();
+        info
+    }
+
+    /// Returns the cached account with its code.
+    fn cached_account(&self, address: &Address) -> Option<AccountInfo> {
+        let cache = self.cache.read().unwrap();
+        let mut info = cache.accounts.get(address)?.clone();
+        info.code = cache.contracts.get(&info.code_hash).cloned();
+        Some(info)
+    }
 
-        Ok(Some(AccountInfo::new(balance, nonce, code_hash, code)))
+    /// Returns the cached storage slot.
+    fn cached_storage(&self, address: &Address, index: &U256) -> Option<U256> {
+        self.cache
+            .read()
+            .unwrap()
+            .storage
+            .get(address)
+            .and_then(|storage| storage.get(index).copied())
     }
 
-    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+    /// Fetches the accounts that are not cached yet, concurrently.
+    pub async fn prefetch_accounts(&self, addresses: &[Address]) -> Result<(), T::Error> {
+        let missing = {
+            let cache = self.cache.read().unwrap();
+            addresses
+                .iter()
+                .filter(|address| !cache.accounts.contains_key(*address))
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let accounts = try_join_all(
+            missing
+                .iter()
+                .map(|address| self.transport.account(*address, self.block_number)),
+        )
+        .await?;
+        for (address, info) in missing.into_iter().zip(accounts) {
+            self.insert_account(address, info);
+        }
+        Ok(())
+    }
+
+    /// Fetches the storage slots that are not cached yet, concurrently.
+    pub async fn prefetch_storage(&self, slots: &[(Address, U256)]) -> Result<(), T::Error> {
+        let missing = {
+            let cache = self.cache.read().unwrap();
+            slots
+                .iter()
+                .filter(|(address, index)| {
+                    !cache
+                        .storage
+                        .get(address)
+                        .is_some_and(|storage| storage.contains_key(index))
+                })
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let values =
+            try_join_all(missing.iter().map(|(address, index)| {
+                self.transport.storage(*address, *index, self.block_number)
+            }))
+            .await?;
+        let mut cache = self.cache.write().unwrap();
+        for ((address, index), value) in missing.into_iter().zip(values) {
+            cache
+                .storage
+                .entry(address)
+                .or_default()
+                .insert(index, value);
+        }
+        Ok(())
+    }
+}
+
+impl<T: StateTransport> DatabaseAsyncRef for AlloyDB<T> {
+    type Error = AlloyDBError<T::Error>;
+
+    async fn basic_async_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(info) = self.cached_account(&address) {
+            return Ok(Some(info));
+        }
+
+        let info = self
+            .transport
+            .account(address, self.block_number)
+            .await
+            .map_err(AlloyDBError::Transport)?;
+        Ok(Some(self.insert_account(address, info)))
+    }
+
+    async fn code_by_hash_async_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        // Code can't be fetched by hash, it is loaded with the account.
+        self.cache
+            .read()
+            .unwrap()
+            .contracts
+            .get(&code_hash)
+            .cloned()
+            .ok_or(AlloyDBError::CodeNotFound(code_hash))
+    }
+
+    async fn storage_async_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(value) = self.cached_storage(&address, &index) {
+            return Ok(value);
+        }
+
+        let value = self
+            .transport
+            .storage(address, index, self.block_number)
+            .await
+            .map_err(AlloyDBError::Transport)?;
+        self.cache
+            .write()
+            .unwrap()
+            .storage
+            .entry(address)
+            .or_default()
+            .insert(index, value);
+        Ok(value)
+    }
+
+    async fn block_hash_async_ref(&self, number: U256) -> Result<B256, Self::Error> {
         // Saturate usize
         if number > U256::from(u64::MAX) {
             return Ok(KECCAK_EMPTY);
         }
+        // SAFETY: We know number <= u64::MAX, so we can safely convert it to u64
+        let number = number.to::<u64>();
+        let cached = self
+            .cache
+            .read()
+            .unwrap()
+            .block_hashes
+            .get(&number)
+            .copied();
+        if let Some(hash) = cached {
+            return Ok(hash);
+        }
 
-        let block = Self::block_on(
-            self.provider
-                // SAFETY: We know number <= u64::MAX, so we can safely convert it to u64
-                .get_block_by_number(number.to::<u64>().into(), false),
-        )?;
-        // SAFETY: If the number is given, the block is supposed to be finalized, so unwrapping is safe.
-        Ok(B256::new(*block.unwrap().header.hash.unwrap()))
+        let hash = self
+            .transport
+            .block_hash(number)
+            .await
+            .map_err(AlloyDBError::Transport)?;
+        self.cache
+            .write()
+            .unwrap()
+            .block_hashes
+            .insert(number, hash);
+        Ok(hash)
     }
-
-    fn code_by_hash_ref(&self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
-        panic!("This should not be called, as the code is already loaded");
-        // This is not needed, as the code is already loaded with basic_ref
+    async fn basic_many_async_ref(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        self.prefetch_accounts(addresses)
+            .await
+            .map_err(AlloyDBError::Transport)?;
+        let mut infos = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            infos.push(self.basic_async_ref(*address).await?);
+        }
+        Ok(infos)
     }
 
-    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
-        let slot_val = Self::block_on(self.provider.get_storage_at(
-            address,
-            index,
-            self.block_number,
-        ))?;
-        Ok(slot_val)
+    async fn storage_many_async_ref(
+        &self,
+        slots: &[(Address, U256)],
+    ) -> Result<Vec<U256>, Self::Error> {
+        self.prefetch_storage(slots)
+            .await
+            .map_err(AlloyDBError::Transport)?;
+        let mut values = Vec::with_capacity(slots.len());
+        for (address, index) in slots {
+            values.push(self.storage_async_ref(*address, *index).await?);
+        }
+        Ok(values)
     }
 }
 
-impl<T: Transport + Clone, N: Network, P: Provider<T, N>> Database for AlloyDB<T, N, P> {
-    type Error = TransportError;
+impl<T: StateTransport> DatabaseAsync for AlloyDB<T> {
+    type Error = AlloyDBError<T::Error>;
 
     #[inline]
-    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
-        <Self as DatabaseRef>::basic_ref(self, address)
+    async fn basic_async(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_async_ref(address).await
     }
 
     #[inline]
-    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
-        <Self as DatabaseRef>::code_by_hash_ref(self, code_hash)
+    async fn code_by_hash_async(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_async_ref(code_hash).await
     }
 
     #[inline]
-    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
-        <Self as DatabaseRef>::storage_ref(self, address, index)
+    async fn storage_async(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_async_ref(address, index).await
     }
 
     #[inline]
-    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
-        <Self as DatabaseRef>::block_hash_ref(self, number)
+    async fn block_hash_async(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_async_ref(number).await
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use alloy_provider::ProviderBuilder;
-
     use super::*;
+    use crate::{
+        db::{DatabaseRef, WrapDatabaseAsync},
+        primitives::{address, b256, Bytes},
+    };
+    use core::convert::Infallible;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::runtime::Builder;
+
+    fn wrap<T>(db: T) -> WrapDatabaseAsync<T> {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        WrapDatabaseAsync::with_runtime(db, runtime)
+    }
+
+    /// In-process transport that counts the requests.
+    #[derive(Default)]
+    struct MockTransport {
+        accounts: HashMap<Address, AccountInfo>,
+        storage: HashMap<(Address, U256), U256>,
+        requests: AtomicUsize,
+    }
+
+    impl StateTransport for MockTransport {
+        type Error = Infallible;
+
+        async fn account(&self, address: Address, _: BlockId) -> Result<AccountInfo, Infallible> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            Ok(self.accounts.get(&address).cloned().unwrap_or_default())
+        }
+
+        async fn storage(
+            &self,
+            address: Address,
+            index: U256,
+            _: BlockId,
+        ) -> Result<U256, Infallible> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            Ok(self
+                .storage
+                .get(&(address, index))
+                .copied()
+                .unwrap_or_default())
+        }
+
+        async fn block_hash(&self, number: u64) -> Result<B256, Infallible> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            Ok(B256::from(U256::from(number)))
+        }
+    }
+
+    fn mock() -> MockTransport {
+        let contract = address!("1000000000000000000000000000000000000001");
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]));
+        let mut transport = MockTransport::default();
+        transport.accounts.insert(
+            contract,
+            AccountInfo::new(U256::from(10), 1, code.hash_slow(), code),
+        );
+        transport
+            .storage
+            .insert((contract, U256::from(1)), U256::from(2));
+        transport
+    }
 
     #[test]
-    fn can_get_basic() {
-        let client = ProviderBuilder::new().on_http(
-            "https://mainnet.infura.io/v3/c60b0bb42f8a4c6481ecd229eddaca27"
-                .parse()
+    fn fetches_once() {
+        let contract = address!("1000000000000000000000000000000000000001");
+        let other = address!("1000000000000000000000000000000000000002");
+        let db = wrap(AlloyDB::with_transport(mock(), BlockId::from(100)));
+        let requests = || db.db().transport.requests.load(Ordering::SeqCst);
+
+        assert_eq!(db.basic_many_ref(&[contract, other]).unwrap().len(), 2);
+        assert_eq!(
+            db.storage_many_ref(&[(contract, U256::from(1)), (contract, U256::from(2))]),
+            Ok(vec![U256::from(2), U256::ZERO])
+        );
+        assert_eq!(requests(), 4);
+
+        let info = db.basic_ref(contract).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(10));
+        assert_eq!(db.code_by_hash_ref(info.code_hash), Ok(info.code.unwrap()));
+        assert_eq!(db.storage_ref(contract, U256::from(1)), Ok(U256::from(2)));
+        assert_eq!(db.storage_ref(contract, U256::from(2)), Ok(U256::ZERO));
+        assert!(!db.basic_ref(other).unwrap().unwrap().exists());
+        assert_eq!(requests(), 4);
+
+        assert_eq!(
+            db.block_hash_ref(U256::from(99)),
+            Ok(b256!(
+                "0000000000000000000000000000000000000000000000000000000000000063"
+            ))
+        );
+        db.block_hash_ref(U256::from(99)).unwrap();
+        assert_eq!(requests(), 5);
+
+        // code of the accounts that are not loaded is not known.
+        let hash = B256::repeat_byte(1);
+        assert_eq!(
+            db.code_by_hash_ref(hash),
+            Err(AlloyDBError::CodeNotFound(hash))
+        );
+    }
+
+    #[test]
+    fn disk_cache() {
+        let contract = address!("1000000000000000000000000000000000000001");
+        let dir = std::env::temp_dir().join(format!("revm-alloydb-{}", std::process::id()));
+
+        let db = wrap(
+            AlloyDB::with_transport(mock(), BlockId::from(100))
+                .with_cache_dir(&dir)
                 .unwrap(),
         );
-        let alloydb = AlloyDB::new(client, BlockId::from(16148323));
+        db.storage_ref(contract, U256::from(1)).unwrap();
+        db.basic_ref(contract).unwrap();
+        db.db().flush_cache().unwrap();
 
-        // ETH/USDT pair on Uniswap V2
-        let address: Address = "0x0d4a11d5EEaaC28EC3F61d100daF4d40471f1852"
-            .parse()
-            .unwrap();
+        // cached state is served without requests.
+        let mut db = wrap(
+            AlloyDB::with_transport(MockTransport::default(), BlockId::from(100))
+                .with_cache_dir(&dir)
+                .unwrap(),
+        );
+        assert_eq!(db.storage_ref(contract, U256::from(1)), Ok(U256::from(2)));
+        assert_eq!(
+            db.basic_ref(contract).unwrap().unwrap().balance,
+            U256::from(10)
+        );
+        assert_eq!(db.db().transport.requests.load(Ordering::SeqCst), 0);
 
-        let acc_info = alloydb.basic_ref(address).unwrap().unwrap();
-        assert!(acc_info.exists());
+        // cache of other blocks is not shared.
+        db.db_mut().set_block_number(BlockId::from(101)).unwrap();
+        assert_eq!(db.storage_ref(contract, U256::from(1)), Ok(U256::ZERO));
+        assert!(db.db_mut().set_block_number(BlockId::latest()).is_err());
+        fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,527 @@
+//! Dump and load of the [`CacheDB`] state.
+
+use super::{AccountState, CacheDB, DbAccount};
+use crate::primitives::{AccountInfo, Address, Bytecode, Bytes, B256, KECCAK_EMPTY, U256};
+use core::fmt;
+use std::{collections::BTreeMap, vec::Vec};
+
+/// Accounts of the state dump by address, in the shape of the statetest `pre` alloc.
+pub type Alloc = BTreeMap<Address, DumpAccount>;
+
+/// Account in the state dump.
+///
+/// Shape matches the accounts of the anvil `dumpState` and of the statetest `pre` alloc.
+/// Nonce is serialized as a number, as anvil does, and is deserialized from a number or
+/// a hex or decimal string, as used by the statetests. The [`AccountState`] of the cached
+/// account is an extra field they omit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DumpAccount {
+    /// Account balance.
+    pub balance: U256,
+    /// Account nonce.
+    #[cfg_attr(feature = "serde", serde(with = "serde_nonce"))]
+    pub nonce: u64,
+    /// Account code, empty if the account has no code.
+    pub code: Bytes,
+    /// Account storage.
+    pub storage: BTreeMap<U256, U256>,
+    /// State of the cached account, `None` is the same as [`AccountState::None`].
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub state: Option<AccountState>,
+}
+
+/// Dump of the [`CacheDB`] state, see [`CacheDB::dump`].
+///
+/// Serializes in the shape of the anvil `dumpState`, so its dumps can be loaded as well.
+/// [`StateDump::to_bytes`] encodes it in a compact binary format for large states.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct StateDump {
+    /// Accounts by address.
+    pub accounts: Alloc,
+    /// Block hashes by number.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "BTreeMap::is_empty"))]
+    pub block_hashes: BTreeMap<U256, B256>,
+}
+
+/// Error returned when the binary state dump can't be decoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DumpError {
+    /// Input does not start with the dump magic bytes.
+    InvalidMagic,
+    /// Dump is encoded with an unsupported version.
+    UnsupportedVersion(u8),
+    /// Input ended before the dump was decoded.
+    UnexpectedEof,
+    /// Encoded value is out of range.
+    InvalidValue,
+}
+
+impl fmt::Display for DumpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidMagic => f.write_str("invalid state dump magic"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported state dump version {version}")
+            }
+            Self::UnexpectedEof => f.write_str("unexpected end of the state dump"),
+            Self::InvalidValue => f.write_str("invalid value in the state dump"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DumpError {}
+
+impl StateDump {
+    /// Magic bytes of the binary dump.
+    const MAGIC: &'static [u8; 4] = b"RVMD";
+    /// Version of the binary dump.
+    const VERSION: u8 = 1;
+
+    /// Encodes the dump in the compact binary format.
+    ///
+    /// Integers are encoded as LEB128 varints and the [`U256`] values with their leading
+    /// zero bytes trimmed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(Self::MAGIC);
+        out.push(Self::VERSION);
+
+        write_varint(&mut out, self.accounts.len() as u64);
+        for (address, account) in &self.accounts {
+            out.extend_from_slice(address.as_slice());
+            write_u256(&mut out, account.balance);
+            write_varint(&mut out, account.nonce);
+            write_varint(&mut out, account.code.len() as u64);
+            out.extend_from_slice(&account.code);
+            write_varint(&mut out, account.storage.len() as u64);
+            for (index, value) in &account.storage {
+                write_u256(&mut out, *index);
+                write_u256(&mut out, *value);
+            }
+            out.push(encode_state(account.state.as_ref()));
+        }
+
+        write_varint(&mut out, self.block_hashes.len() as u64);
+        for (number, hash) in &self.block_hashes {
+            write_u256(&mut out, *number);
+            out.extend_from_slice(hash.as_slice());
+        }
+        out
+    }
+
+    /// Decodes the dump from the compact binary format, see [`StateDump::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DumpError> {
+        let mut reader = Reader(bytes);
+        if reader.take(Self::MAGIC.len())? != Self::MAGIC {
+            return Err(DumpError::InvalidMagic);
+        }
+        let version = reader.take(1)?[0];
+        if version != Self::VERSION {
+            return Err(DumpError::UnsupportedVersion(version));
+        }
+
+        let mut dump = Self::default();
+        for _ in 0..reader.varint()? {
+            let address = Address::from_slice(reader.take(Address::len_bytes())?);
+            let balance = reader.u256()?;
+            let nonce = reader.varint()?;
+            let code_len = reader.len()?;
+            let code = Bytes::copy_from_slice(reader.take(code_len)?);
+            let mut storage = BTreeMap::new();
+            for _ in 0..reader.varint()? {
+                storage.insert(reader.u256()?, reader.u256()?);
+            }
+            let state = decode_state(reader.take(1)?[0])?;
+            dump.accounts.insert(
+                address,
+                DumpAccount {
+                    balance,
+                    nonce,
+                    code,
+                    storage,
+                    state,
+                },
+            );
+        }
+        for _ in 0..reader.varint()? {
+            let number = reader.u256()?;
+            let hash = B256::from_slice(reader.take(B256::len_bytes())?);
+            dump.block_hashes.insert(number, hash);
+        }
+        Ok(dump)
+    }
+}
+
+impl<ExtDB> CacheDB<ExtDB> {
+    /// Dumps the cached accounts with their states, their cached storage and the block hashes.
+    ///
+    /// Only the cached state is dumped, so code that was not loaded with the account is
+    /// missing from the dump.
+    pub fn dump(&self) -> StateDump {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|(address, account)| {
+                let code = account
+                    .info
+                    .code
+                    .as_ref()
+                    .or_else(|| self.contracts.get(&account.info.code_hash))
+                    .map(Bytecode::original_bytes)
+                    .unwrap_or_default();
+                let dump_account = DumpAccount {
+                    balance: account.info.balance,
+                    nonce: account.info.nonce,
+                    code,
+                    storage: account
+                        .storage
+                        .iter()
+                        .map(|(index, value)| (*index, *value))
+                        .collect(),
+                    state: (account.account_state != AccountState::None)
+                        .then(|| account.account_state.clone()),
+                };
+                (*address, dump_account)
+            })
+            .collect();
+        StateDump {
+            accounts,
+            block_hashes: self
+                .block_hashes
+                .iter()
+                .map(|(number, hash)| (*number, *hash))
+                .collect(),
+        }
+    }
+
+    /// Loads the dump, replacing the cached accounts it contains.
+    ///
+    /// Storage slots that are not in the dump are read from the underlying database.
+    pub fn load_dump(&mut self, dump: StateDump) {
+        self.load_alloc(dump.accounts);
+        self.block_hashes.extend(dump.block_hashes);
+    }
+
+    /// Loads the accounts, replacing the cached accounts with the same address.
+    ///
+    /// Storage slots that are not in the alloc are read from the underlying database, unless
+    /// the account state is [`AccountState::NotExisting`] or [`AccountState::StorageCleared`].
+    pub fn load_alloc(&mut self, alloc: Alloc) {
+        for (address, account) in alloc {
+            let mut info = AccountInfo::new(
+                account.balance,
+                account.nonce,
+                KECCAK_EMPTY,
+                Bytecode::new_raw(account.code),
+            );
+            self.insert_contract(&mut info);
+            self.accounts.insert(
+                address,
+                DbAccount {
+                    info,
+                    account_state: account.state.unwrap_or_default(),
+                    storage: account.storage.into_iter().collect(),
+                },
+            );
+        }
+    }
+}
+
+/// Encodes the account state as a byte, zero if it is not set.
+fn encode_state(state: Option<&AccountState>) -> u8 {
+    match state {
+        None | Some(AccountState::None) => 0,
+        Some(AccountState::NotExisting) => 1,
+        Some(AccountState::Touched) => 2,
+        Some(AccountState::StorageCleared) => 3,
+    }
+}
+
+/// Decodes the account state byte, see [`encode_state`].
+fn decode_state(byte: u8) -> Result<Option<AccountState>, DumpError> {
+    match byte {
+        0 => Ok(None),
+        1 => Ok(Some(AccountState::NotExisting)),
+        2 => Ok(Some(AccountState::Touched)),
+        3 => Ok(Some(AccountState::StorageCleared)),
+        _ => Err(DumpError::InvalidValue),
+    }
+}
+
+/// Writes the LEB128 varint.
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Writes the value with its leading zero bytes trimmed, prefixed with its length.
+fn write_u256(out: &mut Vec<u8>, value: U256) {
+    let bytes = value.to_be_bytes::<32>();
+    let trimmed = &bytes[(value.leading_zeros() / 8)..];
+    out.push(trimmed.len() as u8);
+    out.extend_from_slice(trimmed);
+}
+
+/// Reader of the binary dump.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DumpError> {
+        if self.0.len() < len {
+            return Err(DumpError::UnexpectedEof);
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn varint(&mut self) -> Result<u64, DumpError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f)
+                .checked_shl(shift)
+                .filter(|shifted| shifted >> shift == u64::from(byte & 0x7f))
+                .ok_or(DumpError::InvalidValue)?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DumpError::InvalidValue)
+    }
+
+    fn len(&mut self) -> Result<usize, DumpError> {
+        usize::try_from(self.varint()?).map_err(|_| DumpError::InvalidValue)
+    }
+
+    fn u256(&mut self) -> Result<U256, DumpError> {
+        let len = self.take(1)?[0] as usize;
+        if len > 32 {
+            return Err(DumpError::InvalidValue);
+        }
+        Ok(U256::from_be_slice(self.take(len)?))
+    }
+}
+
+/// Serializes the nonce as a number and deserializes it from a number or a string.
+#[cfg(feature = "serde")]
+mod serde_nonce {
+    use core::fmt;
+    use serde::{de, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(nonce: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(*nonce)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        struct NonceVisitor;
+
+        impl de::Visitor<'_> for NonceVisitor {
+            type Value = u64;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a number or a hex or decimal string")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+                Ok(value)
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<u64, E> {
+                match value.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => value.parse(),
+                }
+                .map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(NonceVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{Database, DatabaseCommit, DatabaseRef, EmptyDB},
+        primitives::{address, hex, SpecId, TransactTo},
+        Evm,
+    };
+
+    fn cache_db() -> CacheDB<EmptyDB> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        let contract = address!("1000000000000000000000000000000000000001");
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(
+                U256::from(1),
+                2,
+                KECCAK_EMPTY,
+                Bytecode::new_raw(Bytes::from_static(&hex!("6000"))),
+            ),
+        );
+        db.insert_account_storage(contract, U256::from(1), U256::MAX)
+            .unwrap();
+        db.insert_account_storage(contract, U256::from(2), U256::from(3))
+            .unwrap();
+        db.insert_account_info(
+            address!("1000000000000000000000000000000000000002"),
+            AccountInfo::from_balance(U256::from(300)),
+        );
+        db.basic(address!("1000000000000000000000000000000000000003"))
+            .unwrap();
+        db.block_hashes.insert(U256::from(7), B256::repeat_byte(7));
+        db
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let db = cache_db();
+        let dump = db.dump();
+        assert_eq!(dump.accounts.len(), 3);
+
+        let bytes = dump.to_bytes();
+        assert_eq!(StateDump::from_bytes(&bytes), Ok(dump.clone()));
+        assert_eq!(
+            StateDump::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DumpError::UnexpectedEof)
+        );
+        let mut other_version = bytes.clone();
+        other_version[4] = 2;
+        assert_eq!(
+            StateDump::from_bytes(&other_version),
+            Err(DumpError::UnsupportedVersion(2))
+        );
+
+        let mut loaded = CacheDB::new(EmptyDB::default());
+        loaded.load_dump(dump.clone());
+        assert_eq!(loaded.dump(), dump);
+        let contract = address!("1000000000000000000000000000000000000001");
+        assert_eq!(
+            loaded.basic_ref(contract).unwrap(),
+            db.basic_ref(contract).unwrap()
+        );
+        assert_eq!(loaded.storage_ref(contract, U256::from(1)), Ok(U256::MAX));
+        assert_eq!(
+            loaded.block_hash_ref(U256::from(7)),
+            Ok(B256::repeat_byte(7))
+        );
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn json_round_trip() {
+        let dump = cache_db().dump();
+        let json = serde_json::to_string(&dump).unwrap();
+        assert_eq!(serde_json::from_str::<StateDump>(&json).unwrap(), dump);
+        let mut existing = dump.accounts.clone();
+        existing.remove(&address!("1000000000000000000000000000000000000003"));
+
+        // statetest `pre` alloc.
+        let alloc: Alloc = serde_json::from_str(
+            r#"{
+                "0x1000000000000000000000000000000000000001": {
+                    "balance": "0x01",
+                    "code": "0x6000",
+                    "nonce": "0x02",
+                    "storage": {
+                        "0x01": "0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+                        "0x02": "0x03"
+                    }
+                },
+                "0x1000000000000000000000000000000000000002": {
+                    "balance": "0x012c",
+                    "code": "0x",
+                    "nonce": "0x00",
+                    "storage": {}
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(alloc, existing);
+
+        // anvil dump, with unknown fields.
+        let anvil: StateDump = serde_json::from_str(
+            r#"{
+                "block": { "number": "0x1" },
+                "accounts": {
+                    "0x1000000000000000000000000000000000000002": {
+                        "nonce": 0,
+                        "balance": "0x12c",
+                        "code": "0x",
+                        "storage": {}
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            anvil.accounts[&address!("1000000000000000000000000000000000000002")],
+            dump.accounts[&address!("1000000000000000000000000000000000000002")]
+        );
+    }
+
+    #[test]
+    fn destroyed_round_trip() {
+        let contract = address!("1000000000000000000000000000000000000001");
+        let cleared = address!("1000000000000000000000000000000000000002");
+        let mut base = CacheDB::new(EmptyDB::default());
+        // SELFDESTRUCT to the zero address.
+        base.insert_account_info(
+            contract,
+            AccountInfo::new(
+                U256::from(1),
+                1,
+                KECCAK_EMPTY,
+                Bytecode::new_raw(Bytes::from_static(&hex!("5fff"))),
+            ),
+        );
+        for address in [contract, cleared] {
+            base.insert_account_storage(address, U256::from(1), U256::from(5))
+                .unwrap();
+        }
+
+        let mut db = CacheDB::new(&base);
+        db.replace_account_storage(cleared, [(U256::from(2), U256::from(6))].into())
+            .unwrap();
+        let mut evm = Evm::builder()
+            .with_db(&mut db)
+            .with_spec_id(SpecId::SHANGHAI)
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(contract))
+            .build();
+        let state = evm.transact().unwrap().state;
+        drop(evm);
+        db.commit(state);
+
+        let dump = db.dump();
+        assert_eq!(
+            dump.accounts[&contract].state,
+            Some(AccountState::NotExisting)
+        );
+        assert_eq!(
+            dump.accounts[&cleared].state,
+            Some(AccountState::StorageCleared)
+        );
+        assert_eq!(StateDump::from_bytes(&dump.to_bytes()), Ok(dump.clone()));
+
+        // destroyed and cleared storage is not read from the underlying database.
+        let mut loaded = CacheDB::new(&base);
+        loaded.load_dump(dump);
+        assert_eq!(loaded.basic_ref(contract), Ok(None));
+        assert_eq!(loaded.storage_ref(contract, U256::from(1)), Ok(U256::ZERO));
+        assert_eq!(loaded.storage_ref(cleared, U256::from(1)), Ok(U256::ZERO));
+        assert_eq!(
+            loaded.storage_ref(cleared, U256::from(2)),
+            Ok(U256::from(6))
+        );
+    }
+}
@@ -1,9 +1,10 @@
 use std::sync::Arc;
 
 use ethers_core::types::{Block, BlockId, TxHash, H160 as eH160, H256, U64 as eU64};
-use ethers_providers::Middleware;
+use ethers_providers::{Middleware, MiddlewareError, ProviderError};
 use tokio::runtime::{Builder, Handle, RuntimeFlavor};
 
+use crate::db::{DatabaseAsync, DatabaseAsyncRef};
 use crate::primitives::{AccountInfo, Address, Bytecode, B256, KECCAK_EMPTY, U256};
 use crate::{Database, DatabaseRef};
 
@@ -69,19 +70,16 @@ impl<M: Middleware> EthersDB<M> {
     }
 }
 
-impl<M: Middleware> DatabaseRef for EthersDB<M> {
+impl<M: Middleware> DatabaseAsyncRef for EthersDB<M> {
     type Error = M::Error;
 
-    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+    async fn basic_async_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
         let add = eH160::from(address.0 .0);
 
-        let f = async {
-            let nonce = self.client.get_transaction_count(add, self.block_number);
-            let balance = self.client.get_balance(add, self.block_number);
-            let code = self.client.get_code(add, self.block_number);
-            tokio::join!(nonce, balance, code)
-        };
-        let (nonce, balance, code) = Self::block_on(f);
+        let nonce = self.client.get_transaction_count(add, self.block_number);
+        let balance = self.client.get_balance(add, self.block_number);
+        let code = self.client.get_code(add, self.block_number);
+        let (nonce, balance, code) = tokio::join!(nonce, balance, code);
 
         let balance = U256::from_limbs(balance?.0);
         let nonce = nonce?.as_u64();
@@ -90,33 +88,84 @@ impl<M: Middleware> DatabaseRef for EthersDB<M> {
         Ok(Some(AccountInfo::new(balance, nonce, code_hash, bytecode)))
     }
 
-    fn code_by_hash_ref(&self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
-        panic!("Should not be called. Code is already loaded");
-        // not needed because we already load code with basic info
+    async fn code_by_hash_async_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        // code is loaded with the basic info, and it can't be fetched by its hash.
+        Err(M::Error::from_provider_err(ProviderError::CustomError(
+            format!("code by hash {code_hash} is not available, it is loaded with the account"),
+        )))
     }
 
-    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+    async fn storage_async_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
         let add = eH160::from(address.0 .0);
         let index = H256::from(index.to_be_bytes());
-        let slot_value: H256 =
-            Self::block_on(self.client.get_storage_at(add, index, self.block_number))?;
+        let slot_value: H256 = self
+            .client
+            .get_storage_at(add, index, self.block_number)
+            .await?;
         Ok(U256::from_be_bytes(slot_value.to_fixed_bytes()))
     }
 
-    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+    async fn block_hash_async_ref(&self, number: U256) -> Result<B256, Self::Error> {
         // saturate usize
         if number > U256::from(u64::MAX) {
             return Ok(KECCAK_EMPTY);
         }
         // We know number <= u64::MAX so unwrap is safe
         let number = eU64::from(u64::try_from(number).unwrap());
-        let block: Option<Block<TxHash>> =
-            Self::block_on(self.client.get_block(BlockId::from(number)))?;
+        let block: Option<Block<TxHash>> = self.client.get_block(BlockId::from(number)).await?;
         // If number is given, the block is supposed to be finalized so unwrap is safe too.
         Ok(B256::new(block.unwrap().hash.unwrap().0))
     }
 }
 
+impl<M: Middleware> DatabaseAsync for EthersDB<M> {
+    type Error = M::Error;
+
+    #[inline]
+    async fn basic_async(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_async_ref(address).await
+    }
+
+    #[inline]
+    async fn code_by_hash_async(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_async_ref(code_hash).await
+    }
+
+    #[inline]
+    async fn storage_async(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_async_ref(address, index).await
+    }
+
+    #[inline]
+    async fn block_hash_async(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_async_ref(number).await
+    }
+}
+
+impl<M: Middleware> DatabaseRef for EthersDB<M> {
+    type Error = M::Error;
+
+    #[inline]
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        Self::block_on(self.basic_async_ref(address))
+    }
+
+    #[inline]
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        Self::block_on(self.code_by_hash_async_ref(code_hash))
+    }
+
+    #[inline]
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        Self::block_on(self.storage_async_ref(address, index))
+    }
+
+    #[inline]
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        Self::block_on(self.block_hash_async_ref(number))
+    }
+}
+
 impl<M: Middleware> Database for EthersDB<M> {
     type Error = M::Error;
 
@@ -244,6 +244,67 @@ impl<ExtDB: DatabaseRef> Database for CacheDB<ExtDB> {
             }
         }
     }
+
+    /// Loads the accounts that are not cached with one [`DatabaseRef::basic_many_ref`] call.
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        let mut missing = Vec::new();
+        for address in addresses {
+            if !self.accounts.contains_key(address) && !missing.contains(address) {
+                missing.push(*address);
+            }
+        }
+        if !missing.is_empty() {
+            let infos = self.db.basic_many_ref(&missing)?;
+            for (address, info) in missing.into_iter().zip(infos) {
+                self.accounts.insert(address, info.into());
+            }
+        }
+        Ok(addresses
+            .iter()
+            .map(|address| self.accounts[address].info())
+            .collect())
+    }
+
+    /// Loads the accounts and storage slots that are not cached with one
+    /// [`DatabaseRef::basic_many_ref`] and one [`DatabaseRef::storage_many_ref`] call.
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        let addresses = slots
+            .iter()
+            .map(|(address, _)| *address)
+            .collect::<Vec<_>>();
+        self.basic_many(&addresses)?;
+
+        let mut missing = Vec::new();
+        for (address, index) in slots {
+            let account = &self.accounts[address];
+            if !account.storage.contains_key(index)
+                && !matches!(
+                    account.account_state,
+                    AccountState::StorageCleared | AccountState::NotExisting
+                )
+                && !missing.contains(&(*address, *index))
+            {
+                missing.push((*address, *index));
+            }
+        }
+        if !missing.is_empty() {
+            let values = self.db.storage_many_ref(&missing)?;
+            for ((address, index), value) in missing.into_iter().zip(values) {
+                self.accounts
+                    .get_mut(&address)
+                    .unwrap()
+                    .storage
+                    .insert(index, value);
+            }
+        }
+        slots
+            .iter()
+            .map(|(address, index)| self.storage(*address, *index))
+            .collect()
+    }
 }
 
 impl<ExtDB: DatabaseRef> DatabaseRef for CacheDB<ExtDB> {
@@ -288,6 +349,55 @@ impl<ExtDB: DatabaseRef> DatabaseRef for CacheDB<ExtDB> {
             None => self.db.block_hash_ref(number),
         }
     }
+
+    fn basic_many_ref(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        let missing = addresses
+            .iter()
+            .filter(|address| !self.accounts.contains_key(*address))
+            .copied()
+            .collect::<Vec<_>>();
+        let mut fetched = self.db.basic_many_ref(&missing)?.into_iter();
+        Ok(addresses
+            .iter()
+            .map(|address| match self.accounts.get(address) {
+                Some(acc) => acc.info(),
+                None => fetched.next().flatten(),
+            })
+            .collect())
+    }
+
+    fn storage_many_ref(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        // value of the slot if it is known without reading the underlying database.
+        let cached = |address: &Address, index: &U256| {
+            let acc_entry = self.accounts.get(address)?;
+            match acc_entry.storage.get(index) {
+                Some(value) => Some(*value),
+                None if matches!(
+                    acc_entry.account_state,
+                    AccountState::StorageCleared | AccountState::NotExisting
+                ) =>
+                {
+                    Some(U256::ZERO)
+                }
+                None => None,
+            }
+        };
+        let missing = slots
+            .iter()
+            .filter(|(address, index)| cached(address, index).is_none())
+            .copied()
+            .collect::<Vec<_>>();
+        let mut fetched = self.db.storage_many_ref(&missing)?.into_iter();
+        Ok(slots
+            .iter()
+            .map(|(address, index)| {
+                cached(address, index).unwrap_or_else(|| fetched.next().unwrap_or_default())
+            })
+            .collect())
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -0,0 +1,705 @@
+//! Multi-version memory used for the parallel execution of the block.
+//!
+//! Every transaction of the block writes its changes to the memory under its index and
+//! reads the values written by the transactions with a lower index, or from the base
+//! database if there are none. This is the shared data structure of the [Block-STM]
+//! algorithm.
+//!
+//! [Block-STM]: https://arxiv.org/abs/2203.06871
+
+use crate::{
+    primitives::{
+        db::{Database, DatabaseRef},
+        AccountInfo, Address, Bytecode, EvmState, HashMap, HashSet, B256, KECCAK_EMPTY, U256,
+    },
+    WriteSet,
+};
+use core::fmt;
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, RwLock},
+    vec::Vec,
+};
+
+/// Index of the transaction in the block.
+pub type TxIdx = usize;
+
+/// Incarnation of the transaction, number of times it was executed before.
+pub type Incarnation = usize;
+
+/// Location of the state value in the multi-version memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MemoryLocation {
+    /// Account info: balance, nonce and code hash.
+    Basic(Address),
+    /// Code hash of the account, written together with the account info when the code
+    /// changes. Transactions that only need the code hash read it without depending on
+    /// the balance and nonce.
+    CodeHash(Address),
+    /// Storage slot of the account.
+    Storage(Address, U256),
+    /// Whole storage of the account, written when account is created or destroyed.
+    StorageCleared(Address),
+}
+
+/// Value written to the [`MemoryLocation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MemoryValue {
+    /// Account info without the code, `None` if account was destroyed.
+    Basic(Option<AccountInfo>),
+    /// Balance increment of the account, applied on top of the lower writes.
+    BalanceIncrement(U256),
+    /// Code hash of the account.
+    CodeHash(B256),
+    /// Storage slot value.
+    Storage(U256),
+    /// Storage of the account is cleared.
+    StorageCleared,
+}
+
+/// Origin of the value read by the transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadOrigin {
+    /// Value was read from the base database.
+    Base,
+    /// Value was written by the incarnation of the lower transaction.
+    Version(TxIdx, Incarnation),
+}
+
+/// Result of the read from the [`MvMemory`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MemoryRead {
+    /// No lower transaction wrote to the location, value needs to be read from the base database.
+    NotFound,
+    /// Value written by the incarnation of the lower transaction.
+    Version {
+        /// Index of the writing transaction.
+        tx_idx: TxIdx,
+        /// Incarnation of the writing transaction.
+        incarnation: Incarnation,
+        /// Written value.
+        value: MemoryValue,
+    },
+    /// Lower transaction was aborted and is expected to write to the location again.
+    /// Reading transaction depends on it and should wait for its re-execution.
+    Estimate {
+        /// Index of the aborted transaction.
+        blocking_tx_idx: TxIdx,
+    },
+}
+
+impl MemoryRead {
+    /// Returns the origin of the value or `None` if the read hit the estimate.
+    pub fn origin(&self) -> Option<ReadOrigin> {
+        match self {
+            Self::NotFound => Some(ReadOrigin::Base),
+            Self::Version {
+                tx_idx,
+                incarnation,
+                ..
+            } => Some(ReadOrigin::Version(*tx_idx, *incarnation)),
+            Self::Estimate { .. } => None,
+        }
+    }
+}
+
+/// Account read from the [`MvMemory`], see [`MvMemory::read_account`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountRead {
+    /// Origins of all the values read, starting with the highest writer.
+    pub origins: Vec<ReadOrigin>,
+    /// Account info written by the lower transaction, `None` if it needs to be read from the
+    /// base database.
+    pub info: Option<Option<AccountInfo>>,
+    /// Sum of the balance increments written on top of the account.
+    pub balance_increment: U256,
+}
+
+impl AccountRead {
+    /// Applies the balance increments to the account read from the memory or the `base`.
+    pub fn apply(self, base: Option<AccountInfo>) -> Option<AccountInfo> {
+        let info = self.info.unwrap_or(base);
+        if self.balance_increment.is_zero() {
+            return info;
+        }
+        let mut info = info.unwrap_or_default();
+        info.balance = info.balance.saturating_add(self.balance_increment);
+        Some(info)
+    }
+}
+
+/// Entry of the memory location written by the transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum MemoryEntry {
+    /// Value written by the incarnation.
+    Data(Incarnation, MemoryValue),
+    /// Write of the aborted incarnation.
+    Estimate,
+}
+
+/// Multi-version memory of the block.
+///
+/// Stores the writes of every transaction of the block per [`MemoryLocation`] and serves
+/// reads from the writer with the highest index lower than the reading transaction.
+/// Last reads of every transaction are kept so they can be validated after lower
+/// transactions are re-executed.
+#[derive(Debug, Default)]
+pub struct MvMemory {
+    /// Writes of the transactions for every location, by transaction index.
+    data: RwLock<HashMap<MemoryLocation, BTreeMap<TxIdx, MemoryEntry>>>,
+    /// Code written by the transactions.
+    ///
+    /// Code is identified by its hash, so it is never invalidated.
+    code: RwLock<HashMap<B256, Bytecode>>,
+    /// Locations written by the last incarnation of every transaction.
+    last_writes: Vec<Mutex<Vec<MemoryLocation>>>,
+    /// Reads of the last incarnation of every transaction.
+    last_reads: Vec<Mutex<Vec<(MemoryLocation, ReadOrigin)>>>,
+}
+
+impl MvMemory {
+    /// Creates new multi-version memory for the block with `block_size` transactions.
+    pub fn new(block_size: usize) -> Self {
+        Self {
+            data: RwLock::default(),
+            code: RwLock::default(),
+            last_writes: (0..block_size).map(|_| Mutex::default()).collect(),
+            last_reads: (0..block_size).map(|_| Mutex::default()).collect(),
+        }
+    }
+
+    /// Returns number of transactions in the block.
+    pub fn block_size(&self) -> usize {
+        self.last_writes.len()
+    }
+
+    /// Reads the location as seen by the transaction at `tx_idx`.
+    pub fn read(&self, location: &MemoryLocation, tx_idx: TxIdx) -> MemoryRead {
+        let data = self.data.read().unwrap();
+        let Some((writer, entry)) = data
+            .get(location)
+            .and_then(|writes| writes.range(..tx_idx).next_back())
+        else {
+            return MemoryRead::NotFound;
+        };
+        match entry {
+            MemoryEntry::Data(incarnation, value) => MemoryRead::Version {
+                tx_idx: *writer,
+                incarnation: *incarnation,
+                value: value.clone(),
+            },
+            MemoryEntry::Estimate => MemoryRead::Estimate {
+                blocking_tx_idx: *writer,
+            },
+        }
+    }
+
+    /// Reads the account as seen by the transaction at `tx_idx`.
+    ///
+    /// Balance increments of the lower transactions are summed until the transaction that
+    /// wrote the whole account is found. Returns the index of the blocking transaction if
+    /// any of the read entries is an estimate.
+    pub fn read_account(&self, address: Address, tx_idx: TxIdx) -> Result<AccountRead, TxIdx> {
+        let mut read = AccountRead {
+            origins: Vec::new(),
+            info: None,
+            balance_increment: U256::ZERO,
+        };
+        let data = self.data.read().unwrap();
+        let writes = data
+            .get(&MemoryLocation::Basic(address))
+            .into_iter()
+            .flat_map(|writes| writes.range(..tx_idx).rev());
+        for (writer, entry) in writes {
+            let MemoryEntry::Data(incarnation, value) = entry else {
+                return Err(*writer);
+            };
+            read.origins
+                .push(ReadOrigin::Version(*writer, *incarnation));
+            match value {
+                MemoryValue::BalanceIncrement(increment) => {
+                    read.balance_increment = read.balance_increment.saturating_add(*increment);
+                }
+                MemoryValue::Basic(info) => {
+                    read.info = Some(info.clone());
+                    return Ok(read);
+                }
+                _ => unreachable!("account location holds account values"),
+            }
+        }
+        read.origins.push(ReadOrigin::Base);
+        Ok(read)
+    }
+
+    /// Returns the accounts which balance was incremented by the last incarnation of
+    /// the transaction.
+    pub fn balance_increments(&self, tx_idx: TxIdx) -> Vec<Address> {
+        let data = self.data.read().unwrap();
+        self.last_writes[tx_idx]
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|location| match location {
+                MemoryLocation::Basic(address) => match data.get(location)?.get(&tx_idx)? {
+                    MemoryEntry::Data(_, MemoryValue::BalanceIncrement(_)) => Some(*address),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the code written by any transaction.
+    pub fn code_by_hash(&self, code_hash: &B256) -> Option<Bytecode> {
+        self.code.read().unwrap().get(code_hash).cloned()
+    }
+
+    /// Records reads and writes of the executed incarnation of the transaction.
+    ///
+    /// Writes of the previous incarnation that are not written again are removed.
+    pub fn record(
+        &self,
+        tx_idx: TxIdx,
+        incarnation: Incarnation,
+        reads: Vec<(MemoryLocation, ReadOrigin)>,
+        writes: Vec<(MemoryLocation, MemoryValue)>,
+    ) {
+        *self.last_reads[tx_idx].lock().unwrap() = reads;
+
+        let locations = writes
+            .iter()
+            .map(|(location, _)| *location)
+            .collect::<Vec<_>>();
+        let written = locations.iter().collect::<HashSet<_>>();
+        let previous = self.last_writes[tx_idx].lock().unwrap().clone();
+
+        let mut data = self.data.write().unwrap();
+        for location in previous {
+            if !written.contains(&location) {
+                if let Some(entries) = data.get_mut(&location) {
+                    entries.remove(&tx_idx);
+                }
+            }
+        }
+        for (location, value) in writes {
+            data.entry(location)
+                .or_default()
+                .insert(tx_idx, MemoryEntry::Data(incarnation, value));
+        }
+        *self.last_writes[tx_idx].lock().unwrap() = locations;
+    }
+
+    /// Records reads and the state changed by the executed incarnation of the transaction.
+    ///
+    /// `write_set` decides which locations are written, values are taken from the `state`.
+    pub fn record_state(
+        &self,
+        tx_idx: TxIdx,
+        incarnation: Incarnation,
+        reads: Vec<(MemoryLocation, ReadOrigin)>,
+        state: &EvmState,
+        write_set: &WriteSet,
+    ) {
+        let mut writes = Vec::new();
+        for (address, account) in state {
+            if write_set.clears_storage(address) {
+                writes.push((
+                    MemoryLocation::StorageCleared(*address),
+                    MemoryValue::StorageCleared,
+                ));
+            }
+            if write_set.destroyed.contains(address) {
+                writes.push((MemoryLocation::Basic(*address), MemoryValue::Basic(None)));
+                writes.push((
+                    MemoryLocation::CodeHash(*address),
+                    MemoryValue::CodeHash(KECCAK_EMPTY),
+                ));
+                continue;
+            }
+            if writes_account_info(write_set, address) {
+                let mut info = account.info.clone();
+                if let Some(increment) = write_set.balance_increments.get(address) {
+                    info.balance = info.balance.saturating_add(*increment);
+                }
+                if let Some(code) = write_set.code.get(address) {
+                    self.code
+                        .write()
+                        .unwrap()
+                        .insert(info.code_hash, code.clone());
+                }
+                if write_set.code.contains_key(address) || write_set.clears_storage(address) {
+                    writes.push((
+                        MemoryLocation::CodeHash(*address),
+                        MemoryValue::CodeHash(info.code_hash),
+                    ));
+                }
+                writes.push((
+                    MemoryLocation::Basic(*address),
+                    MemoryValue::Basic(Some(info.without_code())),
+                ));
+            }
+        }
+        for (address, increment) in &write_set.balance_increments {
+            // increment is folded into the account info if the whole account is written.
+            if !writes_account_info(write_set, address) {
+                writes.push((
+                    MemoryLocation::Basic(*address),
+                    MemoryValue::BalanceIncrement(*increment),
+                ));
+            }
+        }
+        for (address, slots) in &write_set.storage {
+            writes.extend(slots.iter().map(|(slot, value)| {
+                (
+                    MemoryLocation::Storage(*address, *slot),
+                    MemoryValue::Storage(*value),
+                )
+            }));
+        }
+        self.record(tx_idx, incarnation, reads, writes);
+    }
+
+    /// Marks writes of the last incarnation of the transaction as estimates.
+    ///
+    /// Called when the incarnation is aborted, transactions reading the estimates
+    /// will wait for the re-execution.
+    pub fn convert_writes_to_estimates(&self, tx_idx: TxIdx) {
+        let locations = self.last_writes[tx_idx].lock().unwrap();
+        let mut data = self.data.write().unwrap();
+        for location in locations.iter() {
+            if let Some(entry) = data
+                .get_mut(location)
+                .and_then(|entries| entries.get_mut(&tx_idx))
+            {
+                *entry = MemoryEntry::Estimate;
+            }
+        }
+    }
+
+    /// Returns true if all the reads of the last incarnation of the transaction would
+    /// read the same values now.
+    ///
+    /// Account read is recorded as consecutive origins of all the values it was built from.
+    pub fn validate_reads(&self, tx_idx: TxIdx) -> bool {
+        let reads = self.last_reads[tx_idx].lock().unwrap();
+        let mut idx = 0;
+        while let Some((location, origin)) = reads.get(idx) {
+            if let MemoryLocation::Basic(address) = location {
+                let Ok(account) = self.read_account(*address, tx_idx) else {
+                    return false;
+                };
+                let end = idx + account.origins.len();
+                let is_same = reads.get(idx..end).is_some_and(|recorded| {
+                    recorded
+                        .iter()
+                        .zip(&account.origins)
+                        .all(|(read, origin)| read.0 == *location && read.1 == *origin)
+                });
+                if !is_same {
+                    return false;
+                }
+                idx = end;
+            } else {
+                if self.read(location, tx_idx).origin() != Some(*origin) {
+                    return false;
+                }
+                idx += 1;
+            }
+        }
+        true
+    }
+}
+
+/// Returns true if the balance, nonce or code of the account is written, ignoring the
+/// balance increments.
+fn writes_account_info(write_set: &WriteSet, address: &Address) -> bool {
+    write_set.balances.contains_key(address)
+        || write_set.nonces.contains_key(address)
+        || write_set.code.contains_key(address)
+        || write_set.clears_storage(address)
+}
+
+/// Error of the [`MvDatabase`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MvDatabaseError<E> {
+    /// Read hit the estimate of the aborted transaction at this index.
+    Estimate(TxIdx),
+    /// Error of the base database.
+    Database(E),
+}
+
+impl<E: fmt::Display> fmt::Display for MvDatabaseError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Estimate(tx_idx) => write!(f, "read depends on aborted transaction {tx_idx}"),
+            Self::Database(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: std::error::Error> std::error::Error for MvDatabaseError<E> {}
+
+/// Database view of the transaction over the [`MvMemory`] and the base database.
+///
+/// Reads values written by the lower transactions and falls back to the base database.
+/// Origins of all the reads are recorded so they can be validated later.
+#[derive(Debug)]
+pub struct MvDatabase<'a, DB> {
+    /// Memory of the block.
+    memory: &'a MvMemory,
+    /// Base database, state before the block.
+    db: &'a DB,
+    /// Index of the executing transaction.
+    tx_idx: TxIdx,
+    /// Origins of the values read.
+    reads: Vec<(MemoryLocation, ReadOrigin)>,
+}
+
+impl<'a, DB: DatabaseRef> MvDatabase<'a, DB> {
+    /// Creates new database view for the transaction at `tx_idx`.
+    pub fn new(memory: &'a MvMemory, db: &'a DB, tx_idx: TxIdx) -> Self {
+        Self {
+            memory,
+            db,
+            tx_idx,
+            reads: Vec::new(),
+        }
+    }
+
+    /// Returns index of the transaction.
+    pub fn tx_idx(&self) -> TxIdx {
+        self.tx_idx
+    }
+
+    /// Takes the recorded reads.
+    pub fn take_reads(&mut self) -> Vec<(MemoryLocation, ReadOrigin)> {
+        core::mem::take(&mut self.reads)
+    }
+
+    /// Reads the location from the memory and records its origin.
+    fn read(
+        &mut self,
+        location: MemoryLocation,
+    ) -> Result<Option<MemoryValue>, MvDatabaseError<DB::Error>> {
+        let (origin, value) = match self.memory.read(&location, self.tx_idx) {
+            MemoryRead::NotFound => (ReadOrigin::Base, None),
+            MemoryRead::Version {
+                tx_idx,
+                incarnation,
+                value,
+            } => (ReadOrigin::Version(tx_idx, incarnation), Some(value)),
+            MemoryRead::Estimate { blocking_tx_idx } => {
+                return Err(MvDatabaseError::Estimate(blocking_tx_idx))
+            }
+        };
+        self.reads.push((location, origin));
+        Ok(value)
+    }
+}
+
+impl<DB: DatabaseRef> Database for MvDatabase<'_, DB> {
+    type Error = MvDatabaseError<DB::Error>;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let account = self
+            .memory
+            .read_account(address, self.tx_idx)
+            .map_err(MvDatabaseError::Estimate)?;
+        let location = MemoryLocation::Basic(address);
+        self.reads
+            .extend(account.origins.iter().map(|origin| (location, *origin)));
+        let base = match account.info {
+            Some(_) => None,
+            None => self
+                .db
+                .basic_ref(address)
+                .map_err(MvDatabaseError::Database)?,
+        };
+        Ok(account.apply(base))
+    }
+
+    fn code_hash(&mut self, address: Address) -> Result<B256, Self::Error> {
+        match self.read(MemoryLocation::CodeHash(address))? {
+            Some(MemoryValue::CodeHash(code_hash)) => Ok(code_hash),
+            _ => Ok(self
+                .db
+                .basic_ref(address)
+                .map_err(MvDatabaseError::Database)?
+                .map_or(KECCAK_EMPTY, |info| info.code_hash)),
+        }
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.memory.code_by_hash(&code_hash) {
+            Some(code) => Ok(code),
+            None => self
+                .db
+                .code_by_hash_ref(code_hash)
+                .map_err(MvDatabaseError::Database),
+        }
+    }
+
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        let cleared = self
+            .memory
+            .read(&MemoryLocation::StorageCleared(address), self.tx_idx);
+        let slot = self
+            .memory
+            .read(&MemoryLocation::Storage(address, index), self.tx_idx);
+        // slot written in the same or later transaction than the one that cleared the storage
+        // has the latest value.
+        let value = match (&cleared, &slot) {
+            (MemoryRead::Estimate { blocking_tx_idx }, _)
+            | (_, MemoryRead::Estimate { blocking_tx_idx }) => {
+                return Err(MvDatabaseError::Estimate(*blocking_tx_idx))
+            }
+            (
+                MemoryRead::Version {
+                    tx_idx: cleared_idx,
+                    ..
+                },
+                MemoryRead::Version {
+                    tx_idx: slot_idx,
+                    value: MemoryValue::Storage(value),
+                    ..
+                },
+            ) if slot_idx >= cleared_idx => *value,
+            (
+                MemoryRead::NotFound,
+                MemoryRead::Version {
+                    value: MemoryValue::Storage(value),
+                    ..
+                },
+            ) => *value,
+            (MemoryRead::Version { .. }, _) => U256::ZERO,
+            _ => self
+                .db
+                .storage_ref(address, index)
+                .map_err(MvDatabaseError::Database)?,
+        };
+        let location = MemoryLocation::StorageCleared(address);
+        self.reads.push((location, cleared.origin().unwrap()));
+        let location = MemoryLocation::Storage(address, index);
+        self.reads.push((location, slot.origin().unwrap()));
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.db
+            .block_hash_ref(number)
+            .map_err(MvDatabaseError::Database)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::EmptyDB, primitives::address};
+
+    #[test]
+    fn read_lower_versions() {
+        let address = address!("1000000000000000000000000000000000000001");
+        let slot = MemoryLocation::Storage(address, U256::from(1));
+        let memory = MvMemory::new(4);
+
+        memory.record(
+            0,
+            0,
+            Vec::new(),
+            vec![(slot, MemoryValue::Storage(U256::from(1)))],
+        );
+        memory.record(
+            2,
+            0,
+            Vec::new(),
+            vec![(slot, MemoryValue::Storage(U256::from(2)))],
+        );
+        assert_eq!(memory.read(&slot, 0), MemoryRead::NotFound);
+        assert_eq!(
+            memory.read(&slot, 2).origin(),
+            Some(ReadOrigin::Version(0, 0))
+        );
+        assert_eq!(
+            memory.read(&slot, 3).origin(),
+            Some(ReadOrigin::Version(2, 0))
+        );
+
+        // transaction three reads the write of the transaction two.
+        let db = EmptyDB::default();
+        let mut view = MvDatabase::new(&memory, &db, 3);
+        assert_eq!(view.storage(address, U256::from(1)), Ok(U256::from(2)));
+        let reads = view.take_reads();
+        memory.record(3, 0, reads, Vec::new());
+        assert!(memory.validate_reads(3));
+
+        // aborted transaction two makes the read depend on it.
+        memory.convert_writes_to_estimates(2);
+        assert!(!memory.validate_reads(3));
+        let mut view = MvDatabase::new(&memory, &db, 3);
+        assert_eq!(
+            view.storage(address, U256::from(1)),
+            Err(MvDatabaseError::Estimate(2))
+        );
+
+        // re-execution of the transaction two does not write to the slot, and the
+        // destroyed account clears the storage for the higher transactions.
+        memory.record(
+            2,
+            1,
+            Vec::new(),
+            vec![
+                (MemoryLocation::Basic(address), MemoryValue::Basic(None)),
+                (
+                    MemoryLocation::StorageCleared(address),
+                    MemoryValue::StorageCleared,
+                ),
+            ],
+        );
+        assert!(!memory.validate_reads(3));
+        let mut view = MvDatabase::new(&memory, &db, 3);
+        assert_eq!(view.storage(address, U256::from(1)), Ok(U256::ZERO));
+        assert_eq!(view.basic(address), Ok(None));
+        let mut view = MvDatabase::new(&memory, &db, 1);
+        assert_eq!(view.storage(address, U256::from(1)), Ok(U256::from(1)));
+    }
+
+    #[test]
+    fn read_balance_increments() {
+        let address = address!("1000000000000000000000000000000000000001");
+        let location = MemoryLocation::Basic(address);
+        let memory = MvMemory::new(4);
+        let increment = |value| vec![(location, MemoryValue::BalanceIncrement(U256::from(value)))];
+
+        memory.record(0, 0, Vec::new(), increment(1));
+        memory.record(1, 0, Vec::new(), increment(2));
+        let db = EmptyDB::default();
+        let mut view = MvDatabase::new(&memory, &db, 3);
+        assert_eq!(
+            view.basic(address),
+            Ok(Some(AccountInfo::from_balance(U256::from(3))))
+        );
+        memory.record(3, 0, view.take_reads(), Vec::new());
+        assert!(memory.validate_reads(3));
+
+        // full write of the account hides the lower increments.
+        let info = AccountInfo::from_balance(U256::from(10));
+        memory.record(
+            2,
+            0,
+            Vec::new(),
+            vec![(location, MemoryValue::Basic(Some(info)))],
+        );
+        assert!(!memory.validate_reads(3));
+        let account = memory.read_account(address, 3).unwrap();
+        assert_eq!(account.origins, vec![ReadOrigin::Version(2, 0)]);
+        let mut view = MvDatabase::new(&memory, &db, 3);
+        assert_eq!(
+            view.basic(address),
+            Ok(Some(AccountInfo::from_balance(U256::from(10))))
+        );
+
+        // aborted increment blocks the read.
+        memory.convert_writes_to_estimates(1);
+        assert_eq!(memory.read_account(address, 2), Err(1));
+    }
+}
@@ -0,0 +1,611 @@
+//! Copy-on-write overlay database with cheap branching.
+
+use crate::primitives::{
+    db::{Database, DatabaseCommit, DatabaseRef},
+    Account, AccountInfo, Address, Bytecode, HashMap, B256, KECCAK_EMPTY, U256,
+};
+use core::{fmt, mem};
+use std::{sync::Arc, vec::Vec};
+
+/// Account changed in the [`OverlayDB`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OverlayAccount {
+    /// Account info without the code, `None` if the account was destroyed.
+    pub info: Option<AccountInfo>,
+    /// Storage of the lower layers is cleared, as the account was created or destroyed.
+    pub storage_cleared: bool,
+    /// Changed storage slots.
+    pub storage: HashMap<U256, U256>,
+}
+
+/// Changes made on top of the parent state.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OverlayDiff {
+    /// Changed accounts.
+    pub accounts: HashMap<Address, OverlayAccount>,
+    /// Code of the changed accounts, by its hash.
+    pub contracts: HashMap<B256, Bytecode>,
+}
+
+impl OverlayDiff {
+    /// Returns true if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.contracts.is_empty()
+    }
+
+    /// Applies the later changes on top of these.
+    fn extend(&mut self, other: &Self) {
+        for (address, account) in &other.accounts {
+            match self.accounts.get_mut(address) {
+                Some(existing) if !account.storage_cleared => {
+                    existing.info.clone_from(&account.info);
+                    existing.storage.extend(&account.storage);
+                }
+                _ => {
+                    self.accounts.insert(*address, account.clone());
+                }
+            }
+        }
+        self.contracts.extend(
+            other
+                .contracts
+                .iter()
+                .map(|(hash, code)| (*hash, code.clone())),
+        );
+    }
+}
+
+/// Frozen changes shared by the snapshots and forks.
+#[derive(Debug)]
+struct Layer {
+    /// Lower layer, `None` if the layer is on top of the underlying database.
+    parent: Option<Arc<Layer>>,
+    /// Changes of the layer.
+    diff: OverlayDiff,
+}
+
+impl Layer {
+    /// Iterates the layer and its ancestors, from the top.
+    fn ancestors(self: &Arc<Self>) -> impl Iterator<Item = &Arc<Self>> {
+        core::iter::successors(Some(self), |layer| layer.parent.as_ref())
+    }
+}
+
+impl Drop for Layer {
+    fn drop(&mut self) {
+        // parents that are not shared are dropped in a loop, as the recursive drop of
+        // a long chain overflows the stack.
+        let mut parent = self.parent.take();
+        while let Some(layer) = parent {
+            parent = Arc::try_unwrap(layer)
+                .ok()
+                .and_then(|mut layer| layer.parent.take());
+        }
+    }
+}
+
+/// Returns the layers above the `base`, from the top.
+fn layers_above<'a>(top: Option<&'a Arc<Layer>>, base: Option<&Arc<Layer>>) -> Vec<&'a Arc<Layer>> {
+    top.into_iter()
+        .flat_map(Layer::ancestors)
+        .take_while(|layer| !base.is_some_and(|base| Arc::ptr_eq(layer, base)))
+        .collect()
+}
+
+/// Error returned by [`OverlayDB::revert_to`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OverlayRevertError {
+    /// Snapshot is not taken from the state of the branch after it was forked.
+    ForeignSnapshot,
+}
+
+impl fmt::Display for OverlayRevertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ForeignSnapshot => f.write_str("snapshot is not from the branch"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OverlayRevertError {}
+
+/// Snapshot of the [`OverlayDB`] state, see [`OverlayDB::snapshot`].
+#[derive(Clone, Debug)]
+pub struct OverlaySnapshot {
+    layer: Option<Arc<Layer>>,
+}
+
+/// Database that keeps the changes in copy-on-write layers on top of the [`DatabaseRef`].
+///
+/// Changes are committed to the mutable top layer. [`snapshot`](Self::snapshot) and
+/// [`fork`](Self::fork) freeze it into a layer shared by reference, so they are O(1) and
+/// all branches forked from the same state share its layers. Reads walk the layers from
+/// the top down to the underlying database, [`flatten`](Self::flatten) squashes them into
+/// one if the chain gets deep.
+///
+/// Branch is discarded by dropping it, or its changes are applied to the database it was
+/// forked from with [`merge`](Self::merge).
+#[derive(Debug)]
+pub struct OverlayDB<ExtDB> {
+    /// Frozen layers of the state.
+    parent: Option<Arc<Layer>>,
+    /// Layer the database was forked from, changes above it are the own changes of the branch.
+    base: Option<Arc<Layer>>,
+    /// Changes committed since the last snapshot or fork.
+    changes: OverlayDiff,
+    /// Underlying database, shared by all branches.
+    db: Arc<ExtDB>,
+}
+
+impl<ExtDB> Clone for OverlayDB<ExtDB> {
+    fn clone(&self) -> Self {
+        Self {
+            parent: self.parent.clone(),
+            base: self.base.clone(),
+            changes: self.changes.clone(),
+            db: self.db.clone(),
+        }
+    }
+}
+
+impl<ExtDB> OverlayDB<ExtDB> {
+    /// Creates new overlay database on top of the `db`.
+    pub fn new(db: ExtDB) -> Self {
+        Self::new_shared(Arc::new(db))
+    }
+
+    /// Creates new overlay database on top of the shared `db`.
+    pub fn new_shared(db: Arc<ExtDB>) -> Self {
+        Self {
+            parent: None,
+            base: None,
+            changes: OverlayDiff::default(),
+            db,
+        }
+    }
+
+    /// Returns the underlying database.
+    pub fn db(&self) -> &Arc<ExtDB> {
+        &self.db
+    }
+
+    /// Returns the changes committed since the last snapshot or fork.
+    pub fn changes(&self) -> &OverlayDiff {
+        &self.changes
+    }
+
+    /// Returns the number of frozen layers.
+    pub fn depth(&self) -> usize {
+        self.parent
+            .as_ref()
+            .map_or(0, |layer| layer.ancestors().count())
+    }
+
+    /// Freezes the committed changes into a new shared layer.
+    fn freeze(&mut self) -> Option<Arc<Layer>> {
+        if !self.changes.is_empty() {
+            self.parent = Some(Arc::new(Layer {
+                parent: self.parent.take(),
+                diff: mem::take(&mut self.changes),
+            }));
+        }
+        self.parent.clone()
+    }
+
+    /// Takes the snapshot of the current state in O(1).
+    ///
+    /// State can be restored to it with [`revert_to`](Self::revert_to).
+    pub fn snapshot(&mut self) -> OverlaySnapshot {
+        OverlaySnapshot {
+            layer: self.freeze(),
+        }
+    }
+
+    /// Reverts the state to the snapshot, discarding all later changes.
+    ///
+    /// Snapshot of the other branch forked from the same state can be used as well. Returns
+    /// an error if the snapshot does not contain the state the branch was forked from, like
+    /// the snapshot taken before the fork, as the own changes of the branch could not be
+    /// told apart from the shared state on [`merge`](Self::merge).
+    pub fn revert_to(&mut self, snapshot: &OverlaySnapshot) -> Result<(), OverlayRevertError> {
+        if let Some(base) = &self.base {
+            let mut layers = snapshot.layer.iter().flat_map(Layer::ancestors);
+            if !layers.any(|layer| Arc::ptr_eq(layer, base)) {
+                return Err(OverlayRevertError::ForeignSnapshot);
+            }
+        }
+        self.parent.clone_from(&snapshot.layer);
+        self.changes = OverlayDiff::default();
+        Ok(())
+    }
+
+    /// Creates a new branch from the current state in O(1).
+    ///
+    /// Branch and this database share the current state and see only their own later
+    /// changes.
+    pub fn fork(&mut self) -> Self {
+        let parent = self.freeze();
+        Self {
+            base: parent.clone(),
+            parent,
+            changes: OverlayDiff::default(),
+            db: self.db.clone(),
+        }
+    }
+
+    /// Applies the changes of the branch made after it was forked.
+    ///
+    /// Only the changes written by the branch itself are applied, on top of the current
+    /// state of this database, so the changes made here after the fork are kept even if
+    /// this database was flattened or reverted since. Branch that was not forked, like
+    /// the one created with [`new`](Self::new), applies all its changes.
+    pub fn merge(&mut self, branch: Self) {
+        let layers = layers_above(branch.parent.as_ref(), branch.base.as_ref());
+        for layer in layers.into_iter().rev() {
+            self.changes.extend(&layer.diff);
+        }
+        self.changes.extend(&branch.changes);
+    }
+
+    /// Squashes the frozen layers and changes into a single layer.
+    ///
+    /// Makes the reads faster when there are many layers, other branches keep
+    /// sharing the old layers. Layers of the branch shared with the database it was forked
+    /// from are kept below the squashed one, so the branch can still be merged.
+    pub fn flatten(&mut self) {
+        let layers = layers_above(self.parent.as_ref(), self.base.as_ref());
+        if layers.is_empty() && self.changes.is_empty() {
+            return;
+        }
+        let mut diff = OverlayDiff::default();
+        for layer in layers.into_iter().rev() {
+            diff.extend(&layer.diff);
+        }
+        diff.extend(&self.changes);
+        self.parent = Some(Arc::new(Layer {
+            parent: self.base.clone(),
+            diff,
+        }));
+        self.changes = OverlayDiff::default();
+    }
+
+    /// Iterates the changes from the top, committed changes first.
+    fn diffs(&self) -> impl Iterator<Item = &OverlayDiff> {
+        core::iter::once(&self.changes).chain(
+            self.parent
+                .iter()
+                .flat_map(Layer::ancestors)
+                .map(|layer| &layer.diff),
+        )
+    }
+
+    /// Returns the topmost change of the account.
+    fn changed_account(&self, address: &Address) -> Option<&OverlayAccount> {
+        self.diffs().find_map(|diff| diff.accounts.get(address))
+    }
+
+    /// Returns the value of the slot if it is known without reading the underlying database.
+    fn changed_storage(&self, address: &Address, index: &U256) -> Option<U256> {
+        for diff in self.diffs() {
+            if let Some(account) = diff.accounts.get(address) {
+                if let Some(value) = account.storage.get(index) {
+                    return Some(*value);
+                }
+                if account.storage_cleared {
+                    return Some(U256::ZERO);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<ExtDB> DatabaseCommit for OverlayDB<ExtDB> {
+    fn commit(&mut self, changes: HashMap<Address, Account>) {
+        for (address, account) in changes {
+            if !account.is_touched() {
+                continue;
+            }
+            if account.is_selfdestructed() {
+                self.changes.accounts.insert(
+                    address,
+                    OverlayAccount {
+                        info: None,
+                        storage_cleared: true,
+                        storage: HashMap::new(),
+                    },
+                );
+                continue;
+            }
+
+            let mut info = account.info;
+            if let Some(code) = info.code.take() {
+                if !code.is_empty() {
+                    self.changes.contracts.insert(info.code_hash, code);
+                }
+            }
+            let storage = account
+                .storage
+                .into_iter()
+                .map(|(slot, value)| (slot, value.present_value()));
+
+            let overlay_account = self.changes.accounts.entry(address).or_default();
+            if account
+                .status
+                .contains(crate::primitives::AccountStatus::Created)
+            {
+                overlay_account.storage_cleared = true;
+                overlay_account.storage.clear();
+            }
+            overlay_account.info = Some(info);
+            overlay_account.storage.extend(storage);
+        }
+    }
+}
+
+impl<ExtDB: DatabaseRef> Database for OverlayDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+
+    #[inline]
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        self.basic_many_ref(addresses)
+    }
+
+    #[inline]
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        self.storage_many_ref(slots)
+    }
+}
+
+impl<ExtDB: DatabaseRef> DatabaseRef for OverlayDB<ExtDB> {
+    type Error = ExtDB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.changed_account(&address) {
+            Some(account) => Ok(account.info.clone()),
+            None => self.db.basic_ref(address),
+        }
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        match self.diffs().find_map(|diff| diff.contracts.get(&code_hash)) {
+            Some(code) => Ok(code.clone()),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self.changed_storage(&address, &index) {
+            Some(value) => Ok(value),
+            None => self.db.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+
+    fn basic_many_ref(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        let missing = addresses
+            .iter()
+            .filter(|address| self.changed_account(address).is_none())
+            .copied()
+            .collect::<Vec<_>>();
+        let mut fetched = self.db.basic_many_ref(&missing)?.into_iter();
+        Ok(addresses
+            .iter()
+            .map(|address| match self.changed_account(address) {
+                Some(account) => account.info.clone(),
+                None => fetched.next().flatten(),
+            })
+            .collect())
+    }
+
+    fn storage_many_ref(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        let missing = slots
+            .iter()
+            .filter(|(address, index)| self.changed_storage(address, index).is_none())
+            .copied()
+            .collect::<Vec<_>>();
+        let mut fetched = self.db.storage_many_ref(&missing)?.into_iter();
+        Ok(slots
+            .iter()
+            .map(|(address, index)| {
+                self.changed_storage(address, index)
+                    .unwrap_or_else(|| fetched.next().unwrap_or_default())
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{address, AccountStatus, EvmStorageSlot},
+    };
+
+    fn changes(address: Address, balance: u64, slot: u64, value: u64) -> HashMap<Address, Account> {
+        let mut account = Account::from(AccountInfo::from_balance(U256::from(balance)));
+        account.mark_touch();
+        account.storage.insert(
+            U256::from(slot),
+            EvmStorageSlot::new_changed(U256::ZERO, U256::from(value)),
+        );
+        HashMap::from_iter([(address, account)])
+    }
+
+    #[test]
+    fn fork_snapshot_and_merge() {
+        let address = address!("1000000000000000000000000000000000000001");
+        let mut base = CacheDB::new(EmptyDB::default());
+        base.insert_account_info(address, AccountInfo::from_balance(U256::from(1)));
+        base.insert_account_storage(address, U256::from(1), U256::from(1))
+            .unwrap();
+
+        let mut db = OverlayDB::new(base);
+        db.commit(changes(address, 2, 2, 2));
+        let mut branch = db.fork();
+        assert_eq!(db.depth(), 1);
+        branch.commit(changes(address, 3, 1, 3));
+        // branches don't see each other changes.
+        assert_eq!(
+            db.basic_ref(address).unwrap().unwrap().balance,
+            U256::from(2)
+        );
+        assert_eq!(db.storage_ref(address, U256::from(1)), Ok(U256::from(1)));
+        assert_eq!(
+            branch.storage_ref(address, U256::from(1)),
+            Ok(U256::from(3))
+        );
+        assert_eq!(
+            branch.storage_ref(address, U256::from(2)),
+            Ok(U256::from(2))
+        );
+
+        let snapshot = branch.snapshot();
+        let mut created = changes(address, 4, 3, 4);
+        created
+            .get_mut(&address)
+            .unwrap()
+            .status
+            .insert(AccountStatus::Created);
+        branch.commit(created);
+        assert_eq!(branch.storage_ref(address, U256::from(1)), Ok(U256::ZERO));
+        assert_eq!(
+            branch.storage_ref(address, U256::from(3)),
+            Ok(U256::from(4))
+        );
+        branch.revert_to(&snapshot).unwrap();
+        assert_eq!(
+            branch.storage_ref(address, U256::from(1)),
+            Ok(U256::from(3))
+        );
+        assert_eq!(branch.storage_ref(address, U256::from(3)), Ok(U256::ZERO));
+
+        // discarded branch leaves no trace, merged one is applied.
+        drop(db.fork());
+        db.merge(branch);
+        assert_eq!(
+            db.basic_ref(address).unwrap().unwrap().balance,
+            U256::from(3)
+        );
+        assert_eq!(db.storage_ref(address, U256::from(1)), Ok(U256::from(3)));
+        assert_eq!(db.storage_ref(address, U256::from(2)), Ok(U256::from(2)));
+
+        db.flatten();
+        assert_eq!(db.depth(), 1);
+        assert_eq!(db.storage_ref(address, U256::from(1)), Ok(U256::from(3)));
+        assert_eq!(db.storage_ref(address, U256::from(2)), Ok(U256::from(2)));
+    }
+
+    #[test]
+    fn merge_after_flatten() {
+        let address = address!("1000000000000000000000000000000000000001");
+        let other = address!("1000000000000000000000000000000000000002");
+        let mut db = OverlayDB::new(EmptyDB::default());
+        db.commit(changes(address, 1, 1, 1));
+        let mut branch = db.fork();
+        branch.commit(changes(other, 2, 1, 2));
+
+        // newer state of the database is not overwritten by the shared layers.
+        db.commit(changes(address, 3, 1, 3));
+        db.flatten();
+        branch.flatten();
+        db.merge(branch.clone());
+        assert_eq!(
+            db.basic_ref(address).unwrap().unwrap().balance,
+            U256::from(3)
+        );
+        assert_eq!(db.storage_ref(address, U256::from(1)), Ok(U256::from(3)));
+        assert_eq!(db.storage_ref(other, U256::from(1)), Ok(U256::from(2)));
+    }
+
+    #[test]
+    fn revert_to_foreign_snapshot() {
+        let address = address!("1000000000000000000000000000000000000001");
+        let mut db = OverlayDB::new(EmptyDB::default());
+        db.commit(changes(address, 1, 1, 1));
+        let before_fork = db.snapshot();
+        db.commit(changes(address, 2, 1, 2));
+        let mut branch = db.fork();
+        let mut sibling = db.fork();
+        sibling.commit(changes(address, 3, 1, 3));
+
+        assert_eq!(
+            branch.revert_to(&before_fork),
+            Err(OverlayRevertError::ForeignSnapshot)
+        );
+        assert_eq!(
+            branch.revert_to(&OverlayDB::new(EmptyDB::default()).snapshot()),
+            Err(OverlayRevertError::ForeignSnapshot)
+        );
+        assert_eq!(
+            branch.storage_ref(address, U256::from(1)),
+            Ok(U256::from(2))
+        );
+
+        // snapshot of the branch forked from the same state is accepted.
+        branch.revert_to(&sibling.snapshot()).unwrap();
+        assert_eq!(
+            branch.storage_ref(address, U256::from(1)),
+            Ok(U256::from(3))
+        );
+        // database that was not forked can revert to any of its snapshots.
+        db.revert_to(&before_fork).unwrap();
+        db.merge(branch);
+        assert_eq!(db.storage_ref(address, U256::from(1)), Ok(U256::from(3)));
+    }
+
+    #[test]
+    fn drop_deep_layers() {
+        let address = address!("1000000000000000000000000000000000000001");
+        let mut db = OverlayDB::new(EmptyDB::default());
+        for i in 0..100_000 {
+            db.commit(changes(address, i, 1, i));
+            db.snapshot();
+        }
+        assert_eq!(db.depth(), 100_000);
+        let branch = db.fork();
+        drop(db);
+        assert_eq!(
+            branch.storage_ref(address, U256::from(1)),
+            Ok(U256::from(99_999))
+        );
+        drop(branch);
+    }
+}
@@ -0,0 +1,354 @@
+//! Database wrapper that applies geth style state overrides.
+
+use crate::primitives::{
+    db::{Database, DatabaseRef},
+    AccountInfo, Address, Bytecode, Bytes, HashMap, B256, U256,
+};
+use core::fmt;
+use std::vec::Vec;
+
+/// Overrides of the accounts, by address. See [`AccountOverride`].
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// Override of the account, in the shape of the geth `eth_call` state override.
+///
+/// Fields that are `None` are read from the underlying database. `state` replaces the whole
+/// storage of the account, while `state_diff` replaces only the given slots, they can't be
+/// set at the same time.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct AccountOverride {
+    /// Overrides the balance.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub balance: Option<U256>,
+    /// Overrides the nonce.
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "hex_u64_opt", skip_serializing_if = "Option::is_none")
+    )]
+    pub nonce: Option<u64>,
+    /// Overrides the code.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub code: Option<Bytes>,
+    /// Replaces the whole storage, slots that are not present are zero.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub state: Option<HashMap<B256, B256>>,
+    /// Replaces the given storage slots.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub state_diff: Option<HashMap<B256, B256>>,
+}
+
+impl AccountOverride {
+    /// Returns the overridden value of the storage slot, `None` if it is not overridden.
+    fn storage(&self, index: U256) -> Option<U256> {
+        let key = B256::from(index);
+        if let Some(state) = &self.state {
+            return Some(state.get(&key).map_or(U256::ZERO, |value| (*value).into()));
+        }
+        self.state_diff
+            .as_ref()
+            .and_then(|state_diff| state_diff.get(&key))
+            .map(|value| (*value).into())
+    }
+}
+
+/// Error returned when the [`StateOverride`] is invalid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StateOverrideError {
+    /// Both `state` and `state_diff` are set for the account.
+    StateAndStateDiff(Address),
+}
+
+impl fmt::Display for StateOverrideError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StateAndStateDiff(address) => {
+                write!(f, "account {address} has both 'state' and 'stateDiff'")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StateOverrideError {}
+
+/// Database that applies the [`StateOverride`] on top of the wrapped [`DatabaseRef`].
+///
+/// Underlying database is never changed, so overrides don't leak to other calls.
+/// Wrap a reference to the database to use it for a single call.
+#[derive(Clone, Debug)]
+pub struct StateOverrideDB<DB> {
+    /// Underlying database.
+    pub db: DB,
+    /// Overrides of the accounts.
+    overrides: StateOverride,
+    /// Overridden code of the accounts, with its hash.
+    code: HashMap<Address, (B256, Bytecode)>,
+}
+
+impl<DB: DatabaseRef> StateOverrideDB<DB> {
+    /// Creates new database with the overrides applied on top of the `db`.
+    pub fn new(db: DB, overrides: StateOverride) -> Result<Self, StateOverrideError> {
+        let mut code = HashMap::new();
+        for (address, account) in &overrides {
+            if account.state.is_some() && account.state_diff.is_some() {
+                return Err(StateOverrideError::StateAndStateDiff(*address));
+            }
+            if let Some(bytes) = &account.code {
+                let bytecode = Bytecode::new_raw(bytes.clone());
+                code.insert(*address, (bytecode.hash_slow(), bytecode));
+            }
+        }
+        Ok(Self {
+            db,
+            overrides,
+            code,
+        })
+    }
+
+    /// Returns the overrides.
+    pub fn overrides(&self) -> &StateOverride {
+        &self.overrides
+    }
+
+    /// Consumes the database and returns the underlying database.
+    pub fn into_inner(self) -> DB {
+        self.db
+    }
+
+    /// Applies the override of the account on top of the info read from the database.
+    fn override_info(&self, address: &Address, info: Option<AccountInfo>) -> Option<AccountInfo> {
+        let Some(account) = self.overrides.get(address) else {
+            return info;
+        };
+        let mut info = info.unwrap_or_default();
+        if let Some(balance) = account.balance {
+            info.balance = balance;
+        }
+        if let Some(nonce) = account.nonce {
+            info.nonce = nonce;
+        }
+        if let Some((code_hash, code)) = self.code.get(address) {
+            info.code_hash = *code_hash;
+            info.code = Some(code.clone());
+        }
+        Some(info)
+    }
+
+    /// Returns the overridden value of the slot.
+    fn override_storage(&self, address: &Address, index: U256) -> Option<U256> {
+        self.overrides
+            .get(address)
+            .and_then(|account| account.storage(index))
+    }
+}
+
+impl<DB: DatabaseRef> Database for StateOverrideDB<DB> {
+    type Error = DB::Error;
+
+    #[inline]
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.basic_ref(address)
+    }
+
+    #[inline]
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.code_by_hash_ref(code_hash)
+    }
+
+    #[inline]
+    fn storage(&mut self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.storage_ref(address, index)
+    }
+
+    #[inline]
+    fn block_hash(&mut self, number: U256) -> Result<B256, Self::Error> {
+        self.block_hash_ref(number)
+    }
+
+    #[inline]
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        self.basic_many_ref(addresses)
+    }
+
+    #[inline]
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        self.storage_many_ref(slots)
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for StateOverrideDB<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let info = self.db.basic_ref(address)?;
+        Ok(self.override_info(&address, info))
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.code.values().find(|(hash, _)| *hash == code_hash) {
+            Some((_, code)) => Ok(code.clone()),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        match self.override_storage(&address, index) {
+            Some(value) => Ok(value),
+            None => self.db.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+
+    fn basic_many_ref(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        let infos = self.db.basic_many_ref(addresses)?;
+        Ok(addresses
+            .iter()
+            .zip(infos)
+            .map(|(address, info)| self.override_info(address, info))
+            .collect())
+    }
+
+    fn storage_many_ref(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        let missing = slots
+            .iter()
+            .filter(|(address, index)| self.override_storage(address, *index).is_none())
+            .copied()
+            .collect::<Vec<_>>();
+        let mut fetched = self.db.storage_many_ref(&missing)?.into_iter();
+        Ok(slots
+            .iter()
+            .map(|(address, index)| {
+                self.override_storage(address, *index)
+                    .unwrap_or_else(|| fetched.next().unwrap_or_default())
+            })
+            .collect())
+    }
+}
+
+/// Serializes `Option<u64>` as hex quantity, as geth does for the nonce.
+#[cfg(feature = "serde")]
+mod hex_u64_opt {
+    use crate::primitives::U256;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        value: &Option<u64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.map(U256::from).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        Option::<U256>::deserialize(deserializer)?
+            .map(|value| value.try_into().map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{address, hex, BlockOverrides, TransactTo},
+        Evm,
+    };
+
+    #[test]
+    fn override_state_and_block() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let contract = address!("1000000000000000000000000000000000000002");
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_storage(contract, U256::from(1), U256::from(7))
+            .unwrap();
+        db.insert_account_storage(contract, U256::from(2), U256::from(8))
+            .unwrap();
+
+        // stores NUMBER + SLOAD(1) + SLOAD(2) into slot 0.
+        let code = Bytes::from_static(&hex!("6002546001544301015f5500"));
+        let slot = |value: u64| B256::from(U256::from(value));
+        let overrides = StateOverride::from_iter([
+            (
+                caller,
+                AccountOverride {
+                    balance: Some(U256::from(1_000_000)),
+                    nonce: Some(5),
+                    ..Default::default()
+                },
+            ),
+            (
+                contract,
+                AccountOverride {
+                    code: Some(code),
+                    state_diff: Some(HashMap::from_iter([(slot(1), slot(100))])),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        let mut evm = Evm::builder()
+            .with_db(StateOverrideDB::new(&db, overrides.clone()).unwrap())
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(contract);
+                tx.nonce = Some(5);
+            })
+            .build();
+        let block = evm.block().clone();
+        let result = evm
+            .transact_with_block_overrides(&BlockOverrides {
+                number: Some(U256::from(1000)),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(result.result.is_success());
+        assert_eq!(
+            result.state[&contract].storage[&U256::ZERO].present_value,
+            U256::from(1108)
+        );
+        assert_eq!(evm.block(), &block);
+
+        // full storage replacement clears the slots that are not given.
+        let mut overrides = overrides;
+        let contract_override = overrides.get_mut(&contract).unwrap();
+        contract_override.state = contract_override.state_diff.take();
+        let override_db = StateOverrideDB::new(&db, overrides.clone()).unwrap();
+        assert_eq!(
+            override_db.storage_ref(contract, U256::from(1)),
+            Ok(U256::from(100))
+        );
+        assert_eq!(
+            override_db.storage_ref(contract, U256::from(2)),
+            Ok(U256::ZERO)
+        );
+        assert_eq!(
+            override_db.basic_ref(caller).unwrap().unwrap().balance,
+            U256::from(1_000_000)
+        );
+
+        // underlying database is not changed.
+        assert_eq!(db.storage_ref(contract, U256::from(1)), Ok(U256::from(7)));
+        assert!(db.basic_ref(caller).unwrap().is_none());
+
+        overrides.get_mut(&contract).unwrap().state_diff = Some(HashMap::default());
+        assert_eq!(
+            StateOverrideDB::new(&db, overrides).unwrap_err(),
+            StateOverrideError::StateAndStateDiff(contract)
+        );
+    }
+}
@@ -8,6 +8,7 @@ pub mod plain_account;
 pub mod reverts;
 pub mod state;
 pub mod state_builder;
+pub mod state_root;
 pub mod transition_account;
 pub mod transition_state;
 
@@ -22,5 +23,6 @@ pub use plain_account::{PlainAccount, StorageSlot, StorageWithOriginalValues};
 pub use reverts::{AccountRevert, RevertToSlot};
 pub use state::{DBBox, State, StateDBBox};
 pub use state_builder::StateBuilder;
+pub use state_root::StateRoot;
 pub use transition_account::TransitionAccount;
 pub use transition_state::TransitionState;
@@ -184,19 +184,48 @@ impl<DB: Database> State<DB> {
                 }
                 // if not found in bundle, load it from database
                 let info = self.database.basic(address)?;
-                let account = match info {
-                    None => CacheAccount::new_loaded_not_existing(),
-                    Some(acc) if acc.is_empty() => {
-                        CacheAccount::new_loaded_empty_eip161(HashMap::new())
-                    }
-                    Some(acc) => CacheAccount::new_loaded(acc, HashMap::new()),
-                };
-                Ok(entry.insert(account))
+                Ok(entry.insert(Self::loaded_cache_account(info)))
             }
             hash_map::Entry::Occupied(entry) => Ok(entry.into_mut()),
         }
     }
 
+    /// Loads the accounts that are not cached, and not found in the bundle if it is used,
+    /// with one [`Database::basic_many`] call.
+    pub fn load_cache_accounts(&mut self, addresses: &[Address]) -> Result<(), DB::Error> {
+        let mut missing = Vec::new();
+        for address in addresses {
+            if self.cache.accounts.contains_key(address) || missing.contains(address) {
+                continue;
+            }
+            if self.use_preloaded_bundle {
+                if let Some(account) = self.bundle_state.account(address).cloned() {
+                    self.cache.accounts.insert(*address, account.into());
+                    continue;
+                }
+            }
+            missing.push(*address);
+        }
+        if !missing.is_empty() {
+            let infos = self.database.basic_many(&missing)?;
+            for (address, info) in missing.into_iter().zip(infos) {
+                self.cache
+                    .accounts
+                    .insert(address, Self::loaded_cache_account(info));
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the cache account from the info loaded from the database.
+    fn loaded_cache_account(info: Option<AccountInfo>) -> CacheAccount {
+        match info {
+            None => CacheAccount::new_loaded_not_existing(),
+            Some(acc) if acc.is_empty() => CacheAccount::new_loaded_empty_eip161(HashMap::new()),
+            Some(acc) => CacheAccount::new_loaded(acc, HashMap::new()),
+        }
+    }
+
     // TODO make cache aware of transitions dropping by having global transition counter.
     /// Takes changeset and reverts from state and replaces it with empty one.
     /// This will trop pending Transition and any transitions would be lost.
@@ -290,6 +319,58 @@ impl<DB: Database> Database for State<DB> {
             }
         }
     }
+
+    fn basic_many(
+        &mut self,
+        addresses: &[Address],
+    ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+        self.load_cache_accounts(addresses)?;
+        Ok(addresses
+            .iter()
+            .map(|address| self.cache.accounts[address].account_info())
+            .collect())
+    }
+
+    fn storage_many(&mut self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+        let addresses = slots
+            .iter()
+            .map(|(address, _)| *address)
+            .collect::<Vec<_>>();
+        self.load_cache_accounts(&addresses)?;
+
+        // slots of the existing accounts that are neither cached nor known to be zero.
+        let mut missing = Vec::new();
+        for (address, index) in slots {
+            let account = &self.cache.accounts[address];
+            let is_missing = account
+                .account
+                .as_ref()
+                .is_some_and(|account| !account.storage.contains_key(index))
+                && !account.status.is_storage_known();
+            if is_missing && !missing.contains(&(*address, *index)) {
+                missing.push((*address, *index));
+            }
+        }
+        if !missing.is_empty() {
+            let values = self.database.storage_many(&missing)?;
+            for ((address, index), value) in missing.into_iter().zip(values) {
+                if let Some(account) = self
+                    .cache
+                    .accounts
+                    .get_mut(&address)
+                    .unwrap()
+                    .account
+                    .as_mut()
+                {
+                    account.storage.insert(index, value);
+                }
+            }
+        }
+        slots
+            .iter()
+            .map(|(address, index)| self.storage(*address, *index))
+            .collect()
+    }
 }
 
 impl<DB: Database> DatabaseCommit for State<DB> {
@@ -0,0 +1,333 @@
+use super::{BundleState, CacheState, PlainAccount};
+use revm_interpreter::primitives::{
+    keccak256,
+    trie::{storage_root, trie_root, TrieAccount},
+    AccountInfo, Address, HashMap, B256, U256,
+};
+
+/// Account tracked by [`StateRoot`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct StateRootAccount {
+    /// Hashed address, key of the account in the state trie.
+    hashed_address: B256,
+    /// Account info.
+    info: AccountInfo,
+    /// Storage of the account. Only non zero values are present.
+    storage: HashMap<U256, U256>,
+    /// Cached storage root. `None` if storage changed since the root was calculated.
+    storage_root: Option<B256>,
+}
+
+impl StateRootAccount {
+    fn new(address: Address) -> Self {
+        Self {
+            hashed_address: keccak256(address),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the storage slot and invalidates the cached storage root if value changed.
+    fn set_storage(&mut self, slot: U256, value: U256) {
+        let changed = if value == U256::ZERO {
+            self.storage.remove(&slot).is_some()
+        } else {
+            self.storage.insert(slot, value) != Some(value)
+        };
+        if changed {
+            self.storage_root = None;
+        }
+    }
+
+    /// Clears the storage and invalidates the cached storage root if it was not empty.
+    fn wipe_storage(&mut self) {
+        if !self.storage.is_empty() {
+            self.storage.clear();
+            self.storage_root = None;
+        }
+    }
+
+    /// Returns storage root, calculating it if storage changed.
+    fn storage_root(&mut self) -> B256 {
+        *self
+            .storage_root
+            .get_or_insert_with(|| storage_root(self.storage.iter().map(|(k, v)| (*k, *v))))
+    }
+}
+
+/// State root calculator.
+///
+/// Holds the full plain state needed to calculate the state root and is kept up to date by
+/// applying [`BundleState`] or [`CacheState`] changes on top of it. Storage roots are cached
+/// and only recalculated for accounts whose storage changed since the last calculation.
+///
+/// Bytecode is not tracked, only the code hash of the account is needed for the state root.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StateRoot {
+    /// All existing accounts of the state.
+    accounts: HashMap<Address, StateRootAccount>,
+}
+
+impl StateRoot {
+    /// Creates new empty state root calculator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates state root calculator from the full list of accounts.
+    pub fn from_plain_accounts<'a>(
+        accounts: impl IntoIterator<Item = (Address, &'a PlainAccount)>,
+    ) -> Self {
+        let mut this = Self::default();
+        for (address, account) in accounts {
+            this.insert_account(
+                address,
+                account.info.clone(),
+                account.storage.iter().map(|(k, v)| (*k, *v)),
+            );
+        }
+        this
+    }
+
+    /// Creates state root calculator from the cache state.
+    ///
+    /// Cache is expected to contain the full state, as it does when state is built
+    /// from the genesis or the test alloc.
+    pub fn from_cache_state(cache: &CacheState) -> Self {
+        let mut this = Self::default();
+        this.apply_cache_state(cache);
+        this
+    }
+
+    /// Returns number of accounts in the state.
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Returns true if there are no accounts in the state.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Inserts account with the full storage, replacing the existing one.
+    pub fn insert_account(
+        &mut self,
+        address: Address,
+        info: AccountInfo,
+        storage: impl IntoIterator<Item = (U256, U256)>,
+    ) {
+        self.update_account(address, Some(info), true, storage);
+    }
+
+    /// Removes account from the state.
+    pub fn remove_account(&mut self, address: Address) {
+        self.accounts.remove(&address);
+    }
+
+    /// Applies changes of the bundle on top of the state.
+    ///
+    /// Bundle should contain changes made after the state this calculator was
+    /// built from, for example bundle of every executed block.
+    pub fn apply_bundle_state(&mut self, bundle: &BundleState) {
+        for (address, account) in bundle.state() {
+            self.update_account(
+                *address,
+                account.info.clone(),
+                // same as in the plain state changeset, storage is wiped if account was destroyed.
+                account.was_destroyed(),
+                account
+                    .storage
+                    .iter()
+                    .map(|(slot, value)| (*slot, value.present_value)),
+            );
+        }
+    }
+
+    /// Applies accounts of the cache state on top of the state.
+    ///
+    /// Storage slots that are not present in the cache are left unchanged, unless account
+    /// storage is known to be wiped.
+    pub fn apply_cache_state(&mut self, cache: &CacheState) {
+        for (address, account) in &cache.accounts {
+            match &account.account {
+                Some(plain) => self.update_account(
+                    *address,
+                    Some(plain.info.clone()),
+                    account.status.is_storage_known(),
+                    plain.storage.iter().map(|(k, v)| (*k, *v)),
+                ),
+                None => self.remove_account(*address),
+            }
+        }
+    }
+
+    /// Updates account info and storage. Account is removed if `info` is `None`.
+    fn update_account(
+        &mut self,
+        address: Address,
+        info: Option<AccountInfo>,
+        wipe_storage: bool,
+        storage: impl IntoIterator<Item = (U256, U256)>,
+    ) {
+        let Some(mut info) = info else {
+            self.remove_account(address);
+            return;
+        };
+        // code is not part of the state root.
+        info.code = None;
+
+        let account = self
+            .accounts
+            .entry(address)
+            .or_insert_with(|| StateRootAccount::new(address));
+        account.info = info;
+        if wipe_storage {
+            account.wipe_storage();
+        }
+        for (slot, value) in storage {
+            account.set_storage(slot, value);
+        }
+    }
+
+    /// Returns the storage root of the account or `None` if account does not exist.
+    pub fn storage_root(&mut self, address: Address) -> Option<B256> {
+        self.accounts
+            .get_mut(&address)
+            .map(StateRootAccount::storage_root)
+    }
+
+    /// Calculates the state root.
+    ///
+    /// Storage roots are recalculated only for accounts whose storage changed.
+    pub fn state_root(&mut self) -> B256 {
+        trie_root(self.accounts.values_mut().map(|account| {
+            let storage_root = account.storage_root();
+            (
+                account.hashed_address,
+                TrieAccount::new(&account.info, storage_root).encoded(),
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{states::bundle_state::BundleRetention, State},
+        primitives::{
+            address, trie::EMPTY_ROOT_HASH, Account, AccountStatus as EvmAccountStatus, EvmState,
+            EvmStorageSlot, KECCAK_EMPTY,
+        },
+        DatabaseCommit,
+    };
+
+    fn info(balance: u64) -> AccountInfo {
+        AccountInfo {
+            balance: U256::from(balance),
+            nonce: 1,
+            code_hash: KECCAK_EMPTY,
+            code: None,
+        }
+    }
+
+    /// Account with storage changes given as `(slot, original, present)`.
+    fn changed_account(balance: u64, storage: &[(u64, u64, u64)]) -> Account {
+        Account {
+            info: info(balance),
+            storage: storage
+                .iter()
+                .map(|(slot, original, present)| {
+                    (
+                        U256::from(*slot),
+                        EvmStorageSlot::new_changed(U256::from(*original), U256::from(*present)),
+                    )
+                })
+                .collect(),
+            status: EvmAccountStatus::Touched,
+        }
+    }
+
+    #[test]
+    fn empty_state_root() {
+        assert_eq!(StateRoot::new().state_root(), EMPTY_ROOT_HASH);
+    }
+
+    #[test]
+    fn incremental_matches_full_recalculation() {
+        let address1 = address!("1000000000000000000000000000000000000001");
+        let address2 = address!("1000000000000000000000000000000000000002");
+
+        let mut state = State::builder().with_bundle_update().build();
+        let mut state_root = StateRoot::new();
+        for address in [address1, address2] {
+            state.load_cache_account(address).unwrap();
+        }
+
+        // first block creates both accounts.
+        state.commit(EvmState::from_iter([
+            (address1, changed_account(10, &[(1, 0, 1), (2, 0, 2)])),
+            (address2, changed_account(20, &[(1, 0, 3)])),
+        ]));
+        state.merge_transitions(BundleRetention::Reverts);
+        state_root.apply_bundle_state(&state.take_bundle());
+        let root1 = state_root.state_root();
+        assert_eq!(
+            root1,
+            StateRoot::from_cache_state(&state.cache).state_root()
+        );
+        let storage_root2 = state_root.storage_root(address2).unwrap();
+
+        // second block changes storage of the first account and balance of the second.
+        state.commit(EvmState::from_iter([
+            (address1, changed_account(10, &[(1, 1, 0), (3, 0, 3)])),
+            (address2, changed_account(25, &[])),
+        ]));
+        state.merge_transitions(BundleRetention::Reverts);
+        state_root.apply_bundle_state(&state.take_bundle());
+
+        // storage root of the second account is cached, first one is invalidated.
+        assert_eq!(
+            state_root.accounts[&address2].storage_root,
+            Some(storage_root2)
+        );
+        assert_eq!(state_root.accounts[&address1].storage_root, None);
+
+        let root2 = state_root.state_root();
+        assert_ne!(root1, root2);
+        assert_eq!(
+            root2,
+            StateRoot::from_cache_state(&state.cache).state_root()
+        );
+
+        let full = StateRoot::from_plain_accounts(
+            [
+                (
+                    address1,
+                    &PlainAccount {
+                        info: info(10),
+                        storage: HashMap::from_iter([
+                            (U256::from(2), U256::from(2)),
+                            (U256::from(3), U256::from(3)),
+                        ]),
+                    },
+                ),
+                (
+                    address2,
+                    &PlainAccount {
+                        info: info(25),
+                        storage: HashMap::from_iter([(U256::from(1), U256::from(3))]),
+                    },
+                ),
+            ]
+            .iter()
+            .map(|(address, account)| (*address, *account)),
+        )
+        .state_root();
+        assert_eq!(root2, full);
+
+        // removing the account changes the state root back to the single account state.
+        state_root.remove_account(address1);
+        assert_eq!(state_root.len(), 1);
+        assert_ne!(state_root.state_root(), root2);
+    }
+}
@@ -1,23 +1,36 @@
 use crate::{
     builder::{EvmBuilder, HandlerStage, SetGenericStage},
     db::{Database, DatabaseCommit, EmptyDB},
-    handler::Handler,
+    handler::{mainnet, Handler},
     interpreter::{
-        analysis::validate_eof, CallInputs, CreateInputs, EOFCreateInputs, EOFCreateOutcome, Gas,
-        Host, InstructionResult, InterpreterAction, InterpreterResult, SharedMemory,
+        analysis::validate_eof, gas::CALL_STIPEND, CallInputs, CreateInputs, EOFCreateInputs,
+        EOFCreateOutcome, Gas, Host, InstructionResult, InterpreterAction, InterpreterResult,
+        SharedMemory,
     },
     primitives::{
-        specification::SpecId, BlockEnv, Bytes, CfgEnv, EVMError, EVMResult, EnvWithHandlerCfg,
-        ExecutionResult, HandlerCfg, ResultAndState, TransactTo, TxEnv,
+        spec_to_generic, specification::SpecId, Address, BlockEnv, BlockOverrides, Bytes, CfgEnv,
+        EVMError, EVMResult, EnvWithHandlerCfg, ExecutionResult, HandlerCfg, InvalidHeader,
+        ResultAndState, TransactTo, TxEnv, BEACON_ROOTS_ADDRESS, SYSTEM_ADDRESS,
+        SYSTEM_CALL_GAS_LIMIT, U256,
     },
-    Context, ContextWithHandlerCfg, Frame, FrameOrResult, FrameResult,
+    Context, ContextWithHandlerCfg, Frame, FrameOrResult, FrameResult, ReadSet, WriteSet,
 };
-use core::fmt;
+use core::{fmt, mem};
 use std::vec::Vec;
 
 /// EVM call stack limit.
 pub const CALL_STACK_LIMIT: u64 = 1024;
 
+/// Result of the [`Evm::estimate_gas`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GasEstimate {
+    /// Minimal gas limit with which the transaction succeeds, or the highest gas limit
+    /// that was tried if it fails.
+    pub gas_limit: u64,
+    /// Result of the transaction executed with the `gas_limit`.
+    pub result: ExecutionResult,
+}
+
 /// EVM instance containing both internal EVM context and external context
 /// and the handler that dictates the logic of EVM (or hardfork specification).
 pub struct Evm<'a, EXT, DB: Database> {
@@ -48,6 +61,67 @@ impl<EXT, DB: Database + DatabaseCommit> Evm<'_, EXT, DB> {
         self.context.evm.db.commit(state);
         Ok(result)
     }
+
+    /// Executes the system call and commits the changes to the database.
+    ///
+    /// See [`Evm::transact_system_call`].
+    pub fn transact_system_call_commit(
+        &mut self,
+        system_contract_address: Address,
+        data: Bytes,
+    ) -> Result<ExecutionResult, EVMError<DB::Error>> {
+        let ResultAndState { result, state } =
+            self.transact_system_call(system_contract_address, data)?;
+        self.context.evm.db.commit(state);
+        Ok(result)
+    }
+
+    /// Applies the pre-block call to the EIP-4788 beacon roots contract and commits the changes.
+    ///
+    /// Parent beacon block root from [`BlockEnv::parent_beacon_block_root`] is stored in the
+    /// contract. Returns `None` if the call is skipped, that is the case before Cancun and for
+    /// the genesis block.
+    pub fn apply_beacon_root_contract_call(
+        &mut self,
+    ) -> Result<Option<ExecutionResult>, EVMError<DB::Error>> {
+        if !self.spec_id().is_enabled_in(SpecId::CANCUN) {
+            return Ok(None);
+        }
+
+        let block = self.block();
+        let parent_beacon_block_root = block
+            .parent_beacon_block_root
+            .ok_or(EVMError::Header(InvalidHeader::ParentBeaconBlockRootNotSet))?;
+
+        // There is no parent beacon block root for the genesis block.
+        if block.number == U256::ZERO {
+            return Ok(None);
+        }
+
+        self.transact_system_call_commit(BEACON_ROOTS_ADDRESS, parent_beacon_block_root.into())
+            .map(Some)
+    }
+
+    /// Applies the EIP-2935 block-start update of the history storage contract and commits
+    /// the changes.
+    ///
+    /// Parent block hash is stored in the storage of the history contract, from where it is
+    /// read by the `BLOCKHASH` opcode. See [`mainnet::apply_blockhashes_update`].
+    pub fn apply_blockhashes_update(&mut self) -> Result<(), EVMError<DB::Error>> {
+        let spec_id = self.spec_id();
+        let output = spec_to_generic!(
+            spec_id,
+            mainnet::apply_blockhashes_update::<SPEC, EXT, DB>(&mut self.context)
+        );
+        if let Err(e) = output {
+            self.clear();
+            return Err(e);
+        }
+
+        let (state, _) = self.context.evm.journaled_state.finalize();
+        self.context.evm.db.commit(state);
+        Ok(())
+    }
 }
 
 impl<'a> Evm<'a, (), EmptyDB> {
@@ -240,6 +314,204 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         output
     }
 
+    /// Transact transaction with the [`BlockOverrides`] applied to the block environment.
+    ///
+    /// Block environment is restored after the transaction, so the overrides apply only to
+    /// this call. Combine with [`StateOverrideDB`](crate::db::StateOverrideDB) to also
+    /// override the state, as `eth_call` does.
+    ///
+    /// This function will validate the transaction.
+    pub fn transact_with_block_overrides(
+        &mut self,
+        overrides: &BlockOverrides,
+    ) -> EVMResult<DB::Error> {
+        let block = self.block().clone();
+        self.block_mut().apply_overrides(overrides);
+        let output = self.transact();
+        *self.block_mut() = block;
+        output
+    }
+
+    /// Transact transaction and record the state it read and wrote.
+    ///
+    /// Returns the [`ReadSet`] with values read from the database and the [`WriteSet`]
+    /// with values changed by the transaction, alongside the [`ResultAndState`].
+    /// These can be used to detect conflicts between transactions executed in parallel.
+    ///
+    /// Balance increments recorded by the
+    /// [`lazy_balance_handle_register`](crate::handler::lazy_balance::lazy_balance_handle_register)
+    /// are not applied to the state and are returned in [`WriteSet::balance_increments`].
+    ///
+    /// This function will validate the transaction.
+    pub fn transact_with_read_write_sets(
+        &mut self,
+    ) -> Result<(ResultAndState, ReadSet, WriteSet), EVMError<DB::Error>> {
+        self.context.evm.journaled_state.record_reads();
+        let initial_gas_spend = self
+            .preverify_transaction_inner()
+            .inspect_err(|_| self.clear())?;
+
+        let output = self.transact_preverified_inner(initial_gas_spend);
+        let output = self.handler.post_execution().end(&mut self.context, output);
+        let read_set = self
+            .context
+            .evm
+            .journaled_state
+            .take_read_set()
+            .unwrap_or_default();
+        let balance_increments =
+            mem::take(&mut self.context.evm.journaled_state.balance_increments);
+        self.clear();
+
+        let output = output?;
+        let mut write_set = WriteSet::new(&output.state, &read_set);
+        write_set.balance_increments = balance_increments;
+        Ok((output, read_set, write_set))
+    }
+
+    /// Executes a system call to the `system_contract_address` with the given `data`.
+    ///
+    /// System call is a block-level call made from [`SYSTEM_ADDRESS`] with
+    /// [`SYSTEM_CALL_GAS_LIMIT`] gas. It is not validated as a transaction, intrinsic gas is not
+    /// charged, caller nonce is not increased and no fees are deducted or paid to the beneficiary.
+    /// If there is no code at the address, the call succeeds without doing anything.
+    ///
+    /// Transaction environment is restored after the call.
+    pub fn transact_system_call(
+        &mut self,
+        system_contract_address: Address,
+        data: Bytes,
+    ) -> EVMResult<DB::Error> {
+        let system_tx = TxEnv {
+            caller: SYSTEM_ADDRESS,
+            gas_limit: SYSTEM_CALL_GAS_LIMIT,
+            transact_to: TransactTo::Call(system_contract_address),
+            data,
+            ..Default::default()
+        };
+        let tx = mem::replace(&mut self.context.evm.env.tx, system_tx);
+
+        let output = self.transact_system_call_inner();
+        let output = self.handler.post_execution().end(&mut self.context, output);
+        self.clear();
+
+        self.context.evm.env.tx = tx;
+        output
+    }
+
+    /// Estimates the minimal gas limit the transaction needs to succeed, same as
+    /// `eth_estimateGas`.
+    ///
+    /// Transaction is executed with the gas limits chosen by binary search between the
+    /// intrinsic gas and the highest possible gas limit. Highest gas limit is the gas limit
+    /// of the transaction, capped by the block gas limit and by what the caller can pay for.
+    ///
+    /// Gas used before the refund is the lower bound of the search and the first probe is done
+    /// with some headroom for the 63/64 rule of the calls, as most transactions succeed with it.
+    ///
+    /// If the transaction fails with the highest gas limit, estimation stops and the failed
+    /// result is returned: [`ExecutionResult::Revert`] if the transaction reverts or
+    /// [`HaltReason::OutOfGas`] if the highest gas limit is not enough.
+    ///
+    /// State is never committed and the gas limit of the transaction is restored.
+    ///
+    /// [`HaltReason::OutOfGas`]: crate::primitives::HaltReason::OutOfGas
+    pub fn estimate_gas(&mut self) -> Result<GasEstimate, EVMError<DB::Error>> {
+        let gas_limit = self.context.evm.env.tx.gas_limit;
+        let output = self.estimate_gas_inner();
+        self.context.evm.env.tx.gas_limit = gas_limit;
+        output
+    }
+
+    fn estimate_gas_inner(&mut self) -> Result<GasEstimate, EVMError<DB::Error>> {
+        let mut hi = self.estimate_gas_cap()?;
+        let mut result = self.transact_with_gas_limit(hi)?;
+        if !result.is_success() {
+            return Ok(GasEstimate {
+                gas_limit: hi,
+                result,
+            });
+        }
+
+        let initial_gas = self
+            .handler
+            .validation()
+            .initial_tx_gas(&self.context.evm.env)?;
+        // execution can't succeed with less gas than it spent before the refund.
+        let gas_spent = result.gas_used() + result.gas_refunded();
+        let mut lo = gas_spent.max(initial_gas).saturating_sub(1);
+
+        // most transactions succeed when there is enough gas left for the 63/64 rule
+        // and the call stipend.
+        let optimistic = (gas_spent + CALL_STIPEND) * 64 / 63;
+        if optimistic < hi {
+            let optimistic_result = self.transact_with_gas_limit(optimistic)?;
+            if optimistic_result.is_success() {
+                hi = optimistic;
+                result = optimistic_result;
+            } else {
+                lo = optimistic;
+            }
+        }
+
+        while lo + 1 < hi {
+            let mid = lo + (hi - lo) / 2;
+            let mid_result = self.transact_with_gas_limit(mid)?;
+            if mid_result.is_success() {
+                hi = mid;
+                result = mid_result;
+            } else {
+                // transaction can revert or halt on low gas without running out of it,
+                // for example if it checks the remaining gas, so every failure means
+                // that more gas is needed.
+                lo = mid;
+            }
+        }
+
+        Ok(GasEstimate {
+            gas_limit: hi,
+            result,
+        })
+    }
+
+    /// Returns the highest gas limit that can be used for the transaction.
+    fn estimate_gas_cap(&mut self) -> Result<u64, EVMError<DB::Error>> {
+        let env = &self.context.evm.env;
+        let mut cap = env.tx.gas_limit;
+        if !env.cfg.is_block_gas_limit_disabled() {
+            cap = cap.min(env.block.gas_limit.saturating_to());
+        }
+
+        let gas_price = env.tx.gas_price;
+        if gas_price.is_zero() || env.cfg.is_balance_check_disabled() {
+            return Ok(cap);
+        }
+        // value and the blob fee are paid before the gas.
+        let reserved = env
+            .tx
+            .value
+            .saturating_add(env.calc_max_data_fee().unwrap_or_default());
+        let caller = env.tx.caller;
+        let inner = &mut self.context.evm.inner;
+        let balance = inner
+            .journaled_state
+            .load_account(caller, &mut inner.db)
+            .map(|(account, _)| account.info.balance);
+        // the caller is loaded again by the executions.
+        self.clear();
+        let allowance = balance?.saturating_sub(reserved) / gas_price;
+        Ok(cap.min(allowance.saturating_to()))
+    }
+
+    /// Executes the transaction with the given gas limit.
+    fn transact_with_gas_limit(
+        &mut self,
+        gas_limit: u64,
+    ) -> Result<ExecutionResult, EVMError<DB::Error>> {
+        self.context.evm.env.tx.gas_limit = gas_limit;
+        self.transact().map(|result| result.result)
+    }
+
     /// Returns the reference of handler configuration
     #[inline]
     pub fn handler_cfg(&self) -> &HandlerCfg {
@@ -323,6 +595,44 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         ContextWithHandlerCfg::new(self.context, self.handler.cfg)
     }
 
+    /// Executes the system call that is set in the transaction environment.
+    ///
+    /// Caller is neither charged nor reimbursed and beneficiary is not rewarded.
+    fn transact_system_call_inner(&mut self) -> EVMResult<DB::Error> {
+        let ctx = &mut self.context;
+        let pre_exec = self.handler.pre_execution();
+
+        // load access list and beneficiary if needed.
+        pre_exec.load_accounts(ctx)?;
+
+        // load precompiles
+        let precompiles = pre_exec.load_precompiles();
+        ctx.evm.set_precompiles(precompiles);
+
+        // system call is not charged for the intrinsic gas.
+        let gas_limit = ctx.evm.env.tx.gas_limit;
+        let first_frame_or_result = self.handler.execution().call(
+            ctx,
+            CallInputs::new_boxed(&ctx.evm.env.tx, gas_limit).unwrap(),
+        )?;
+
+        // Starts the main running loop.
+        let mut result = match first_frame_or_result {
+            FrameOrResult::Frame(first_frame) => self.run_the_loop(first_frame)?,
+            FrameOrResult::Result(result) => result,
+        };
+
+        let ctx = &mut self.context;
+
+        // handle output of the call.
+        self.handler
+            .execution()
+            .last_frame_return(ctx, &mut result)?;
+
+        // Returns output of the call.
+        self.handler.post_execution().output(ctx, result)
+    }
+
     /// Transact pre-verified transaction.
     fn transact_preverified_inner(&mut self, initial_gas_spend: u64) -> EVMResult<DB::Error> {
         let spec_id = self.spec_id();
@@ -339,6 +649,9 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         // deduce caller balance with its limit.
         pre_exec.deduct_caller(ctx)?;
 
+        // apply EIP-7702 auth list.
+        let eip7702_gas_refund = pre_exec.apply_eip7702_auth_list(ctx)? as i64;
+
         let gas_limit = ctx.evm.env.tx.gas_limit - initial_gas_spend;
 
         let exec = self.handler.execution();
@@ -418,6 +731,14 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
             .execution()
             .last_frame_return(ctx, &mut result)?;
 
+        // EIP-7702 refund is not reverted together with the execution,
+        // it is added after the refund of the last frame is handled.
+        if eip7702_gas_refund != 0 && !ctx.evm.env.cfg.is_gas_refund_disabled() {
+            let gas = result.gas_mut();
+            gas.record_refund(eip7702_gas_refund);
+            gas.set_final_refund(spec_id.is_enabled_in(SpecId::LONDON));
+        }
+
         let post_exec = self.handler.post_execution();
         // Reimburse the caller
         post_exec.reimburse_caller(ctx, result.gas())?;
@@ -427,3 +748,461 @@ impl<EXT, DB: Database> Evm<'_, EXT, DB> {
         post_exec.output(ctx, result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::InMemoryDB,
+        interpreter::{
+            gas::COLD_ACCOUNT_ACCESS_COST,
+            opcode::{
+                BLOCKHASH, CALL, CALLDATALOAD, GAS, GT, JUMPDEST, JUMPI, PUSH0, PUSH1, PUSH2,
+                PUSH20, PUSH3, REVERT, SSTORE, STOP,
+            },
+        },
+        primitives::{
+            address, b256,
+            eip7702::{PER_AUTH_BASE_COST, PER_EMPTY_ACCOUNT_COST},
+            AccountInfo, Authorization, AuthorizationList, Bytecode, ExecutionResult, HaltReason,
+            OutOfGasError, RecoveredAuthorization, ResultAndState, BLOCKHASH_STORAGE_ADDRESS, U256,
+        },
+    };
+
+    #[test]
+    fn sanity_eip7702_tx() {
+        let delegate = address!("00000000000000000000000000000000000000ff");
+        let caller = address!("0000000000000000000000000000000000000001");
+        let auth = address!("0000000000000000000000000000000000000100");
+
+        let bytecode = Bytecode::new_raw([PUSH1, 0x01, PUSH1, 0x01, SSTORE].into());
+
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::PRAGUE)
+            .with_db(InMemoryDB::default())
+            .modify_db(|db| {
+                db.insert_account_info(
+                    delegate,
+                    AccountInfo::new(U256::ZERO, 0, bytecode.hash_slow(), bytecode),
+                )
+            })
+            .modify_tx_env(|tx| {
+                tx.authorization_list =
+                    Some(AuthorizationList::Recovered(vec![RecoveredAuthorization {
+                        inner: Authorization {
+                            chain_id: U256::from(1),
+                            address: delegate,
+                            nonce: 0,
+                        },
+                        authority: Some(auth),
+                    }]));
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(auth);
+            })
+            .build();
+
+        let ok = evm.transact().unwrap();
+
+        let auth_acc = ok.state.get(&auth).unwrap();
+        assert_eq!(auth_acc.info.code, Some(Bytecode::new_eip7702(delegate)));
+        assert_eq!(auth_acc.info.nonce, 1);
+        assert_eq!(
+            auth_acc.storage.get(&U256::from(1)).unwrap().present_value,
+            U256::from(1)
+        );
+    }
+
+    /// Transacts a Prague transaction to `to` with the given authorizations.
+    fn transact_eip7702(
+        authorizations: Vec<RecoveredAuthorization>,
+        to: Address,
+        modify_db: impl FnOnce(&mut InMemoryDB),
+    ) -> ResultAndState {
+        let mut db = InMemoryDB::default();
+        modify_db(&mut db);
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::PRAGUE)
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.authorization_list = Some(AuthorizationList::Recovered(authorizations));
+                tx.caller = address!("0000000000000000000000000000000000000001");
+                tx.transact_to = TransactTo::Call(to);
+            })
+            .build();
+        evm.transact().unwrap()
+    }
+
+    fn authorization(
+        authority: Option<Address>,
+        address: Address,
+        chain_id: u64,
+        nonce: u64,
+    ) -> RecoveredAuthorization {
+        RecoveredAuthorization {
+            inner: Authorization {
+                chain_id: U256::from(chain_id),
+                address,
+                nonce,
+            },
+            authority,
+        }
+    }
+
+    #[test]
+    fn eip7702_skips_invalid_authorizations() {
+        let delegate = address!("00000000000000000000000000000000000000ff");
+        let authorities = [
+            address!("0000000000000000000000000000000000000100"),
+            address!("0000000000000000000000000000000000000101"),
+            address!("0000000000000000000000000000000000000102"),
+        ];
+        let ok = transact_eip7702(
+            vec![
+                // signature that does not recover.
+                authorization(None, delegate, 1, 0),
+                // other chain.
+                authorization(Some(authorities[1]), delegate, 2, 0),
+                // nonce mismatch.
+                authorization(Some(authorities[2]), delegate, 1, 1),
+                // the only valid one.
+                authorization(Some(authorities[0]), delegate, 0, 0),
+            ],
+            delegate,
+            |_| {},
+        );
+        assert!(ok.result.is_success());
+        let delegated = ok.state.get(&authorities[0]).unwrap();
+        assert_eq!(delegated.info.code, Some(Bytecode::new_eip7702(delegate)));
+        assert_eq!(delegated.info.nonce, 1);
+        for authority in &authorities[1..] {
+            let Some(account) = ok.state.get(authority) else {
+                continue;
+            };
+            assert!(account.info.code.iter().all(Bytecode::is_empty));
+            assert_eq!(account.info.nonce, 0);
+        }
+        // all authorizations are charged.
+        assert_eq!(ok.result.gas_used(), 21000 + 4 * PER_EMPTY_ACCOUNT_COST);
+    }
+
+    #[test]
+    fn eip7702_refunds_existing_authority() {
+        let delegate = address!("00000000000000000000000000000000000000ff");
+        let authority = address!("0000000000000000000000000000000000000100");
+        let transact = |exists: bool| {
+            transact_eip7702(
+                vec![authorization(Some(authority), delegate, 1, 0)],
+                authority,
+                |db| {
+                    if exists {
+                        db.insert_account_info(authority, AccountInfo::from_balance(U256::from(1)));
+                    }
+                },
+            )
+        };
+
+        let gas_used = 21000 + PER_EMPTY_ACCOUNT_COST;
+        let ExecutionResult::Success { gas_refunded, .. } = transact(false).result else {
+            panic!("expected success");
+        };
+        assert_eq!(gas_refunded, 0);
+
+        // refund of PER_EMPTY_ACCOUNT_COST - PER_AUTH_BASE_COST is capped at a fifth of the gas.
+        let result = transact(true).result;
+        let refund = (PER_EMPTY_ACCOUNT_COST - PER_AUTH_BASE_COST).min(gas_used / 5);
+        let ExecutionResult::Success { gas_refunded, .. } = result else {
+            panic!("expected success");
+        };
+        assert_eq!(gas_refunded, refund);
+        assert_eq!(result.gas_used(), gas_used - refund);
+    }
+
+    #[test]
+    fn eip7702_delegated_call_gas() {
+        let delegate = address!("00000000000000000000000000000000000000ff");
+        let authority = address!("0000000000000000000000000000000000000100");
+        let contract = address!("0000000000000000000000000000000000000200");
+        // CALL(GAS, authority, 0, 0, 0, 0, 0)
+        let mut code = vec![PUSH0, PUSH0, PUSH0, PUSH0, PUSH0, PUSH20];
+        code.extend_from_slice(authority.as_slice());
+        code.extend_from_slice(&[GAS, CALL, STOP]);
+        let bytecode = Bytecode::new_raw(code.into());
+        let transact = |nonce: u64| {
+            let bytecode = bytecode.clone();
+            transact_eip7702(
+                vec![authorization(Some(authority), delegate, 1, nonce)],
+                contract,
+                |db| {
+                    db.insert_account_info(
+                        contract,
+                        AccountInfo::new(U256::ZERO, 0, bytecode.hash_slow(), bytecode),
+                    )
+                },
+            )
+        };
+
+        // authority is warm in both, only the delegated account is loaded cold.
+        let delegated = transact(0);
+        assert_eq!(
+            delegated.state[&authority].info.code,
+            Some(Bytecode::new_eip7702(delegate))
+        );
+        let not_delegated = transact(1);
+        assert_eq!(
+            delegated.result.gas_used() - not_delegated.result.gas_used(),
+            COLD_ACCOUNT_ACCESS_COST
+        );
+    }
+
+    #[test]
+    fn system_call_commits_without_charging_caller() {
+        // stores first calldata word to the slot zero.
+        let bytecode = Bytecode::new_raw([PUSH0, CALLDATALOAD, PUSH0, SSTORE].into());
+        let caller = address!("0000000000000000000000000000000000000001");
+        let root = b256!("0000000000000000000000000000000000000000000000000000000000000042");
+
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::CANCUN)
+            .with_db(InMemoryDB::default())
+            .modify_db(|db| {
+                db.insert_account_info(
+                    BEACON_ROOTS_ADDRESS,
+                    AccountInfo::new(U256::ZERO, 1, bytecode.hash_slow(), bytecode),
+                )
+            })
+            .modify_block_env(|block| block.number = U256::from(1))
+            .modify_tx_env(|tx| tx.caller = caller)
+            .build();
+
+        assert_eq!(
+            evm.apply_beacon_root_contract_call(),
+            Err(EVMError::Header(InvalidHeader::ParentBeaconBlockRootNotSet))
+        );
+
+        evm.block_mut().parent_beacon_block_root = Some(root);
+        let result = evm.apply_beacon_root_contract_call().unwrap().unwrap();
+        assert!(result.is_success());
+
+        // transaction environment is restored.
+        assert_eq!(evm.tx().caller, caller);
+
+        let db = evm.db();
+        assert_eq!(
+            db.accounts[&BEACON_ROOTS_ADDRESS].storage[&U256::ZERO],
+            U256::from_be_bytes(root.0)
+        );
+        assert!(!db.accounts.contains_key(&SYSTEM_ADDRESS));
+    }
+
+    #[test]
+    fn blockhash_from_history_storage() {
+        // stores BLOCKHASH(9000) at slot 0 and BLOCKHASH(9999) at slot 1.
+        let bytecode = Bytecode::new_raw(
+            [
+                PUSH2, 0x23, 0x28, BLOCKHASH, PUSH0, SSTORE, PUSH2, 0x27, 0x0F, BLOCKHASH, PUSH1,
+                0x01, SSTORE,
+            ]
+            .into(),
+        );
+        let contract = address!("0000000000000000000000000000000000000100");
+        let old_hash = U256::from(0x42);
+        let history_bytecode = Bytecode::new_raw([STOP].into());
+
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::PRAGUE)
+            .with_db(InMemoryDB::default())
+            .modify_db(|db| {
+                db.insert_account_info(
+                    contract,
+                    AccountInfo::new(U256::ZERO, 1, bytecode.hash_slow(), bytecode),
+                );
+                db.insert_account_info(
+                    BLOCKHASH_STORAGE_ADDRESS,
+                    AccountInfo::new(
+                        U256::ZERO,
+                        1,
+                        history_bytecode.hash_slow(),
+                        history_bytecode,
+                    ),
+                );
+                // hash of the block 9000, more than 256 blocks in the past.
+                db.insert_account_storage(
+                    BLOCKHASH_STORAGE_ADDRESS,
+                    U256::from(9000 % 8192),
+                    old_hash,
+                )
+                .unwrap();
+            })
+            .modify_block_env(|block| block.number = U256::from(10000))
+            .modify_tx_env(|tx| tx.transact_to = TransactTo::Call(contract))
+            .build();
+
+        evm.apply_blockhashes_update().unwrap();
+        let parent_hash = evm.db_mut().block_hash(U256::from(9999)).unwrap();
+        let parent_hash = U256::from_be_bytes(parent_hash.0);
+        assert_eq!(
+            evm.db().accounts[&BLOCKHASH_STORAGE_ADDRESS].storage[&U256::from(9999 % 8192)],
+            parent_hash
+        );
+
+        let state = evm.transact().unwrap().state;
+        let storage = &state[&contract].storage;
+        assert_eq!(storage[&U256::ZERO].present_value, old_hash);
+        assert_eq!(storage[&U256::from(1)].present_value, parent_hash);
+        // slots of the history storage contract are not loaded by BLOCKHASH.
+        assert!(state[&BLOCKHASH_STORAGE_ADDRESS].storage.is_empty());
+    }
+
+    #[test]
+    fn blockhashes_update_skipped_without_code() {
+        let mut evm = Evm::builder()
+            .with_spec_id(SpecId::PRAGUE)
+            .with_db(InMemoryDB::default())
+            .modify_block_env(|block| block.number = U256::from(10000))
+            .build();
+
+        evm.apply_blockhashes_update().unwrap();
+        let account = evm.db().accounts.get(&BLOCKHASH_STORAGE_ADDRESS);
+        assert!(account.iter().all(|account| account.storage.is_empty()));
+    }
+
+    #[test]
+    fn estimate_gas() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let contract = address!("1000000000000000000000000000000000000002");
+        // reverts if there is not more than 100_000 gas left, clears the slot one otherwise.
+        let bytecode = Bytecode::new_raw(
+            [
+                PUSH3, 0x01, 0x86, 0xa0, GAS, GT, PUSH1, 0x0c, JUMPI, PUSH0, PUSH0, REVERT,
+                JUMPDEST, PUSH0, PUSH1, 0x01, SSTORE, STOP,
+            ]
+            .into(),
+        );
+        let store = address!("1000000000000000000000000000000000000003");
+        let store_bytecode = Bytecode::new_raw([PUSH1, 0x01, PUSH0, SSTORE, STOP].into());
+
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_db(|db| {
+                db.insert_account_info(
+                    store,
+                    AccountInfo::new(U256::ZERO, 1, store_bytecode.hash_slow(), store_bytecode),
+                );
+                db.insert_account_info(
+                    caller,
+                    AccountInfo::from_balance(U256::from(10_000_000_000u64)),
+                );
+                db.insert_account_info(
+                    contract,
+                    AccountInfo::new(U256::ZERO, 1, bytecode.hash_slow(), bytecode),
+                );
+                db.insert_account_storage(contract, U256::from(1), U256::from(1))
+                    .unwrap();
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_price = U256::from(1);
+                tx.gas_limit = 1_000_000;
+            })
+            .build();
+
+        let estimate = evm.estimate_gas().unwrap();
+        assert!(estimate.result.is_success());
+        assert!(estimate.result.gas_refunded() > 0);
+        assert!(estimate.gas_limit > 100_000);
+        assert_eq!(evm.tx().gas_limit, 1_000_000);
+
+        evm.tx_mut().gas_limit = estimate.gas_limit;
+        assert_eq!(evm.transact().unwrap().result, estimate.result);
+        evm.tx_mut().gas_limit = estimate.gas_limit - 1;
+        assert!(matches!(
+            evm.transact().unwrap().result,
+            ExecutionResult::Revert { .. }
+        ));
+
+        // gas limit is capped by the caller balance.
+        evm.tx_mut().gas_limit = 1_000_000;
+        evm.tx_mut().gas_price = U256::from(100_000);
+        let estimate = evm.estimate_gas().unwrap();
+        assert_eq!(estimate.gas_limit, 100_000);
+        assert!(matches!(estimate.result, ExecutionResult::Revert { .. }));
+
+        // intrinsic gas is not enough.
+        evm.tx_mut().gas_price = U256::from(1);
+        evm.tx_mut().gas_limit = 30_000;
+        evm.tx_mut().transact_to = TransactTo::Call(caller);
+        evm.tx_mut().data = vec![1; 1000].into();
+        assert!(evm.estimate_gas().is_err());
+        evm.tx_mut().data = Default::default();
+
+        // not enough gas for the storage write.
+        evm.tx_mut().transact_to = TransactTo::Call(store);
+        evm.tx_mut().gas_limit = 30_000;
+        let estimate = evm.estimate_gas().unwrap();
+        assert_eq!(estimate.gas_limit, 30_000);
+        assert!(matches!(
+            estimate.result,
+            ExecutionResult::Halt {
+                reason: HaltReason::OutOfGas(OutOfGasError::Basic),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn estimate_gas_cap_reserves_value_and_blob_fee() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let contract = address!("1000000000000000000000000000000000000002");
+        // reverts if there is not more than 100_000 gas left.
+        let bytecode = Bytecode::new_raw(
+            [
+                PUSH3, 0x01, 0x86, 0xa0, GAS, GT, PUSH1, 0x0c, JUMPI, PUSH0, PUSH0, REVERT,
+                JUMPDEST, STOP,
+            ]
+            .into(),
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_db(|db| {
+                db.insert_account_info(
+                    caller,
+                    AccountInfo::from_balance(U256::from(10_000_000_000u64)),
+                );
+                db.insert_account_info(
+                    contract,
+                    AccountInfo::new(U256::ZERO, 1, bytecode.hash_slow(), bytecode),
+                );
+            })
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_price = U256::from(100_000);
+                tx.gas_limit = 1_000_000;
+            })
+            .build();
+
+        // half of the balance is transferred.
+        evm.tx_mut().value = U256::from(5_000_000_000u64);
+        let estimate = evm.estimate_gas().unwrap();
+        assert_eq!(estimate.gas_limit, 50_000);
+        assert!(matches!(estimate.result, ExecutionResult::Revert { .. }));
+
+        // blob fee of 131_072 * 40_000 is paid for the single blob.
+        evm.tx_mut().value = U256::ZERO;
+        evm.tx_mut().blob_hashes = vec![b256!(
+            "0100000000000000000000000000000000000000000000000000000000000000"
+        )];
+        evm.tx_mut().max_fee_per_blob_gas = Some(U256::from(40_000));
+        let estimate = evm.estimate_gas().unwrap();
+        assert_eq!(estimate.gas_limit, 47_571);
+        assert!(matches!(estimate.result, ExecutionResult::Revert { .. }));
+
+        // the estimate is not capped by the balance when the call succeeds below the cap.
+        evm.tx_mut().gas_price = U256::from(1);
+        let estimate = evm.estimate_gas().unwrap();
+        assert!(estimate.result.is_success());
+        assert!(estimate.gas_limit > 100_000);
+    }
+}
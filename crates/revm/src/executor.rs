@@ -0,0 +1,372 @@
+//! Block executor that runs all transactions of the block on top of [`State`].
+
+use crate::{
+    db::{states::bundle_state::BundleRetention, BundleState, Database, State, TransitionState},
+    primitives::{
+        receipts_bloom, receipts_root, Address, BlockEnv, Bloom, EVMError, Receipt, SpecId, TxEnv,
+        B256, MAX_BLOB_GAS_PER_BLOCK,
+    },
+    Evm,
+};
+use core::fmt;
+use std::vec::Vec;
+
+/// Validator withdrawal from the consensus layer.
+///
+/// Incorporated as part of the Shanghai upgrade via [EIP-4895].
+///
+/// [EIP-4895]: https://eips.ethereum.org/EIPS/eip-4895
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Withdrawal {
+    /// Monotonically increasing identifier issued by consensus layer.
+    pub index: u64,
+    /// Index of validator associated with withdrawal.
+    pub validator_index: u64,
+    /// Target address for withdrawn ether.
+    pub address: Address,
+    /// Value of the withdrawal in gwei.
+    pub amount: u64,
+}
+
+impl Withdrawal {
+    /// Returns the withdrawal amount in wei.
+    #[inline]
+    pub fn amount_wei(&self) -> u128 {
+        self.amount as u128 * 1_000_000_000
+    }
+}
+
+/// Output of the block execution.
+#[derive(Debug)]
+pub struct BlockExecutionOutput {
+    /// Receipts of all executed transactions, in the block order.
+    pub receipts: Vec<Receipt>,
+    /// Gas used by all transactions of the block.
+    pub gas_used: u64,
+    /// Blob gas used by all transactions of the block.
+    pub blob_gas_used: u64,
+    /// State changes of the block together with reverts.
+    pub bundle: BundleState,
+}
+
+impl BlockExecutionOutput {
+    /// Returns the receipts root of the block.
+    pub fn receipts_root(&self) -> B256 {
+        receipts_root(&self.receipts)
+    }
+
+    /// Returns the logs bloom of the block.
+    pub fn logs_bloom(&self) -> Bloom {
+        receipts_bloom(&self.receipts)
+    }
+}
+
+/// Errors that can happen during block execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlockExecutionError<DBError> {
+    /// Transaction gas limit is more than the gas left in the block.
+    BlockGasLimitExceeded {
+        /// Index of the transaction in the block.
+        index: usize,
+        /// Gas limit of the transaction.
+        tx_gas_limit: u64,
+        /// Gas that is left in the block.
+        block_available_gas: u64,
+    },
+    /// Transaction blob gas is more than the blob gas left in the block.
+    BlobGasLimitExceeded {
+        /// Index of the transaction in the block.
+        index: usize,
+        /// Blob gas of the transaction.
+        tx_blob_gas: u64,
+        /// Blob gas that is left in the block.
+        block_available_blob_gas: u64,
+    },
+    /// Transaction could not be executed.
+    Transaction {
+        /// Index of the transaction in the block.
+        index: usize,
+        /// Error returned by the EVM.
+        error: EVMError<DBError>,
+    },
+    /// Pre-block system calls or withdrawals could not be applied.
+    Evm(EVMError<DBError>),
+}
+
+impl<DBError> From<EVMError<DBError>> for BlockExecutionError<DBError> {
+    fn from(value: EVMError<DBError>) -> Self {
+        Self::Evm(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<DBError: std::error::Error + 'static> std::error::Error for BlockExecutionError<DBError> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transaction { error, .. } | Self::Evm(error) => Some(error),
+            Self::BlockGasLimitExceeded { .. } | Self::BlobGasLimitExceeded { .. } => None,
+        }
+    }
+}
+
+impl<DBError: fmt::Display> fmt::Display for BlockExecutionError<DBError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BlockGasLimitExceeded {
+                index,
+                tx_gas_limit,
+                block_available_gas,
+            } => write!(
+                f,
+                "transaction {index} gas limit {tx_gas_limit} is more than block available gas {block_available_gas}"
+            ),
+            Self::BlobGasLimitExceeded {
+                index,
+                tx_blob_gas,
+                block_available_blob_gas,
+            } => write!(
+                f,
+                "transaction {index} blob gas {tx_blob_gas} is more than block available blob gas {block_available_blob_gas}"
+            ),
+            Self::Transaction { index, error } => write!(f, "transaction {index} error: {error}"),
+            Self::Evm(error) => write!(f, "block execution error: {error}"),
+        }
+    }
+}
+
+/// Executes blocks of transactions on top of [`State`].
+///
+/// For every block the executor sets the [`BlockEnv`], applies the pre-block system calls
+/// (EIP-4788 beacon root and EIP-2935 block hashes), executes and commits the transactions,
+/// applies the withdrawals and merges all transitions into the [`BundleState`].
+///
+/// The executor enforces the block gas limit and the blob gas limit over all transactions
+/// of the block.
+pub struct BlockExecutor<'a, EXT, DB: Database> {
+    /// EVM used to execute the transactions.
+    evm: Evm<'a, EXT, State<DB>>,
+}
+
+impl<'a, EXT, DB: Database> BlockExecutor<'a, EXT, DB> {
+    /// Creates new block executor from the EVM.
+    ///
+    /// Transition tracking is enabled on the [`State`] if it was not already.
+    pub fn new(mut evm: Evm<'a, EXT, State<DB>>) -> Self {
+        let state = evm.db_mut();
+        if state.transition_state.is_none() {
+            state.transition_state = Some(TransitionState::default());
+        }
+        Self { evm }
+    }
+
+    /// Returns reference to the EVM.
+    pub fn evm(&self) -> &Evm<'a, EXT, State<DB>> {
+        &self.evm
+    }
+
+    /// Returns mutable reference to the EVM.
+    pub fn evm_mut(&mut self) -> &mut Evm<'a, EXT, State<DB>> {
+        &mut self.evm
+    }
+
+    /// Consumes the executor and returns the EVM.
+    pub fn into_evm(self) -> Evm<'a, EXT, State<DB>> {
+        self.evm
+    }
+
+    /// Executes the block.
+    ///
+    /// Returns receipts of the transactions and the [`BundleState`] with the changes
+    /// of the block. State changes of the failed block are not reverted.
+    pub fn execute_block(
+        &mut self,
+        block: BlockEnv,
+        txs: impl IntoIterator<Item = TxEnv>,
+        withdrawals: &[Withdrawal],
+    ) -> Result<BlockExecutionOutput, BlockExecutionError<DB::Error>> {
+        let spec_id = self.evm.spec_id();
+        *self.evm.block_mut() = block;
+
+        // EIP-161: State trie clearing.
+        self.evm
+            .db_mut()
+            .set_state_clear_flag(spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON));
+
+        // pre-block system calls.
+        self.evm.apply_beacon_root_contract_call()?;
+        self.evm.apply_blockhashes_update()?;
+
+        let block_gas_limit: u64 = self.evm.block().gas_limit.saturating_to();
+        let mut gas_used = 0u64;
+        let mut blob_gas_used = 0u64;
+        let mut receipts = Vec::new();
+
+        for (index, tx) in txs.into_iter().enumerate() {
+            // Transaction gas limit can't be more than the gas left in the block.
+            let block_available_gas = block_gas_limit - gas_used;
+            if tx.gas_limit > block_available_gas {
+                return Err(BlockExecutionError::BlockGasLimitExceeded {
+                    index,
+                    tx_gas_limit: tx.gas_limit,
+                    block_available_gas,
+                });
+            }
+
+            // EIP-4844: Blob gas of all transactions is limited by MAX_BLOB_GAS_PER_BLOCK.
+            let tx_blob_gas = tx.get_total_blob_gas();
+            let block_available_blob_gas = MAX_BLOB_GAS_PER_BLOCK - blob_gas_used;
+            if tx_blob_gas > block_available_blob_gas {
+                return Err(BlockExecutionError::BlobGasLimitExceeded {
+                    index,
+                    tx_blob_gas,
+                    block_available_blob_gas,
+                });
+            }
+
+            let tx_type = tx.tx_type();
+            *self.evm.tx_mut() = tx;
+            let result = self
+                .evm
+                .transact_commit()
+                .map_err(|error| BlockExecutionError::Transaction { index, error })?;
+
+            gas_used += result.gas_used();
+            blob_gas_used += tx_blob_gas;
+            receipts.push(Receipt::from_result(tx_type, result, gas_used));
+        }
+
+        // EIP-4895: Beacon chain push withdrawals as operations
+        if spec_id.is_enabled_in(SpecId::SHANGHAI) {
+            self.evm
+                .db_mut()
+                .increment_balances(withdrawals.iter().map(|w| (w.address, w.amount_wei())))
+                .map_err(EVMError::Database)?;
+        }
+
+        let state = self.evm.db_mut();
+        state.merge_transitions(BundleRetention::Reverts);
+        let bundle = state.take_bundle();
+
+        Ok(BlockExecutionOutput {
+            receipts,
+            gas_used,
+            blob_gas_used,
+            bundle,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{address, AccountInfo, TransactTo, U256},
+        StateBuilder,
+    };
+
+    fn executor(spec_id: SpecId, caller: Address) -> BlockExecutor<'static, (), CacheDB<EmptyDB>> {
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            caller,
+            AccountInfo::from_balance(U256::from(1_000_000_000_000u64)),
+        );
+        let state = StateBuilder::new_with_database(db)
+            .with_bundle_update()
+            .build();
+        let evm = Evm::builder().with_db(state).with_spec_id(spec_id).build();
+        BlockExecutor::new(evm)
+    }
+
+    fn transfer(caller: Address, to: Address, nonce: u64) -> TxEnv {
+        TxEnv {
+            caller,
+            gas_limit: 21_000,
+            gas_price: U256::from(1),
+            transact_to: TransactTo::Call(to),
+            value: U256::from(100),
+            nonce: Some(nonce),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn execute_block_with_withdrawals() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let to = address!("1000000000000000000000000000000000000002");
+        let validator = address!("1000000000000000000000000000000000000003");
+        let mut executor = executor(SpecId::SHANGHAI, caller);
+
+        let block = BlockEnv {
+            number: U256::from(1),
+            gas_limit: U256::from(100_000),
+            ..Default::default()
+        };
+        let txs = vec![transfer(caller, to, 0), transfer(caller, to, 1)];
+        let withdrawals = [Withdrawal {
+            address: validator,
+            amount: 1,
+            ..Default::default()
+        }];
+
+        let output = executor.execute_block(block, txs, &withdrawals).unwrap();
+
+        assert_eq!(output.gas_used, 42_000);
+        assert_eq!(output.logs_bloom(), Bloom::ZERO);
+        assert_eq!(
+            output
+                .receipts
+                .iter()
+                .map(|r| (r.success, r.cumulative_gas_used))
+                .collect::<Vec<_>>(),
+            vec![(true, 21_000), (true, 42_000)]
+        );
+        assert_eq!(
+            output
+                .bundle
+                .account(&to)
+                .unwrap()
+                .info
+                .as_ref()
+                .unwrap()
+                .balance,
+            U256::from(200)
+        );
+        assert_eq!(
+            output
+                .bundle
+                .account(&validator)
+                .unwrap()
+                .info
+                .as_ref()
+                .unwrap()
+                .balance,
+            U256::from(1_000_000_000u64)
+        );
+    }
+
+    #[test]
+    fn block_gas_limit_is_enforced() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let to = address!("1000000000000000000000000000000000000002");
+        let mut executor = executor(SpecId::SHANGHAI, caller);
+
+        let block = BlockEnv {
+            number: U256::from(1),
+            gas_limit: U256::from(30_000),
+            ..Default::default()
+        };
+        let txs = vec![transfer(caller, to, 0), transfer(caller, to, 1)];
+
+        assert_eq!(
+            executor.execute_block(block, txs, &[]).unwrap_err(),
+            BlockExecutionError::BlockGasLimitExceeded {
+                index: 1,
+                tx_gas_limit: 21_000,
+                block_available_gas: 9_000,
+            }
+        );
+    }
+}
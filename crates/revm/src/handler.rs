@@ -1,6 +1,8 @@
 // Modules.
 mod handle_types;
+pub mod lazy_balance;
 pub mod mainnet;
+pub mod prefetch;
 pub mod register;
 
 // Exports.
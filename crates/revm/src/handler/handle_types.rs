@@ -17,7 +17,8 @@ pub use execution::{
 };
 
 pub use pre_execution::{
-    DeductCallerHandle, LoadAccountsHandle, LoadPrecompilesHandle, PreExecutionHandler,
+    ApplyEIP7702AuthListHandle, DeductCallerHandle, LoadAccountsHandle, LoadPrecompilesHandle,
+    PreExecutionHandler,
 };
 
 pub use post_execution::{
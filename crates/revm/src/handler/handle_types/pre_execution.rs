@@ -19,6 +19,10 @@ pub type LoadAccountsHandle<'a, EXT, DB> =
 pub type DeductCallerHandle<'a, EXT, DB> =
     Arc<dyn Fn(&mut Context<EXT, DB>) -> EVMResultGeneric<(), <DB as Database>::Error> + 'a>;
 
+/// Apply EIP-7702 authorization list and return gas refund for already existing accounts.
+pub type ApplyEIP7702AuthListHandle<'a, EXT, DB> =
+    Arc<dyn Fn(&mut Context<EXT, DB>) -> EVMResultGeneric<u64, <DB as Database>::Error> + 'a>;
+
 /// Handles related to pre execution before the stack loop is started.
 pub struct PreExecutionHandler<'a, EXT, DB: Database> {
     /// Load precompiles
@@ -27,6 +31,8 @@ pub struct PreExecutionHandler<'a, EXT, DB: Database> {
     pub load_accounts: LoadAccountsHandle<'a, EXT, DB>,
     /// Deduct max value from the caller.
     pub deduct_caller: DeductCallerHandle<'a, EXT, DB>,
+    /// Apply EIP-7702 auth list
+    pub apply_eip7702_auth_list: ApplyEIP7702AuthListHandle<'a, EXT, DB>,
 }
 
 impl<'a, EXT: 'a, DB: Database + 'a> PreExecutionHandler<'a, EXT, DB> {
@@ -36,6 +42,7 @@ impl<'a, EXT: 'a, DB: Database + 'a> PreExecutionHandler<'a, EXT, DB> {
             load_precompiles: Arc::new(mainnet::load_precompiles::<SPEC, DB>),
             load_accounts: Arc::new(mainnet::load_accounts::<SPEC, EXT, DB>),
             deduct_caller: Arc::new(mainnet::deduct_caller::<SPEC, EXT, DB>),
+            apply_eip7702_auth_list: Arc::new(mainnet::apply_eip7702_auth_list::<SPEC, EXT, DB>),
         }
     }
 }
@@ -46,6 +53,14 @@ impl<'a, EXT, DB: Database> PreExecutionHandler<'a, EXT, DB> {
         (self.deduct_caller)(context)
     }
 
+    /// Apply EIP-7702 auth list and return gas refund for already existing accounts.
+    pub fn apply_eip7702_auth_list(
+        &self,
+        context: &mut Context<EXT, DB>,
+    ) -> Result<u64, EVMError<DB::Error>> {
+        (self.apply_eip7702_auth_list)(context)
+    }
+
     /// Main load
     pub fn load_accounts(&self, context: &mut Context<EXT, DB>) -> Result<(), EVMError<DB::Error>> {
         (self.load_accounts)(context)
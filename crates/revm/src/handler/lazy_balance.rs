@@ -0,0 +1,378 @@
+//! Handler mode that records balance credits as lazy increments.
+//!
+//! Fees paid to the beneficiary and the value of pure transfers are recorded in
+//! [`JournaledState::balance_increments`](crate::JournaledState::balance_increments)
+//! instead of being written to the accounts. Increments are commutative, so transactions
+//! of the block that only credit the same account do not read it and do not conflict
+//! when executed in parallel.
+
+use crate::{
+    handler::{mainnet, register::EvmHandler},
+    interpreter::{
+        opcode::InstructionTables, CallInputs, CallOutcome, CallScheme, Gas, InstructionResult,
+        InterpreterResult,
+    },
+    primitives::{
+        db::Database,
+        spec_to_generic, Bytes, EVMError, Spec, SpecId,
+        SpecId::{LONDON, PRAGUE, SHANGHAI},
+        BLOCKHASH_STORAGE_ADDRESS, KECCAK_EMPTY, U256,
+    },
+    Context, FrameOrResult, FrameResult,
+};
+use std::sync::Arc;
+
+/// Registers the handles that record credits to the beneficiary and receivers of pure
+/// transfers as lazy balance increments.
+///
+/// Pure transfers are executed without loading the receiver only if no inspector is
+/// registered before this register, otherwise they take the mainnet call path.
+///
+/// Increments are applied to the state before the output is returned, unless the
+/// transaction is executed with
+/// [`Evm::transact_with_read_write_sets`](crate::Evm::transact_with_read_write_sets),
+/// which returns them in the [`WriteSet`](crate::WriteSet) instead.
+///
+/// The Optimism handler loads the L1 block info with the accounts and pays the L1 fee and
+/// base fee recipients with the beneficiary reward, so with it the beneficiary is rewarded by
+/// the Optimism handles and only the pure transfers are recorded lazily.
+pub fn lazy_balance_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_, EXT, DB>) {
+    if !handler.is_optimism() {
+        spec_to_generic!(handler.cfg.spec_id, {
+            handler.pre_execution.load_accounts = Arc::new(load_accounts::<SPEC, EXT, DB>);
+            handler.post_execution.reward_beneficiary =
+                Arc::new(reward_beneficiary::<SPEC, EXT, DB>);
+        });
+    }
+
+    // Inspector registers box the instruction table. Call handle of the inspector registered
+    // before this one would be skipped by the pure transfer, so calls take the mainnet path.
+    // Inspector registered after this one wraps the handle and is called as for precompiles.
+    if matches!(handler.instruction_table, InstructionTables::Plain(_)) {
+        let prev_handle = handler.execution.call.clone();
+        handler.execution.call = Arc::new(move |ctx, inputs| {
+            if let Some(result) = pure_transfer(ctx, &inputs)? {
+                return Ok(result);
+            }
+            prev_handle(ctx, inputs)
+        });
+    }
+
+    let prev_handle = handler.post_execution.output.clone();
+    handler.post_execution.output = Arc::new(move |ctx, result| {
+        // increments are returned in the write set when reads are recorded.
+        if ctx.evm.journaled_state.read_set.is_none() {
+            ctx.evm
+                .inner
+                .journaled_state
+                .apply_balance_increments(&mut ctx.evm.inner.db)?;
+        }
+        prev_handle(ctx, result)
+    });
+}
+
+/// Loads the accounts as mainnet, but only marks the coinbase warm without loading it.
+#[inline]
+pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+) -> Result<(), EVMError<DB::Error>> {
+    context.evm.journaled_state.set_spec_id(SPEC::SPEC_ID);
+
+    // EIP-3651: Warm COINBASE.
+    if SPEC::enabled(SHANGHAI) {
+        let coinbase = context.evm.inner.env.block.coinbase;
+        context
+            .evm
+            .inner
+            .journaled_state
+            .warm_preloaded_addresses
+            .insert(coinbase);
+    }
+
+    // EIP-2935: Serve historical block hashes from state
+    if SPEC::enabled(PRAGUE) {
+        context.evm.inner.journaled_state.initial_account_load(
+            BLOCKHASH_STORAGE_ADDRESS,
+            &[],
+            &mut context.evm.inner.db,
+        )?;
+    }
+
+    context.evm.load_access_list()?;
+    Ok(())
+}
+
+/// Rewards the beneficiary with the gas fee as a lazy balance increment.
+///
+/// If the beneficiary was already touched by the transaction, the reward is applied
+/// directly as in [`mainnet::reward_beneficiary`].
+#[inline]
+pub fn reward_beneficiary<SPEC: Spec, EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+    gas: &Gas,
+) -> Result<(), EVMError<DB::Error>> {
+    let beneficiary = context.evm.env.block.coinbase;
+    let is_touched = context
+        .evm
+        .journaled_state
+        .state
+        .get(&beneficiary)
+        .is_some_and(|account| account.is_touched());
+    if is_touched {
+        return mainnet::reward_beneficiary::<SPEC, EXT, DB>(context, gas);
+    }
+
+    let effective_gas_price = context.evm.env.effective_gas_price();
+    // EIP-1559 discard basefee for coinbase transfer.
+    let coinbase_gas_price = if SPEC::enabled(LONDON) {
+        effective_gas_price.saturating_sub(context.evm.env.block.basefee)
+    } else {
+        effective_gas_price
+    };
+    let reward = coinbase_gas_price * U256::from(gas.spent() - gas.refunded() as u64);
+    context
+        .evm
+        .journaled_state
+        .increment_balance_lazily(beneficiary, reward);
+    Ok(())
+}
+
+/// Executes the value transfer of the transaction to an account without code.
+///
+/// Value is transferred with [`JournaledState::transfer_lazily`], so the receiver is credited
+/// with a lazy balance increment and only its code hash is read. Returns `None` if the call
+/// is not a pure transfer.
+///
+/// [`JournaledState::transfer_lazily`]: crate::JournaledState::transfer_lazily
+fn pure_transfer<EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+    inputs: &CallInputs,
+) -> Result<Option<FrameOrResult>, EVMError<DB::Error>> {
+    let target = inputs.target_address;
+    if context.evm.journaled_state.depth() != 0
+        || inputs.scheme != CallScheme::Call
+        || !inputs.transfers_value()
+        || target != inputs.bytecode_address
+        || context.evm.journaled_state.state.contains_key(&target)
+        || context
+            .evm
+            .precompiles
+            .addresses()
+            .any(|address| *address == target)
+    {
+        return Ok(None);
+    }
+    let journaled_state = &mut context.evm.inner.journaled_state;
+    if journaled_state.code_hash(target, &mut context.evm.inner.db)? != KECCAK_EMPTY {
+        return Ok(None);
+    }
+
+    let value = inputs.transfer_value().unwrap_or_default();
+    let checkpoint = journaled_state.checkpoint();
+    let result = match journaled_state.transfer_lazily(&inputs.caller, target, value) {
+        Some(result) => {
+            journaled_state.checkpoint_revert(checkpoint);
+            result
+        }
+        None => {
+            journaled_state.checkpoint_commit();
+            InstructionResult::Stop
+        }
+    };
+
+    Ok(Some(FrameOrResult::Result(FrameResult::Call(
+        CallOutcome::new(
+            InterpreterResult {
+                result,
+                gas: Gas::new(inputs.gas_limit),
+                output: Bytes::new(),
+            },
+            inputs.return_memory_offset.clone(),
+        ),
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        inspector_handle_register,
+        primitives::{address, AccountInfo, TransactTo},
+        Evm, EvmContext, Inspector, JournaledState,
+    };
+
+    #[test]
+    fn lazy_balance_matches_mainnet() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let receiver = address!("1000000000000000000000000000000000000002");
+        let coinbase = address!("1000000000000000000000000000000000000003");
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(10_000_000)));
+        db.insert_account_info(coinbase, AccountInfo::from_balance(U256::from(5)));
+
+        let build = |db, lazy: bool| {
+            let builder = Evm::builder()
+                .with_db(db)
+                .modify_block_env(|block| {
+                    block.coinbase = coinbase;
+                    block.basefee = U256::from(1);
+                })
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TransactTo::Call(receiver);
+                    tx.value = U256::from(100);
+                    tx.gas_price = U256::from(3);
+                    tx.gas_limit = 50_000;
+                });
+            if lazy {
+                builder
+                    .append_handler_register(lazy_balance_handle_register)
+                    .build()
+            } else {
+                builder.build()
+            }
+        };
+
+        let expected = build(db.clone(), false).transact().unwrap();
+        let mut evm = build(db, true);
+        let result = evm.transact().unwrap();
+        assert_eq!(result, expected);
+        assert_eq!(result.state[&receiver].info.balance, U256::from(100));
+        assert_eq!(
+            result.state[&coinbase].info.balance,
+            U256::from(5 + 2 * 21_000)
+        );
+
+        // receiver and coinbase are only credited when reads are recorded.
+        let (_, read_set, write_set) = evm.transact_with_read_write_sets().unwrap();
+        assert!(!read_set.accounts.contains_key(&receiver));
+        assert!(!read_set.accounts.contains_key(&coinbase));
+        assert_eq!(read_set.account_code_hashes[&receiver], KECCAK_EMPTY);
+        assert_eq!(
+            write_set.balance_increments,
+            [(receiver, U256::from(100)), (coinbase, U256::from(42_000))]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    #[test]
+    fn lazy_transfer_reverted() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let receiver = address!("1000000000000000000000000000000000000002");
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(100)));
+        let mut journaled_state = JournaledState::new(SpecId::LATEST, Default::default());
+        journaled_state.load_account(caller, &mut db).unwrap();
+        journaled_state.increment_balance_lazily(receiver, U256::from(5));
+
+        let checkpoint = journaled_state.checkpoint();
+        assert_eq!(
+            journaled_state.transfer_lazily(&caller, receiver, U256::from(60)),
+            None
+        );
+        assert_eq!(
+            journaled_state.transfer_lazily(&caller, receiver, U256::from(60)),
+            Some(InstructionResult::OutOfFunds)
+        );
+        assert_eq!(journaled_state.account(caller).info.balance, U256::from(40));
+        assert_eq!(
+            journaled_state.balance_increments[&receiver],
+            U256::from(65)
+        );
+
+        journaled_state.checkpoint_revert(checkpoint);
+        assert_eq!(
+            journaled_state.account(caller).info.balance,
+            U256::from(100)
+        );
+        assert_eq!(journaled_state.balance_increments[&receiver], U256::from(5));
+        assert!(!journaled_state.account(caller).is_touched());
+        assert!(!journaled_state.state.contains_key(&receiver));
+    }
+
+    #[test]
+    fn inspector_sees_pure_transfer() {
+        #[derive(Default)]
+        struct CallCounter {
+            calls: usize,
+            call_ends: usize,
+        }
+
+        impl<DB: Database> Inspector<DB> for CallCounter {
+            fn call(
+                &mut self,
+                _context: &mut EvmContext<DB>,
+                _inputs: &mut CallInputs,
+            ) -> Option<CallOutcome> {
+                self.calls += 1;
+                None
+            }
+
+            fn call_end(
+                &mut self,
+                _context: &mut EvmContext<DB>,
+                _inputs: &CallInputs,
+                outcome: CallOutcome,
+            ) -> CallOutcome {
+                self.call_ends += 1;
+                outcome
+            }
+        }
+
+        let caller = address!("1000000000000000000000000000000000000001");
+        let receiver = address!("1000000000000000000000000000000000000002");
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(10_000_000)));
+
+        for inspector_first in [true, false] {
+            let builder = Evm::builder()
+                .with_db(db.clone())
+                .with_external_context(CallCounter::default())
+                .modify_tx_env(|tx| {
+                    tx.caller = caller;
+                    tx.transact_to = TransactTo::Call(receiver);
+                    tx.value = U256::from(100);
+                    tx.gas_limit = 50_000;
+                });
+            let mut evm = if inspector_first {
+                builder
+                    .append_handler_register(inspector_handle_register)
+                    .append_handler_register(lazy_balance_handle_register)
+                    .build()
+            } else {
+                builder
+                    .append_handler_register(lazy_balance_handle_register)
+                    .append_handler_register(inspector_handle_register)
+                    .build()
+            };
+            let result = evm.transact().unwrap();
+            assert!(result.result.is_success());
+            assert_eq!(result.state[&receiver].info.balance, U256::from(100));
+            assert_eq!(evm.context.external.calls, 1);
+            assert_eq!(evm.context.external.call_ends, 1);
+        }
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn optimism_keeps_beneficiary_handles() {
+        use crate::handler::register::EvmHandler;
+
+        let mut handler = EvmHandler::<'_, (), EmptyDB>::optimism_with_spec(SpecId::LATEST);
+        let load_accounts = handler.pre_execution.load_accounts.clone();
+        let reward_beneficiary = handler.post_execution.reward_beneficiary.clone();
+        lazy_balance_handle_register(&mut handler);
+        assert!(Arc::ptr_eq(
+            &handler.pre_execution.load_accounts,
+            &load_accounts
+        ));
+        assert!(Arc::ptr_eq(
+            &handler.post_execution.reward_beneficiary,
+            &reward_beneficiary
+        ));
+    }
+}
@@ -11,5 +11,8 @@ pub use execution::{
     insert_eofcreate_outcome, last_frame_return,
 };
 pub use post_execution::{clear, end, output, reimburse_caller, reward_beneficiary};
-pub use pre_execution::{deduct_caller, deduct_caller_inner, load_accounts, load_precompiles};
+pub use pre_execution::{
+    apply_blockhashes_update, apply_eip7702_auth_list, deduct_caller, deduct_caller_inner,
+    load_accounts, load_precompiles, recover_authority,
+};
 pub use validation::{validate_env, validate_initial_tx_gas, validate_tx_against_state};
@@ -26,8 +26,8 @@ pub fn execute_frame<SPEC: Spec, EXT, DB: Database>(
     let interpreter = frame.interpreter_mut();
     let memory = mem::replace(shared_memory, EMPTY_SHARED_MEMORY);
     let next_action = match instruction_tables {
-        InstructionTables::Plain(table) => interpreter.run(memory, table, context),
-        InstructionTables::Boxed(table) => interpreter.run(memory, table, context),
+        InstructionTables::Plain(table) => interpreter.run_decoded(memory, table, context),
+        InstructionTables::Boxed(table) => interpreter.run_decoded_by_table(memory, table, context),
     };
     // Take the shared memory back.
     *shared_memory = interpreter.take_memory();
@@ -3,12 +3,14 @@
 //! They handle initial setup of the EVM, call loop and the final return of the EVM
 
 use crate::{
-    precompile::{PrecompileSpecId, Precompiles},
+    precompile::{secp256k1::ecrecover, PrecompileSpecId, Precompiles},
     primitives::{
+        alloy_primitives::B512,
         db::Database,
-        Account, EVMError, Env, Spec,
+        eip7702::{PER_AUTH_BASE_COST, PER_EMPTY_ACCOUNT_COST},
+        Account, Address, Bytecode, EVMError, Env, SignedAuthorization, Spec,
         SpecId::{CANCUN, PRAGUE, SHANGHAI},
-        TransactTo, BLOCKHASH_STORAGE_ADDRESS, U256,
+        TransactTo, BLOCKHASH_SERVE_WINDOW, BLOCKHASH_STORAGE_ADDRESS, U256,
     },
     Context, ContextPrecompiles,
 };
@@ -53,6 +55,50 @@ pub fn load_accounts<SPEC: Spec, EXT, DB: Database>(
     Ok(())
 }
 
+/// EIP-2935: Serve historical block hashes from state
+///
+/// Block-start hook that stores the parent block hash in the history storage contract
+/// at the slot `(block.number - 1) % BLOCKHASH_SERVE_WINDOW`. Parent hash is fetched from
+/// the database. Nothing is written if there is no code at the history storage address.
+///
+/// Changes are made in the journal and need to be finalized by the caller.
+pub fn apply_blockhashes_update<SPEC: Spec, EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+) -> Result<(), EVMError<DB::Error>> {
+    if !SPEC::enabled(PRAGUE) {
+        return Ok(());
+    }
+
+    // Genesis block has no parent.
+    let block_number = context.evm.inner.env.block.number;
+    if block_number == U256::ZERO {
+        return Ok(());
+    }
+
+    let parent_number = block_number - U256::from(1);
+    let parent_hash = context.evm.inner.block_hash(parent_number)?;
+
+    // Update is skipped if the history storage contract is not deployed.
+    let inner = &mut context.evm.inner;
+    let (account, _) = inner
+        .journaled_state
+        .load_code(BLOCKHASH_STORAGE_ADDRESS, &mut inner.db)?;
+    if account.info.is_empty_code_hash() {
+        return Ok(());
+    }
+
+    let slot = parent_number % U256::from(BLOCKHASH_SERVE_WINDOW);
+    inner.journaled_state.sstore(
+        BLOCKHASH_STORAGE_ADDRESS,
+        slot,
+        parent_hash.into(),
+        &mut inner.db,
+    )?;
+    inner.journaled_state.touch(&BLOCKHASH_STORAGE_ADDRESS);
+
+    Ok(())
+}
+
 /// Helper function that deducts the caller balance.
 #[inline]
 pub fn deduct_caller_inner<SPEC: Spec>(caller_account: &mut Account, env: &Env) {
@@ -96,3 +142,98 @@ pub fn deduct_caller<SPEC: Spec, EXT, DB: Database>(
 
     Ok(())
 }
+
+/// Recovers the authority of the signed EIP-7702 authorization.
+#[inline]
+pub fn recover_authority(authorization: &SignedAuthorization) -> Option<Address> {
+    let sig = B512::from(authorization.signature_bytes());
+    ecrecover(
+        &sig,
+        authorization.y_parity,
+        &authorization.signature_hash(),
+    )
+    .ok()
+    .map(Address::from_word)
+}
+
+/// Apply EIP-7702 auth list and return gas refund on already existing accounts.
+///
+/// Invalid authorizations are skipped and do not fail the transaction.
+#[inline]
+pub fn apply_eip7702_auth_list<SPEC: Spec, EXT, DB: Database>(
+    context: &mut Context<EXT, DB>,
+) -> Result<u64, EVMError<DB::Error>> {
+    // EIP-7702 is enabled after PRAGUE.
+    if !SPEC::enabled(PRAGUE) {
+        return Ok(0);
+    }
+
+    // return if there is no auth list.
+    let Some(authorization_list) = context.evm.inner.env.tx.authorization_list.as_ref() else {
+        return Ok(0);
+    };
+
+    let chain_id = U256::from(context.evm.inner.env.cfg.chain_id);
+    let mut refunded_accounts = 0;
+    for authorization in authorization_list.recovered_authorizations(recover_authority) {
+        // 1. recover authority and authorized addresses.
+        // authority = ecrecover(keccak(MAGIC || rlp([chain_id, address, nonce])), y_parity, r, s]
+        let Some(authority) = authorization.authority() else {
+            continue;
+        };
+
+        // 2. Verify the chain id is either 0 or the chain's current ID.
+        if !authorization.chain_id.is_zero() && authorization.chain_id != chain_id {
+            continue;
+        }
+
+        // 3. Verify the nonce is less than 2**64 - 1.
+        if authorization.nonce == u64::MAX {
+            continue;
+        }
+
+        // 4. Add authority to accessed_addresses (as defined in EIP-2929.)
+        let (authority_acc, _) = context
+            .evm
+            .inner
+            .journaled_state
+            .load_code(authority, &mut context.evm.inner.db)?;
+
+        // 5. Verify the code of authority is either empty or already delegated.
+        if let Some(bytecode) = &authority_acc.info.code {
+            // if it is not empty and it is not eip7702
+            if !bytecode.is_empty() && !bytecode.is_eip7702() {
+                continue;
+            }
+        }
+
+        // 6. Verify the nonce of authority is equal to nonce.
+        if authorization.nonce != authority_acc.info.nonce {
+            continue;
+        }
+
+        // 7. Add PER_EMPTY_ACCOUNT_COST - PER_AUTH_BASE_COST gas to the global refund counter if authority exists in the trie.
+        if !authority_acc.is_loaded_as_not_existing() {
+            refunded_accounts += 1;
+        }
+
+        // 8. Set the code of authority to be 0xef0100 || address. This is a delegation designation.
+        //  * As a special case, if address is 0x0000000000000000000000000000000000000000 do not write the designation.
+        //    Clear the accounts code and reset the account's code hash to the empty hash 0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470.
+        let bytecode = if authorization.address.is_zero() {
+            Bytecode::new()
+        } else {
+            Bytecode::new_eip7702(authorization.address)
+        };
+        authority_acc.info.code_hash = bytecode.hash_slow();
+        authority_acc.info.code = Some(bytecode);
+
+        // 9. Increase the nonce of authority by one.
+        authority_acc.info.nonce = authority_acc.info.nonce.saturating_add(1);
+        authority_acc.mark_touch();
+    }
+
+    let refunded_gas = refunded_accounts * (PER_EMPTY_ACCOUNT_COST - PER_AUTH_BASE_COST);
+
+    Ok(refunded_gas)
+}
@@ -17,13 +17,13 @@ pub fn validate_env<SPEC: Spec, DB: Database>(env: &Env) -> Result<(), EVMError<
 pub fn validate_tx_against_state<SPEC: Spec, EXT, DB: Database>(
     context: &mut Context<EXT, DB>,
 ) -> Result<(), EVMError<DB::Error>> {
-    // load acc
+    // load acc, code is needed to recognise EIP-7702 delegated callers.
     let tx_caller = context.evm.env.tx.caller;
     let (caller_account, _) = context
         .evm
         .inner
         .journaled_state
-        .load_account(tx_caller, &mut context.evm.inner.db)?;
+        .load_code(tx_caller, &mut context.evm.inner.db)?;
 
     context
         .evm
@@ -42,9 +42,20 @@ pub fn validate_initial_tx_gas<SPEC: Spec, DB: Database>(
     let input = &env.tx.data;
     let is_create = env.tx.transact_to.is_create();
     let access_list = &env.tx.access_list;
+    let authorization_list_num = env
+        .tx
+        .authorization_list
+        .as_ref()
+        .map(|l| l.len() as u64)
+        .unwrap_or_default();
 
-    let initial_gas_spend =
-        gas::validate_initial_tx_gas(SPEC::SPEC_ID, input, is_create, access_list);
+    let initial_gas_spend = gas::validate_initial_tx_gas_with_auth_list(
+        SPEC::SPEC_ID,
+        input,
+        is_create,
+        access_list,
+        authorization_list_num,
+    );
 
     // Additional check to see if limit is big enough to cover initial gas.
     if initial_gas_spend > env.tx.gas_limit {
@@ -0,0 +1,178 @@
+//! Handler mode that prefetches the state the transaction is known to access.
+
+use crate::{handler::register::EvmHandler, primitives::db::Database};
+use std::sync::Arc;
+
+/// Registers the handle that prefetches the caller, call target, coinbase and the access
+/// list before the transaction is validated.
+///
+/// Accounts and storage slots are read with one [`Database::basic_many`] and one
+/// [`Database::storage_many`] call, see [`JournaledState::prefetch`](crate::JournaledState::prefetch).
+/// Databases that fetch the batches in one round trip, as
+/// [`CacheDB`](crate::db::CacheDB) over a remote database, avoid a round trip per item.
+pub fn prefetch_handle_register<DB: Database, EXT>(handler: &mut EvmHandler<'_, EXT, DB>) {
+    let prev_handle = handler.validation.tx_against_state.clone();
+    handler.validation.tx_against_state = Arc::new(move |ctx| {
+        let inner = &mut ctx.evm.inner;
+        inner.journaled_state.prefetch(&inner.env, &mut inner.db)?;
+        prev_handle(ctx)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, DatabaseRef, EmptyDB, OverlayDB, State, StateOverrideDB, WrapDatabaseRef},
+        primitives::{
+            address, hex, AccountInfo, Address, Bytecode, Bytes, ExecutionResult, HashMap,
+            TransactTo, B256, U256,
+        },
+        Evm,
+    };
+    use core::{cell::RefCell, convert::Infallible};
+    use std::vec::Vec;
+
+    const CALLER: Address = address!("1000000000000000000000000000000000000001");
+    const CONTRACT: Address = address!("1000000000000000000000000000000000000002");
+    const OTHER: Address = address!("1000000000000000000000000000000000000003");
+    const COINBASE: Address = address!("1000000000000000000000000000000000000004");
+
+    /// Database that records the single reads and the batches.
+    #[derive(Default)]
+    struct CountingDB {
+        db: CacheDB<EmptyDB>,
+        single_reads: RefCell<Vec<Address>>,
+        account_batches: RefCell<Vec<Vec<Address>>>,
+        storage_batches: RefCell<Vec<Vec<(Address, U256)>>>,
+    }
+
+    impl CountingDB {
+        fn new() -> Self {
+            let mut db = Self::default();
+            db.db
+                .insert_account_info(CALLER, AccountInfo::from_balance(U256::from(10_000_000)));
+            // SLOAD(1), BALANCE(OTHER), SLOAD(2)
+            let code = Bytecode::new_raw(Bytes::from_static(&hex!(
+                "600154507310000000000000000000000000000000000000033150600254500000"
+            )));
+            db.db.insert_account_info(
+                CONTRACT,
+                AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+            );
+            db.db
+                .insert_account_storage(CONTRACT, U256::from(1), U256::from(5))
+                .unwrap();
+            db
+        }
+
+        fn clear(&self) {
+            self.single_reads.borrow_mut().clear();
+            self.account_batches.borrow_mut().clear();
+            self.storage_batches.borrow_mut().clear();
+        }
+    }
+
+    impl DatabaseRef for CountingDB {
+        type Error = Infallible;
+
+        fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            self.single_reads.borrow_mut().push(address);
+            self.db.basic_ref(address)
+        }
+
+        fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.db.code_by_hash_ref(code_hash)
+        }
+
+        fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+            self.single_reads.borrow_mut().push(address);
+            self.db.storage_ref(address, index)
+        }
+
+        fn block_hash_ref(&self, number: U256) -> Result<B256, Self::Error> {
+            self.db.block_hash_ref(number)
+        }
+
+        fn basic_many_ref(
+            &self,
+            addresses: &[Address],
+        ) -> Result<Vec<Option<AccountInfo>>, Self::Error> {
+            self.account_batches.borrow_mut().push(addresses.to_vec());
+            self.db.basic_many_ref(addresses)
+        }
+
+        fn storage_many_ref(&self, slots: &[(Address, U256)]) -> Result<Vec<U256>, Self::Error> {
+            self.storage_batches.borrow_mut().push(slots.to_vec());
+            self.db.storage_many_ref(slots)
+        }
+    }
+
+    fn transact<DB: Database<Error = Infallible>>(db: DB, prefetch: bool) -> ExecutionResult {
+        let builder = Evm::builder()
+            .with_db(db)
+            .modify_block_env(|block| block.coinbase = COINBASE)
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = TransactTo::Call(CONTRACT);
+                tx.access_list = vec![(CONTRACT, vec![U256::from(1)]), (OTHER, vec![])];
+            });
+        let mut evm = if prefetch {
+            builder
+                .append_handler_register(prefetch_handle_register)
+                .build()
+        } else {
+            builder.build()
+        };
+        evm.transact().unwrap().result
+    }
+
+    /// Asserts that the transaction state was read with one batch of accounts and one
+    /// batch of storage, and that only the slot outside of the access list is read on its own.
+    fn assert_batched(db: &CountingDB) {
+        assert_eq!(
+            *db.account_batches.borrow(),
+            vec![vec![CALLER, CONTRACT, COINBASE, OTHER]]
+        );
+        assert_eq!(
+            *db.storage_batches.borrow(),
+            vec![vec![(CONTRACT, U256::from(1))]]
+        );
+        let single_reads = db.single_reads.borrow();
+        assert!(![CALLER, OTHER, COINBASE]
+            .iter()
+            .any(|address| single_reads.contains(address)));
+        assert_eq!(single_reads.iter().filter(|a| **a == CONTRACT).count(), 1);
+    }
+
+    #[test]
+    fn prefetch_matches_mainnet() {
+        let db = CountingDB::new();
+        let expected = transact(CacheDB::new(&db), false);
+        db.clear();
+        let result = transact(CacheDB::new(&db), true);
+        assert!(result.is_success());
+        assert_eq!(result, expected);
+        assert_batched(&db);
+    }
+
+    #[test]
+    fn prefetch_batches_through_wrappers() {
+        let db = CountingDB::new();
+        let expected = transact(WrapDatabaseRef(&db), false);
+
+        db.clear();
+        let state = State::builder().with_database_ref(&db).build();
+        assert_eq!(transact(state, true), expected);
+        assert_batched(&db);
+
+        db.clear();
+        let overrides = StateOverrideDB::new(&db, HashMap::new()).unwrap();
+        assert_eq!(transact(overrides, true), expected);
+        assert_batched(&db);
+
+        db.clear();
+        assert_eq!(transact(OverlayDB::new(&db), true), expected);
+        assert_batched(&db);
+    }
+}
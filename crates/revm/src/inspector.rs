@@ -5,6 +5,8 @@ use crate::{
 };
 use auto_impl::auto_impl;
 
+mod access_list;
+mod call_tracer;
 #[cfg(feature = "std")]
 mod customprinter;
 #[cfg(all(feature = "std", feature = "serde-json"))]
@@ -12,6 +14,7 @@ mod eip3155;
 mod gas;
 mod handler_register;
 mod noop;
+mod prestate;
 
 // Exports.
 
@@ -20,12 +23,22 @@ use revm_interpreter::{CallOutcome, CreateOutcome};
 
 /// [Inspector] implementations.
 pub mod inspectors {
+    pub use super::access_list::{create_access_list, AccessListInspector, AccessListResult};
+    pub use super::call_tracer::{
+        decode_revert_reason, Action, CallAction, CallKind, CallLog, CallOutput, CallTrace,
+        CallTraceNode, CallTracer, CallTracerConfig, CallType, CreateAction, CreateOutput,
+        CreationMethod, GethCallFrame, GethCallLogFrame, SelfdestructAction, TraceOutput,
+        TraceResults, TransactionTrace,
+    };
     #[cfg(feature = "std")]
     pub use super::customprinter::CustomPrintTracer;
     #[cfg(all(feature = "std", feature = "serde-json"))]
     pub use super::eip3155::TracerEip3155;
     pub use super::gas::GasInspector;
     pub use super::noop::NoOpInspector;
+    pub use super::prestate::{
+        AccountState, DiffMode, PreStateFrame, PreStateMode, PrestateConfig, PrestateTracer,
+    };
 }
 
 /// EVM [Interpreter] callbacks.
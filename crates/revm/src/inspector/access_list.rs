@@ -0,0 +1,355 @@
+use crate::{
+    interpreter::{
+        gas::{
+            ACCESS_LIST_ADDRESS, ACCESS_LIST_STORAGE_KEY, COLD_ACCOUNT_ACCESS_COST,
+            COLD_SLOAD_COST, WARM_STORAGE_READ_COST,
+        },
+        opcode, CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter,
+    },
+    primitives::{db::Database, Address, EVMError, SpecId, B256, U256},
+    Evm, EvmContext, Inspector,
+};
+use core::mem;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+/// Maximum number of the executions done by [`create_access_list`] while waiting for
+/// the access list to stop changing.
+const MAX_ACCESS_LIST_ITERATIONS: usize = 8;
+
+/// Inspector that collects the [EIP-2930] access list of the transaction.
+///
+/// It watches storage and account accessing opcodes and records the touched accounts
+/// and slots. Sender, recipient, precompiles and the created contract are warm by default
+/// and are never included in the access list, same as geth does. Other entries are
+/// included only if they save more gas than they cost, so the coinbase, that is warm after
+/// Shanghai, is included only together with enough of its slots.
+///
+/// [EIP-2930]: https://eips.ethereum.org/EIPS/eip-2930
+#[derive(Clone, Debug, Default)]
+pub struct AccessListInspector {
+    /// Accessed accounts and their slots.
+    access_list: BTreeMap<Address, BTreeSet<B256>>,
+    /// Accounts that are never included.
+    excluded: BTreeSet<Address>,
+    /// Accounts that are warm by default, but can be included with their slots.
+    warm: BTreeSet<Address>,
+}
+
+impl AccessListInspector {
+    /// Creates new access list inspector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clears collected access list.
+    pub fn clear(&mut self) {
+        self.access_list.clear();
+        self.excluded.clear();
+        self.warm.clear();
+    }
+
+    /// Returns the collected access list in the [`TxEnv::access_list`] format.
+    ///
+    /// [`TxEnv::access_list`]: crate::primitives::TxEnv::access_list
+    pub fn access_list(&self) -> Vec<(Address, Vec<U256>)> {
+        self.access_list
+            .iter()
+            .filter(|(address, slots)| {
+                !self.excluded.contains(*address)
+                    && pays_for_itself(self.warm.contains(*address), slots.len() as u64)
+            })
+            .map(|(address, slots)| {
+                (
+                    *address,
+                    slots
+                        .iter()
+                        .map(|slot| U256::from_be_bytes(slot.0))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// Consumes the inspector and returns the collected access list.
+    pub fn into_access_list(self) -> Vec<(Address, Vec<U256>)> {
+        self.access_list()
+    }
+
+    fn add_address(&mut self, address: Address) {
+        self.access_list.entry(address).or_default();
+    }
+
+    fn add_slot(&mut self, address: Address, slot: U256) {
+        self.access_list
+            .entry(address)
+            .or_default()
+            .insert(B256::from(slot));
+    }
+
+    /// Records accounts that are excluded or warm by default, on the start of the transaction.
+    fn record_excluded<DB: Database>(&mut self, context: &EvmContext<DB>, target: Option<Address>) {
+        if context.journaled_state.depth() != 0 {
+            return;
+        }
+        self.excluded.insert(context.env.tx.caller);
+        self.excluded.extend(target);
+        self.excluded
+            .extend(context.precompiles.addresses().copied());
+        if context.spec_id().is_enabled_in(SpecId::SHANGHAI) {
+            self.warm.insert(context.env.block.coinbase);
+        }
+    }
+}
+
+/// Returns true if the access list entry of the account with `slots` slots saves more gas
+/// than its inclusion costs. Every slot is accessed at least once, so it saves the cold
+/// access cost, while the warm account saves only on its slots.
+fn pays_for_itself(warm: bool, slots: u64) -> bool {
+    let cost = ACCESS_LIST_ADDRESS + slots * ACCESS_LIST_STORAGE_KEY;
+    let mut saved = slots * (COLD_SLOAD_COST - WARM_STORAGE_READ_COST);
+    if !warm {
+        saved += COLD_ACCOUNT_ACCESS_COST - WARM_STORAGE_READ_COST;
+    }
+    saved > cost
+}
+
+/// Converts the stack word to the address.
+fn to_address(word: U256) -> Address {
+    Address::from_word(B256::from(word))
+}
+
+impl<DB: Database> Inspector<DB> for AccessListInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        let stack = &interp.stack;
+        match interp.current_opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                if let Ok(slot) = stack.peek(0) {
+                    self.add_slot(interp.contract.target_address, slot);
+                }
+            }
+            opcode::EXTCODECOPY
+            | opcode::EXTCODEHASH
+            | opcode::EXTCODESIZE
+            | opcode::BALANCE
+            | opcode::SELFDESTRUCT
+            | opcode::EXTCALL
+            | opcode::EXTDELEGATECALL
+            | opcode::EXTSTATICCALL => {
+                if let Ok(address) = stack.peek(0) {
+                    self.add_address(to_address(address));
+                }
+            }
+            opcode::CALL | opcode::CALLCODE | opcode::DELEGATECALL | opcode::STATICCALL => {
+                if let Ok(address) = stack.peek(1) {
+                    self.add_address(to_address(address));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.record_excluded(context, Some(inputs.target_address));
+        None
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.record_excluded(context, None);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        // created contract of the transaction is warm.
+        if context.journaled_state.depth() == 0 {
+            self.excluded.extend(outcome.address);
+        }
+        outcome
+    }
+}
+
+/// Access list generated by [`create_access_list`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessListResult {
+    /// Generated access list.
+    pub access_list: Vec<(Address, Vec<U256>)>,
+    /// Gas used by the transaction with the generated access list.
+    pub gas_used: u64,
+    /// Gas used by the transaction with the original access list.
+    pub gas_used_without_access_list: u64,
+}
+
+impl AccessListResult {
+    /// Returns the gas saved by using the generated access list, negative if it costs more.
+    pub fn gas_saved(&self) -> i64 {
+        self.gas_used_without_access_list as i64 - self.gas_used as i64
+    }
+}
+
+/// Generates the access list for the transaction in the [`Evm`], same as
+/// `eth_createAccessList`.
+///
+/// Transaction is executed until the access list stops changing, as execution with
+/// the access list can take a different path. State is never committed and the
+/// original access list of the transaction is restored.
+///
+/// [`inspector_handle_register`](crate::inspector_handle_register) needs to be
+/// registered for the inspector to be called.
+pub fn create_access_list<DB: Database>(
+    evm: &mut Evm<'_, AccessListInspector, DB>,
+) -> Result<AccessListResult, EVMError<DB::Error>> {
+    let original = evm.tx().access_list.clone();
+    let result = generate_access_list(evm);
+    evm.tx_mut().access_list = original;
+    result
+}
+
+fn generate_access_list<DB: Database>(
+    evm: &mut Evm<'_, AccessListInspector, DB>,
+) -> Result<AccessListResult, EVMError<DB::Error>> {
+    let gas_used_without_access_list = run(evm)?;
+
+    let mut access_list = evm.context.external.access_list();
+    let mut gas_used = gas_used_without_access_list;
+    for _ in 0..MAX_ACCESS_LIST_ITERATIONS {
+        evm.tx_mut().access_list = access_list.clone();
+        gas_used = run(evm)?;
+        let new_access_list = evm.context.external.access_list();
+        if mem::replace(&mut access_list, new_access_list) == access_list {
+            break;
+        }
+    }
+
+    Ok(AccessListResult {
+        access_list,
+        gas_used,
+        gas_used_without_access_list,
+    })
+}
+
+/// Executes the transaction with cleared inspector and returns gas used.
+fn run<DB: Database>(
+    evm: &mut Evm<'_, AccessListInspector, DB>,
+) -> Result<u64, EVMError<DB::Error>> {
+    evm.context.external.clear();
+    Ok(evm.transact()?.result.gas_used())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        inspector_handle_register,
+        primitives::{address, hex, AccountInfo, Bytecode, TransactTo},
+    };
+
+    #[test]
+    fn generate_access_list() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let contract = address!("1000000000000000000000000000000000000002");
+        let other = address!("1000000000000000000000000000000000000003");
+
+        // SLOAD slot 1, BALANCE of `other`, BALANCE of caller, BALANCE of
+        // ecrecover precompile and stop.
+        let mut code = hex!("60015450").to_vec();
+        code.push(0x73);
+        code.extend_from_slice(other.as_slice());
+        code.extend_from_slice(&hex!("3150"));
+        code.push(0x73);
+        code.extend_from_slice(caller.as_slice());
+        code.extend_from_slice(&hex!("315060013150"));
+        code.push(0x00);
+        let code = Bytecode::new_raw(code.into());
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(AccessListInspector::new())
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+
+        let result = create_access_list(&mut evm).unwrap();
+        // recipient, caller and precompile are warm.
+        assert_eq!(result.access_list, vec![(other, vec![])]);
+        // cold `other` access costs 2500 more than the warm one, the entry costs 2400.
+        assert_eq!(result.gas_saved(), 100);
+        assert!(evm.tx().access_list.is_empty());
+    }
+
+    #[test]
+    fn prune_unprofitable_entries() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let contract = address!("1000000000000000000000000000000000000002");
+        let other = address!("1000000000000000000000000000000000000003");
+        let coinbase = address!("1000000000000000000000000000000000000004");
+
+        // SLOAD slot 3, CALL `other` and coinbase with all gas and stop.
+        let mut code = hex!("60035450").to_vec();
+        for address in [other, coinbase] {
+            code.extend_from_slice(&hex!("5f5f5f5f5f73"));
+            code.extend_from_slice(address.as_slice());
+            code.extend_from_slice(&hex!("5af150"));
+        }
+        code.push(0x00);
+        // SLOAD slot 1 and stop.
+        let sload = Bytecode::new_raw(hex!("6001545000").into());
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        for (address, code) in [
+            (contract, Bytecode::new_raw(code.into())),
+            (other, sload.clone()),
+            (coinbase, sload),
+        ] {
+            db.insert_account_info(
+                address,
+                AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+            );
+        }
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(AccessListInspector::new())
+            .modify_block_env(|block| block.coinbase = coinbase)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+
+        let result = create_access_list(&mut evm).unwrap();
+        // slot of the recipient and the single slot of the warm coinbase are dropped.
+        assert_eq!(result.access_list, vec![(other, vec![U256::from(1)])]);
+        // `other` account and its slot save 100 gas each.
+        assert_eq!(result.gas_saved(), 200);
+    }
+}
@@ -0,0 +1,1009 @@
+use crate::{
+    interpreter::{
+        CallInputs, CallOutcome, CallScheme, CallValue, CreateInputs, CreateOutcome, CreateScheme,
+        EOFCreateInputs, EOFCreateOutcome, InstructionResult, InterpreterResult,
+    },
+    primitives::{db::Database, Address, Bytes, Log, B256, U256},
+    EvmContext, Inspector,
+};
+use core::fmt;
+use std::{borrow::ToOwned, string::String, vec::Vec};
+
+/// Selector of the `Error(string)` revert.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Kind of the traced frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CallKind {
+    /// `CALL` or `EXTCALL`.
+    #[default]
+    Call,
+    /// `CALLCODE`.
+    CallCode,
+    /// `DELEGATECALL` or `EXTDELEGATECALL`.
+    DelegateCall,
+    /// `STATICCALL` or `EXTSTATICCALL`.
+    StaticCall,
+    /// `CREATE`.
+    Create,
+    /// `CREATE2`.
+    Create2,
+    /// `EOFCREATE` or EOF contract creation transaction.
+    EOFCreate,
+    /// `SELFDESTRUCT`.
+    SelfDestruct,
+}
+
+impl CallKind {
+    /// Returns true if frame creates a contract.
+    pub fn is_create(&self) -> bool {
+        matches!(self, Self::Create | Self::Create2 | Self::EOFCreate)
+    }
+
+    /// Returns the frame type as reported by geth `callTracer`.
+    pub fn geth_type(&self) -> &'static str {
+        match self {
+            Self::Call => "CALL",
+            Self::CallCode => "CALLCODE",
+            Self::DelegateCall => "DELEGATECALL",
+            Self::StaticCall => "STATICCALL",
+            Self::Create => "CREATE",
+            Self::Create2 => "CREATE2",
+            Self::EOFCreate => "EOFCREATE",
+            Self::SelfDestruct => "SELFDESTRUCT",
+        }
+    }
+}
+
+impl From<CallScheme> for CallKind {
+    fn from(scheme: CallScheme) -> Self {
+        match scheme {
+            CallScheme::Call => Self::Call,
+            CallScheme::CallCode => Self::CallCode,
+            CallScheme::DelegateCall => Self::DelegateCall,
+            CallScheme::StaticCall => Self::StaticCall,
+        }
+    }
+}
+
+impl fmt::Display for CallKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.geth_type())
+    }
+}
+
+/// Log emitted inside of the traced frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallLog {
+    /// Emitted log.
+    pub log: Log,
+    /// Number of child frames of the frame at the moment log was emitted.
+    pub position: usize,
+}
+
+/// Traced frame.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallTrace {
+    /// Kind of the frame.
+    pub kind: CallKind,
+    /// Depth of the frame, zero for the transaction frame.
+    pub depth: usize,
+    /// Caller of the frame.
+    pub from: Address,
+    /// Address of the executed code or the created contract.
+    ///
+    /// `None` if contract creation failed before address was known.
+    pub to: Option<Address>,
+    /// Value of the call. Apparent value for `DELEGATECALL`.
+    pub value: U256,
+    /// Gas limit of the frame.
+    pub gas_limit: u64,
+    /// Gas spent by the frame.
+    pub gas_used: u64,
+    /// Call input or init code.
+    pub input: Bytes,
+    /// Output of the call or code of the created contract.
+    pub output: Bytes,
+    /// Result of the frame execution.
+    pub status: InstructionResult,
+    /// Logs emitted by the frame.
+    pub logs: Vec<CallLog>,
+}
+
+impl CallTrace {
+    /// Returns true if frame was successful.
+    pub fn is_success(&self) -> bool {
+        self.status.is_ok()
+    }
+
+    /// Returns true if frame was reverted by `REVERT`.
+    pub fn is_revert(&self) -> bool {
+        self.status.is_revert()
+    }
+
+    /// Returns the decoded `Error(string)` revert reason.
+    pub fn revert_reason(&self) -> Option<String> {
+        if self.is_revert() {
+            decode_revert_reason(&self.output)
+        } else {
+            None
+        }
+    }
+}
+
+/// Node of the call tree.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallTraceNode {
+    /// Index of the parent node.
+    pub parent: Option<usize>,
+    /// Indices of the child nodes, in execution order.
+    pub children: Vec<usize>,
+    /// Traced frame.
+    pub trace: CallTrace,
+}
+
+/// Configuration of the [`CallTracer`], same as geth `callTracer` config.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct CallTracerConfig {
+    /// Trace only the transaction frame.
+    pub only_top_call: bool,
+    /// Include logs in the geth call frames.
+    pub with_log: bool,
+}
+
+/// Inspector that records the tree of the call frames of the transaction.
+///
+/// Tree can be exported as geth `callTracer` frame with [`CallTracer::geth_call_frame`]
+/// or as Parity flat traces with [`CallTracer::parity_traces`].
+///
+/// Tracer should be used for a single transaction.
+#[derive(Clone, Debug, Default)]
+pub struct CallTracer {
+    config: CallTracerConfig,
+    /// All traced frames, first node is the transaction frame.
+    nodes: Vec<CallTraceNode>,
+    /// Indices of the frames that are currently executing.
+    stack: Vec<usize>,
+    /// Gas limit of the transaction.
+    tx_gas_limit: u64,
+}
+
+impl CallTracer {
+    /// Creates new call tracer.
+    pub fn new(config: CallTracerConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the tracer config.
+    pub fn config(&self) -> &CallTracerConfig {
+        &self.config
+    }
+
+    /// Returns all traced nodes. First node is the transaction frame.
+    pub fn nodes(&self) -> &[CallTraceNode] {
+        &self.nodes
+    }
+
+    /// Returns the trace of the transaction frame.
+    pub fn root(&self) -> Option<&CallTrace> {
+        self.nodes.first().map(|node| &node.trace)
+    }
+
+    /// Returns true if the frame at `depth` should not be traced.
+    fn skip(&self, depth: usize) -> bool {
+        self.config.only_top_call && depth > 0
+    }
+
+    /// Adds new node as the child of the currently executing frame.
+    fn push_node(&mut self, trace: CallTrace) -> usize {
+        let index = self.nodes.len();
+        let parent = self.stack.last().copied();
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(index);
+        }
+        self.nodes.push(CallTraceNode {
+            parent,
+            children: Vec::new(),
+            trace,
+        });
+        index
+    }
+
+    /// Starts the frame.
+    fn start_frame<DB: Database>(&mut self, context: &EvmContext<DB>, trace: CallTrace) {
+        if self.nodes.is_empty() {
+            self.tx_gas_limit = context.env.tx.gas_limit;
+        }
+        let index = self.push_node(trace);
+        self.stack.push(index);
+    }
+
+    /// Ends the currently executing frame.
+    fn end_frame(&mut self, result: &InterpreterResult, address: Option<Address>) {
+        let Some(index) = self.stack.pop() else {
+            return;
+        };
+        let trace = &mut self.nodes[index].trace;
+        trace.gas_used = result.gas.spent();
+        trace.output = result.output.clone();
+        trace.status = result.result;
+        // failed create keeps the address computed at the start of the frame.
+        if trace.kind.is_create() && address.is_some() {
+            trace.to = address;
+        }
+    }
+
+    /// Returns the frame in the shape of geth `callTracer`.
+    ///
+    /// Transaction frame reports gas limit of the transaction and `tx_gas_used`, the gas
+    /// used by the transaction, same as geth does.
+    pub fn geth_call_frame(&self, tx_gas_used: u64) -> Option<GethCallFrame> {
+        let root = self.nodes.first()?;
+        let mut frame = self.geth_frame(root, false);
+        frame.gas = U256::from(self.tx_gas_limit);
+        frame.gas_used = U256::from(tx_gas_used);
+        Some(frame)
+    }
+
+    fn geth_frame(&self, node: &CallTraceNode, parent_failed: bool) -> GethCallFrame {
+        let trace = &node.trace;
+        let failed = parent_failed || !trace.is_success();
+        GethCallFrame {
+            typ: trace.kind.geth_type().to_owned(),
+            from: trace.from,
+            to: trace.to,
+            value: match trace.kind {
+                CallKind::DelegateCall | CallKind::StaticCall => None,
+                _ => Some(trace.value),
+            },
+            gas: U256::from(trace.gas_limit),
+            gas_used: U256::from(trace.gas_used),
+            input: trace.input.clone(),
+            output: (!trace.output.is_empty()).then(|| trace.output.clone()),
+            error: (!trace.is_success()).then(|| geth_error(trace.status).to_owned()),
+            revert_reason: trace.revert_reason(),
+            calls: node
+                .children
+                .iter()
+                .map(|child| self.geth_frame(&self.nodes[*child], failed))
+                .collect(),
+            // logs of the failed frames are dropped, as they are reverted.
+            logs: if self.config.with_log && !failed {
+                trace
+                    .logs
+                    .iter()
+                    .map(|log| GethCallLogFrame {
+                        address: log.log.address,
+                        topics: log.log.topics().to_vec(),
+                        data: log.log.data.data.clone(),
+                        position: log.position as u64,
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    /// Returns the Parity flat traces, same as `trace_transaction`
+    /// without the block and transaction information.
+    pub fn parity_traces(&self) -> Vec<TransactionTrace> {
+        let mut traces = Vec::with_capacity(self.nodes.len());
+        if !self.nodes.is_empty() {
+            self.parity_trace(0, Vec::new(), &mut traces);
+        }
+        traces
+    }
+
+    /// Returns the output of Parity `trace_replayTransaction` with the `trace` type.
+    pub fn parity_replay(&self) -> TraceResults {
+        TraceResults {
+            output: self
+                .root()
+                .map(|trace| trace.output.clone())
+                .unwrap_or_default(),
+            trace: self.parity_traces(),
+        }
+    }
+
+    fn parity_trace(
+        &self,
+        index: usize,
+        trace_address: Vec<usize>,
+        out: &mut Vec<TransactionTrace>,
+    ) {
+        let node = &self.nodes[index];
+        let trace = &node.trace;
+        let action = match trace.kind {
+            CallKind::SelfDestruct => Action::Suicide(SelfdestructAction {
+                address: trace.from,
+                refund_address: trace.to.unwrap_or_default(),
+                balance: trace.value,
+            }),
+            CallKind::Create | CallKind::Create2 | CallKind::EOFCreate => {
+                Action::Create(CreateAction {
+                    from: trace.from,
+                    value: trace.value,
+                    gas: U256::from(trace.gas_limit),
+                    init: trace.input.clone(),
+                    creation_method: match trace.kind {
+                        CallKind::Create2 => CreationMethod::Create2,
+                        CallKind::EOFCreate => CreationMethod::EofCreate,
+                        _ => CreationMethod::Create,
+                    },
+                })
+            }
+            kind => Action::Call(CallAction {
+                from: trace.from,
+                to: trace.to.unwrap_or_default(),
+                value: trace.value,
+                gas: U256::from(trace.gas_limit),
+                input: trace.input.clone(),
+                call_type: match kind {
+                    CallKind::CallCode => CallType::CallCode,
+                    CallKind::DelegateCall => CallType::DelegateCall,
+                    CallKind::StaticCall => CallType::StaticCall,
+                    _ => CallType::Call,
+                },
+            }),
+        };
+        let result = if trace.is_success() {
+            match &action {
+                Action::Call(_) => Some(TraceOutput::Call(CallOutput {
+                    gas_used: U256::from(trace.gas_used),
+                    output: trace.output.clone(),
+                })),
+                Action::Create(_) => Some(TraceOutput::Create(CreateOutput {
+                    gas_used: U256::from(trace.gas_used),
+                    code: trace.output.clone(),
+                    address: trace.to.unwrap_or_default(),
+                })),
+                Action::Suicide(_) => None,
+            }
+        } else {
+            None
+        };
+        out.push(TransactionTrace {
+            action,
+            error: (!trace.is_success()).then(|| parity_error(trace.status).to_owned()),
+            result,
+            subtraces: node.children.len(),
+            trace_address: trace_address.clone(),
+        });
+        for (i, child) in node.children.iter().enumerate() {
+            let mut child_address = trace_address.clone();
+            child_address.push(i);
+            self.parity_trace(*child, child_address, out);
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CallTracer {
+    fn log(&mut self, context: &mut EvmContext<DB>, log: &Log) {
+        // journal depth is one more than the depth of the executing frame.
+        let depth = (context.journaled_state.depth() as usize).saturating_sub(1);
+        if self.skip(depth) {
+            return;
+        }
+        if let Some(index) = self.stack.last() {
+            let node = &mut self.nodes[*index];
+            node.trace.logs.push(CallLog {
+                log: log.clone(),
+                position: node.children.len(),
+            });
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        let depth = context.journaled_state.depth() as usize;
+        if !self.skip(depth) {
+            let value = match inputs.value {
+                CallValue::Transfer(value) | CallValue::Apparent(value) => value,
+            };
+            self.start_frame(
+                context,
+                CallTrace {
+                    kind: inputs.scheme.into(),
+                    depth,
+                    from: inputs.caller,
+                    to: Some(inputs.bytecode_address),
+                    value,
+                    gas_limit: inputs.gas_limit,
+                    input: inputs.input.clone(),
+                    ..Default::default()
+                },
+            );
+        }
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        if !self.skip(context.journaled_state.depth() as usize) {
+            self.end_frame(&outcome.result, None);
+        }
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        let depth = context.journaled_state.depth() as usize;
+        if !self.skip(depth) {
+            // caller is loaded and its nonce is not yet bumped by the create.
+            let nonce = context
+                .journaled_state
+                .state
+                .get(&inputs.caller)
+                .map(|account| account.info.nonce)
+                .unwrap_or_default();
+            self.start_frame(
+                context,
+                CallTrace {
+                    kind: match inputs.scheme {
+                        CreateScheme::Create => CallKind::Create,
+                        CreateScheme::Create2 { .. } => CallKind::Create2,
+                    },
+                    depth,
+                    from: inputs.caller,
+                    to: Some(inputs.created_address(nonce)),
+                    value: inputs.value,
+                    gas_limit: inputs.gas_limit,
+                    input: inputs.init_code.clone(),
+                    ..Default::default()
+                },
+            );
+        }
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        if !self.skip(context.journaled_state.depth() as usize) {
+            self.end_frame(&outcome.result, outcome.address);
+        }
+        outcome
+    }
+
+    fn eofcreate(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut EOFCreateInputs,
+    ) -> Option<EOFCreateOutcome> {
+        let depth = context.journaled_state.depth() as usize;
+        if !self.skip(depth) {
+            self.start_frame(
+                context,
+                CallTrace {
+                    kind: CallKind::EOFCreate,
+                    depth,
+                    from: inputs.caller,
+                    to: Some(inputs.created_address),
+                    value: inputs.value,
+                    gas_limit: inputs.gas_limit,
+                    input: inputs.eof_init_code.raw.clone(),
+                    ..Default::default()
+                },
+            );
+        }
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &EOFCreateInputs,
+        outcome: EOFCreateOutcome,
+    ) -> EOFCreateOutcome {
+        if !self.skip(context.journaled_state.depth() as usize) {
+            self.end_frame(&outcome.result, Some(outcome.address));
+        }
+        outcome
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        let Some(parent) = self.stack.last() else {
+            return;
+        };
+        let depth = self.nodes[*parent].trace.depth + 1;
+        if self.skip(depth) {
+            return;
+        }
+        self.push_node(CallTrace {
+            kind: CallKind::SelfDestruct,
+            depth,
+            from: contract,
+            to: Some(target),
+            value,
+            status: InstructionResult::SelfDestruct,
+            ..Default::default()
+        });
+    }
+}
+
+/// Decodes the `Error(string)` revert reason from the revert output.
+pub fn decode_revert_reason(output: &[u8]) -> Option<String> {
+    let data = output.strip_prefix(&ERROR_SELECTOR)?;
+    // abi encoded string: offset, length and the padded bytes.
+    let word = |index: usize| -> Option<usize> {
+        let word = data.get(index..index + 32)?;
+        // values larger than usize can't be valid offsets.
+        if word[..24].iter().any(|b| *b != 0) {
+            return None;
+        }
+        Some(u64::from_be_bytes(word[24..].try_into().unwrap()) as usize)
+    };
+    let offset = word(0)?;
+    let len = word(offset)?;
+    let start = offset.checked_add(32)?;
+    let bytes = data.get(start..start.checked_add(len)?)?;
+    core::str::from_utf8(bytes).ok().map(ToOwned::to_owned)
+}
+
+/// Returns the error message of the failed frame as reported by geth.
+fn geth_error(status: InstructionResult) -> &'static str {
+    use InstructionResult::*;
+    match status {
+        Revert => "execution reverted",
+        OutOfGas | MemoryOOG | MemoryLimitOOG | PrecompileOOG | InvalidOperandOOG => "out of gas",
+        CallTooDeep => "max call depth exceeded",
+        OutOfFunds => "insufficient balance for transfer",
+        OpcodeNotFound | InvalidFEOpcode | NotActivated | EOFOpcodeDisabledInLegacy => {
+            "invalid opcode"
+        }
+        InvalidJump => "invalid jump destination",
+        CallNotAllowedInsideStatic | StateChangeDuringStaticCall => "write protection",
+        StackUnderflow => "stack underflow",
+        StackOverflow | EOFFunctionStackOverflow => "stack limit reached 1024",
+        OutOfOffset => "return data out of bounds",
+        CreateCollision => "contract address collision",
+        NonceOverflow => "nonce uint64 overflow",
+        CreateContractSizeLimit => "max code size exceeded",
+        CreateContractStartingWithEF => "invalid code: must not begin with 0xef",
+        CreateInitCodeSizeLimit => "max initcode size exceeded",
+        PrecompileError => "precompiled contract failed",
+        _ => "execution failed",
+    }
+}
+
+/// Returns the error message of the failed frame as reported by Parity.
+fn parity_error(status: InstructionResult) -> &'static str {
+    use InstructionResult::*;
+    match status {
+        Revert => "Reverted",
+        OutOfGas | MemoryOOG | MemoryLimitOOG | PrecompileOOG | InvalidOperandOOG => "Out of gas",
+        CallTooDeep => "Call depth limit exceeded",
+        OutOfFunds => "Insufficient balance",
+        OpcodeNotFound | InvalidFEOpcode | NotActivated | EOFOpcodeDisabledInLegacy => {
+            "Bad instruction"
+        }
+        InvalidJump => "Bad jump destination",
+        CallNotAllowedInsideStatic | StateChangeDuringStaticCall => {
+            "Mutable Call In Static Context"
+        }
+        StackUnderflow => "Stack underflow",
+        StackOverflow | EOFFunctionStackOverflow => "Out of stack",
+        PrecompileError => "Built-in failed",
+        _ => "Internal error",
+    }
+}
+
+/// Log in the geth `callTracer` frame.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GethCallLogFrame {
+    /// Address of the contract that emitted the log.
+    pub address: Address,
+    /// Topics of the log.
+    pub topics: Vec<B256>,
+    /// Data of the log.
+    pub data: Bytes,
+    /// Number of child calls of the frame before the log was emitted.
+    #[cfg_attr(feature = "serde", serde(with = "hex_u64"))]
+    pub position: u64,
+}
+
+/// Frame in the shape of the geth `callTracer` output.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct GethCallFrame {
+    /// Type of the frame, `CALL`, `CREATE`, ...
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub typ: String,
+    /// Caller of the frame.
+    pub from: Address,
+    /// Callee or created contract.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub to: Option<Address>,
+    /// Value of the call.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub value: Option<U256>,
+    /// Gas limit of the frame.
+    pub gas: U256,
+    /// Gas used by the frame.
+    pub gas_used: U256,
+    /// Input of the call or init code.
+    pub input: Bytes,
+    /// Output of the call or code of the created contract.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub output: Option<Bytes>,
+    /// Error of the failed frame.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub error: Option<String>,
+    /// Decoded revert reason.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub revert_reason: Option<String>,
+    /// Child frames.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub calls: Vec<GethCallFrame>,
+    /// Logs emitted by the frame.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub logs: Vec<GethCallLogFrame>,
+}
+
+/// Type of the Parity call action.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CallType {
+    /// `CALL`.
+    #[default]
+    Call,
+    /// `CALLCODE`.
+    CallCode,
+    /// `DELEGATECALL`.
+    DelegateCall,
+    /// `STATICCALL`.
+    StaticCall,
+}
+
+/// Method used to create the contract in the Parity create action.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CreationMethod {
+    /// `CREATE` or creation transaction.
+    #[default]
+    Create,
+    /// `CREATE2`.
+    Create2,
+    /// `EOFCREATE`.
+    EofCreate,
+}
+
+/// Parity call action.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallAction {
+    /// Caller.
+    pub from: Address,
+    /// Callee.
+    pub to: Address,
+    /// Transferred value.
+    pub value: U256,
+    /// Gas limit.
+    pub gas: U256,
+    /// Call input.
+    pub input: Bytes,
+    /// Type of the call.
+    pub call_type: CallType,
+}
+
+/// Parity create action.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CreateAction {
+    /// Creator.
+    pub from: Address,
+    /// Endowment of the created contract.
+    pub value: U256,
+    /// Gas limit.
+    pub gas: U256,
+    /// Init code.
+    pub init: Bytes,
+    /// Method used to create the contract.
+    pub creation_method: CreationMethod,
+}
+
+/// Parity selfdestruct action.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SelfdestructAction {
+    /// Destroyed contract.
+    pub address: Address,
+    /// Beneficiary of the contract balance.
+    pub refund_address: Address,
+    /// Transferred balance.
+    pub balance: U256,
+}
+
+/// Action of the Parity trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(tag = "type", content = "action", rename_all = "lowercase")
+)]
+pub enum Action {
+    /// Call.
+    Call(CallAction),
+    /// Contract creation.
+    Create(CreateAction),
+    /// Selfdestruct.
+    Suicide(SelfdestructAction),
+}
+
+/// Output of the successful Parity call trace.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CallOutput {
+    /// Gas used.
+    pub gas_used: U256,
+    /// Call output.
+    pub output: Bytes,
+}
+
+/// Output of the successful Parity create trace.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CreateOutput {
+    /// Gas used.
+    pub gas_used: U256,
+    /// Code of the created contract.
+    pub code: Bytes,
+    /// Address of the created contract.
+    pub address: Address,
+}
+
+/// Output of the successful Parity trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum TraceOutput {
+    /// Output of the call.
+    Call(CallOutput),
+    /// Output of the contract creation.
+    Create(CreateOutput),
+}
+
+/// Parity flat trace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TransactionTrace {
+    /// Traced action.
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub action: Action,
+    /// Error of the failed frame.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub error: Option<String>,
+    /// Output of the successful frame.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub result: Option<TraceOutput>,
+    /// Number of child traces.
+    pub subtraces: usize,
+    /// Position of the trace in the call tree.
+    pub trace_address: Vec<usize>,
+}
+
+/// Output of Parity `trace_replayTransaction` with the `trace` type.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TraceResults {
+    /// Output of the transaction.
+    pub output: Bytes,
+    /// Flat traces of the transaction.
+    pub trace: Vec<TransactionTrace>,
+}
+
+/// Serializes `u64` as hex quantity, as geth does for log position.
+#[cfg(feature = "serde")]
+mod hex_u64 {
+    use crate::primitives::U256;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        U256::from(*value).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        U256::deserialize(deserializer)?
+            .try_into()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        inspector_handle_register,
+        primitives::{address, hex, AccountInfo, Bytecode, TransactTo},
+        Evm,
+    };
+
+    #[test]
+    fn revert_reason() {
+        // Error("no")
+        let output = hex!(
+            "08c379a0"
+            "0000000000000000000000000000000000000000000000000000000000000020"
+            "0000000000000000000000000000000000000000000000000000000000000002"
+            "6e6f000000000000000000000000000000000000000000000000000000000000"
+        );
+        assert_eq!(decode_revert_reason(&output), Some("no".into()));
+        assert_eq!(decode_revert_reason(&output[..40]), None);
+        assert_eq!(decode_revert_reason(&[]), None);
+    }
+
+    #[test]
+    fn nested_calls() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let outer = address!("1000000000000000000000000000000000000002");
+        let inner = address!("1000000000000000000000000000000000000003");
+
+        // LOG0 with empty data, then CALL inner with all gas, then REVERT with empty data.
+        let mut outer_code = hex!("60006000a0600060006000600060007f").to_vec();
+        outer_code.extend_from_slice(B256::left_padding_from(inner.as_slice()).as_slice());
+        outer_code.extend_from_slice(&hex!("5af15060006000fd"));
+        // LOG0 with empty data and STOP.
+        let inner_code = hex!("60006000a000");
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller, AccountInfo::from_balance(U256::from(1_000_000)));
+        for (address, code) in [(outer, outer_code), (inner, inner_code.to_vec())] {
+            let code = Bytecode::new_raw(code.into());
+            db.insert_account_info(
+                address,
+                AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+            );
+        }
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(CallTracer::new(CallTracerConfig {
+                with_log: true,
+                ..Default::default()
+            }))
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(outer);
+                tx.gas_limit = 100_000;
+                tx.value = U256::from(10);
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let result = evm.transact().unwrap().result;
+        assert!(!result.is_success());
+
+        let tracer = &evm.context.external;
+        let nodes = tracer.nodes();
+        assert_eq!(nodes.len(), 2);
+        assert_eq!(nodes[0].children, vec![1]);
+        assert_eq!(nodes[1].parent, Some(0));
+        assert_eq!(nodes[1].trace.depth, 1);
+        assert_eq!(nodes[1].trace.from, outer);
+        assert_eq!(nodes[1].trace.to, Some(inner));
+        assert!(nodes[1].trace.is_success());
+        assert_eq!(nodes[0].trace.logs[0].position, 0);
+        assert_eq!(nodes[0].trace.status, InstructionResult::Revert);
+
+        let frame = tracer.geth_call_frame(result.gas_used()).unwrap();
+        assert_eq!(frame.typ, "CALL");
+        assert_eq!(frame.gas, U256::from(100_000));
+        assert_eq!(frame.gas_used, U256::from(result.gas_used()));
+        assert_eq!(frame.value, Some(U256::from(10)));
+        assert_eq!(frame.error.as_deref(), Some("execution reverted"));
+        assert_eq!(frame.calls.len(), 1);
+        // logs of the reverted frames are dropped.
+        assert!(frame.logs.is_empty());
+        assert!(frame.calls[0].logs.is_empty());
+
+        let traces = tracer.parity_traces();
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[0].subtraces, 1);
+        assert_eq!(traces[0].error.as_deref(), Some("Reverted"));
+        assert_eq!(traces[0].result, None);
+        assert_eq!(traces[1].trace_address, vec![0]);
+        assert!(matches!(
+            &traces[1].action,
+            Action::Call(CallAction { from, to, call_type: CallType::Call, .. })
+                if *from == outer && *to == inner
+        ));
+        assert!(matches!(traces[1].result, Some(TraceOutput::Call(_))));
+    }
+
+    #[test]
+    fn failed_create_reports_created_address() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            caller,
+            AccountInfo {
+                balance: U256::from(1_000_000),
+                nonce: 5,
+                ..Default::default()
+            },
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(CallTracer::new(CallTracerConfig::default()))
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Create;
+                // REVERT with empty data.
+                tx.data = hex!("60006000fd").into();
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let result = evm.transact().unwrap().result;
+        assert!(!result.is_success());
+
+        let tracer = &evm.context.external;
+        let created = caller.create(5);
+        assert_eq!(tracer.nodes()[0].trace.to, Some(created));
+        let frame = tracer.geth_call_frame(result.gas_used()).unwrap();
+        assert_eq!(frame.typ, "CREATE");
+        assert_eq!(frame.to, Some(created));
+    }
+}
@@ -0,0 +1,640 @@
+use crate::{
+    interpreter::{
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, EOFCreateInputs, EOFCreateOutcome,
+    },
+    primitives::{
+        db::Database, Address, Bytes, EVMError, EvmState, SpecId, B256, KECCAK_EMPTY, U256,
+    },
+    EvmContext, Inspector,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+/// Configuration of the [`PrestateTracer`], same as geth `prestateTracer` config.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct PrestateConfig {
+    /// Report both pre and post state of the changed accounts.
+    pub diff_mode: bool,
+    /// Do not report account code.
+    pub disable_code: bool,
+    /// Do not report account storage.
+    pub disable_storage: bool,
+}
+
+/// Account state as reported by the geth `prestateTracer`.
+///
+/// Fields that are not set are omitted from the output.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountState {
+    /// Account balance.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub balance: Option<U256>,
+    /// Account nonce.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub nonce: Option<u64>,
+    /// Account code.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub code: Option<Bytes>,
+    /// Account storage.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub storage: BTreeMap<B256, B256>,
+}
+
+/// Pre-state of all accounts touched by the transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct PreStateMode(pub BTreeMap<Address, AccountState>);
+
+/// Pre and post state of the accounts changed by the transaction.
+///
+/// Pre state contains only changed fields and slots of the accounts, post state contains
+/// only fields and slots that changed. Deleted accounts are present only in the pre state,
+/// created accounts only in the post state.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffMode {
+    /// State before the transaction.
+    pub pre: BTreeMap<Address, AccountState>,
+    /// State after the transaction.
+    pub post: BTreeMap<Address, AccountState>,
+}
+
+/// Output of the [`PrestateTracer`], depending on [`PrestateConfig::diff_mode`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum PreStateFrame {
+    /// Pre-state of the touched accounts.
+    Default(PreStateMode),
+    /// Pre and post state of the changed accounts.
+    Diff(DiffMode),
+}
+
+/// Account as it was before the transaction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct PreAccount {
+    balance: U256,
+    nonce: u64,
+    code: Bytes,
+    /// Original values of the touched slots.
+    storage: BTreeMap<U256, U256>,
+}
+
+impl PreAccount {
+    /// Returns true if account is not empty or has storage, same as geth `exists` check.
+    fn exists(&self) -> bool {
+        self.nonce != 0
+            || self.balance != U256::ZERO
+            || !self.code.is_empty()
+            || !self.storage.is_empty()
+    }
+}
+
+/// Inspector that records the state of every account and storage slot touched by the
+/// transaction, before the transaction was executed.
+///
+/// Pre-state of the accounts is loaded from the database, storage is taken from the
+/// original values of the slots inside the journal. Output is in the shape of the geth
+/// `prestateTracer`, see [`PrestateTracer::into_frame`].
+///
+/// Tracer should be used for a single transaction. Diff mode needs the state returned
+/// by [`Evm::transact`](crate::Evm::transact), before it is committed to the database.
+#[derive(Clone, Debug, Default)]
+pub struct PrestateTracer {
+    config: PrestateConfig,
+    /// Pre-state of the touched accounts.
+    pre: BTreeMap<Address, PreAccount>,
+    /// Accounts created by the transaction.
+    created: BTreeSet<Address>,
+    /// Spec of the traced transaction.
+    spec_id: SpecId,
+}
+
+impl PrestateTracer {
+    /// Creates new prestate tracer.
+    pub fn new(config: PrestateConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the tracer config.
+    pub fn config(&self) -> &PrestateConfig {
+        &self.config
+    }
+
+    /// Records pre-state of the account if it is not already recorded.
+    fn record_account<DB: Database>(&mut self, context: &mut EvmContext<DB>, address: Address) {
+        if self.pre.contains_key(&address) {
+            return;
+        }
+        match load_pre_account(&mut context.inner.db, address) {
+            Ok(account) => {
+                self.pre.insert(address, account);
+            }
+            Err(e) => context.inner.error = Err(EVMError::Database(e)),
+        }
+    }
+
+    /// Records the accounts that are always touched by the transaction,
+    /// geth records them at the start of the transaction.
+    fn record_tx_accounts<DB: Database>(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        target: Option<Address>,
+    ) {
+        if context.journaled_state.depth() != 0 {
+            return;
+        }
+        self.spec_id = context.spec_id();
+        let caller = context.env.tx.caller;
+        let coinbase = context.env.block.coinbase;
+        for address in [Some(caller), target, Some(coinbase)].into_iter().flatten() {
+            self.record_account(context, address);
+        }
+    }
+
+    /// Records all accounts and slots loaded into the journal. Called when the
+    /// outermost frame returns.
+    fn record_journal<DB: Database>(&mut self, context: &mut EvmContext<DB>) {
+        if context.journaled_state.depth() != 0 {
+            return;
+        }
+        let addresses = context
+            .journaled_state
+            .state
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        for address in addresses {
+            self.record_account(context, address);
+            let account = &context.journaled_state.state[&address];
+            if account.is_created() {
+                self.created.insert(address);
+            }
+            if let Some(pre) = self.pre.get_mut(&address) {
+                for (slot, value) in &account.storage {
+                    pre.storage.entry(*slot).or_insert(value.original_value());
+                }
+            }
+        }
+    }
+
+    /// Converts the internal account to the reported account state.
+    fn account_state(&self, account: &PreAccount) -> AccountState {
+        AccountState {
+            balance: Some(account.balance),
+            nonce: (account.nonce != 0).then_some(account.nonce),
+            code: (!self.config.disable_code && !account.code.is_empty())
+                .then(|| account.code.clone()),
+            storage: if self.config.disable_storage {
+                BTreeMap::new()
+            } else {
+                account
+                    .storage
+                    .iter()
+                    .map(|(slot, value)| (B256::from(*slot), B256::from(*value)))
+                    .collect()
+            },
+        }
+    }
+
+    /// Returns the pre-state of all touched accounts.
+    ///
+    /// Accounts created by the transaction that did not exist before are omitted.
+    pub fn pre_state(&self) -> PreStateMode {
+        PreStateMode(
+            self.pre
+                .iter()
+                .filter(|(address, account)| !self.created.contains(*address) || account.exists())
+                .map(|(address, account)| (*address, self.account_state(account)))
+                .collect(),
+        )
+    }
+
+    /// Returns the pre and post state of the accounts changed by the transaction.
+    ///
+    /// `state` is the state returned by the execution of the transaction. Since Spurious
+    /// Dragon, touched empty accounts are considered deleted, as they are under the EIP-161
+    /// state clear rules.
+    pub fn diff(&self, state: &EvmState) -> DiffMode {
+        let mut diff = DiffMode::default();
+        for (address, pre) in &self.pre {
+            let Some(account) = state.get(address) else {
+                continue;
+            };
+            let existed = !self.created.contains(address) || pre.exists();
+            let mut pre_state = self.account_state(pre);
+
+            let state_cleared = self.spec_id.is_enabled_in(SpecId::SPURIOUS_DRAGON)
+                && account.is_touched()
+                && account.is_empty();
+            if account.is_selfdestructed() || state_cleared {
+                if existed && !account.is_loaded_as_not_existing() {
+                    diff.pre.insert(*address, pre_state);
+                }
+                continue;
+            }
+
+            let mut post_state = AccountState::default();
+            let mut modified = false;
+            if account.info.balance != pre.balance {
+                post_state.balance = Some(account.info.balance);
+                modified = true;
+            }
+            if account.info.nonce != pre.nonce {
+                post_state.nonce = Some(account.info.nonce);
+                modified = true;
+            }
+            let code = account
+                .info
+                .code
+                .as_ref()
+                .map(|code| code.original_bytes())
+                .unwrap_or_default();
+            if code != pre.code {
+                if !self.config.disable_code && !code.is_empty() {
+                    post_state.code = Some(code);
+                }
+                modified = true;
+            }
+            for (slot, original) in &pre.storage {
+                let present = account
+                    .storage
+                    .get(slot)
+                    .map(|s| s.present_value())
+                    .unwrap_or(*original);
+                if present == *original {
+                    pre_state.storage.remove(&B256::from(*slot));
+                    continue;
+                }
+                modified = true;
+                if !self.config.disable_storage && present != U256::ZERO {
+                    post_state
+                        .storage
+                        .insert(B256::from(*slot), B256::from(present));
+                }
+            }
+
+            if modified {
+                if existed {
+                    diff.pre.insert(*address, pre_state);
+                }
+                diff.post.insert(*address, post_state);
+            }
+        }
+        diff
+    }
+
+    /// Returns the output in the shape of geth `prestateTracer`, depending on the
+    /// [`PrestateConfig::diff_mode`].
+    pub fn into_frame(self, state: &EvmState) -> PreStateFrame {
+        if self.config.diff_mode {
+            PreStateFrame::Diff(self.diff(state))
+        } else {
+            PreStateFrame::Default(self.pre_state())
+        }
+    }
+}
+
+/// Loads the account with its code from the database.
+fn load_pre_account<DB: Database>(db: &mut DB, address: Address) -> Result<PreAccount, DB::Error> {
+    let Some(info) = db.basic(address)? else {
+        return Ok(PreAccount::default());
+    };
+    let code = match info.code {
+        Some(code) => code.original_bytes(),
+        None if info.code_hash == KECCAK_EMPTY || info.code_hash == B256::ZERO => Bytes::new(),
+        None => db.code_by_hash(info.code_hash)?.original_bytes(),
+    };
+    Ok(PreAccount {
+        balance: info.balance,
+        nonce: info.nonce,
+        code,
+        storage: BTreeMap::new(),
+    })
+}
+
+impl<DB: Database> Inspector<DB> for PrestateTracer {
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.record_tx_accounts(context, Some(inputs.target_address));
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.record_journal(context);
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.record_tx_accounts(context, None);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.record_journal(context);
+        outcome
+    }
+
+    fn eofcreate(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &mut EOFCreateInputs,
+    ) -> Option<EOFCreateOutcome> {
+        self.record_tx_accounts(context, None);
+        None
+    }
+
+    fn eofcreate_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        _inputs: &EOFCreateInputs,
+        outcome: EOFCreateOutcome,
+    ) -> EOFCreateOutcome {
+        self.record_journal(context);
+        outcome
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        inspector_handle_register,
+        primitives::{address, AccountInfo, Bytecode, TransactTo},
+        Evm,
+    };
+
+    const CALLER: Address = address!("1000000000000000000000000000000000000001");
+    const CONTRACT: Address = address!("1000000000000000000000000000000000000002");
+
+    /// Database with the funded caller and the contract that stores 2 into the slot one.
+    fn db() -> CacheDB<EmptyDB> {
+        // PUSH1 2, PUSH1 1, SSTORE, STOP
+        let code = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x02, 0x60, 0x01, 0x55, 0x00]));
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            CALLER,
+            AccountInfo::from_balance(U256::from(1_000_000_000u64)),
+        );
+        db.insert_account_info(
+            CONTRACT,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        db.insert_account_storage(CONTRACT, U256::from(1), U256::from(1))
+            .unwrap();
+        db
+    }
+
+    /// Traces the call from [`CALLER`] to `to`.
+    fn trace(
+        db: CacheDB<EmptyDB>,
+        config: PrestateConfig,
+        spec_id: SpecId,
+        to: Address,
+    ) -> (PrestateTracer, EvmState) {
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(PrestateTracer::new(config))
+            .with_spec_id(spec_id)
+            .modify_tx_env(|tx| {
+                tx.caller = CALLER;
+                tx.transact_to = TransactTo::Call(to);
+                tx.gas_limit = 100_000;
+            })
+            .modify_block_env(|block| block.basefee = U256::ZERO)
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+        (evm.context.external.clone(), result.state)
+    }
+
+    #[test]
+    fn prestate_and_diff() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let contract = address!("1000000000000000000000000000000000000002");
+        // SLOAD slot 1, SSTORE 2 into slot 1, SLOAD slot 2 and stop.
+        let code = Bytecode::new_raw(Bytes::from_static(&[
+            0x60, 0x01, 0x54, 0x50, 0x60, 0x02, 0x60, 0x01, 0x55, 0x60, 0x02, 0x54, 0x00,
+        ]));
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            caller,
+            AccountInfo::from_balance(U256::from(1_000_000_000u64)),
+        );
+        db.insert_account_info(
+            contract,
+            AccountInfo::new(U256::ZERO, 0, code.hash_slow(), code.clone()),
+        );
+        db.insert_account_storage(contract, U256::from(1), U256::from(1))
+            .unwrap();
+        db.insert_account_storage(contract, U256::from(2), U256::from(3))
+            .unwrap();
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .with_external_context(PrestateTracer::new(PrestateConfig {
+                diff_mode: true,
+                ..Default::default()
+            }))
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = TransactTo::Call(contract);
+                tx.gas_limit = 100_000;
+            })
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let result = evm.transact().unwrap();
+        assert!(result.result.is_success());
+
+        let tracer = evm.context.external.clone();
+        let pre = tracer.pre_state();
+        let contract_pre = &pre.0[&contract];
+        assert_eq!(contract_pre.code, Some(code.original_bytes()));
+        assert_eq!(
+            contract_pre.storage,
+            BTreeMap::from([
+                (B256::from(U256::from(1)), B256::from(U256::from(1))),
+                (B256::from(U256::from(2)), B256::from(U256::from(3))),
+            ])
+        );
+        assert_eq!(pre.0[&caller].balance, Some(U256::from(1_000_000_000u64)));
+        assert_eq!(pre.0[&caller].nonce, None);
+
+        let PreStateFrame::Diff(diff) = tracer.into_frame(&result.state) else {
+            panic!("expected diff mode");
+        };
+        // only changed slot is reported, code did not change.
+        assert_eq!(
+            diff.pre[&contract],
+            AccountState {
+                balance: Some(U256::ZERO),
+                storage: BTreeMap::from([(B256::from(U256::from(1)), B256::from(U256::from(1)))]),
+                code: Some(code.original_bytes()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            diff.post[&contract],
+            AccountState {
+                storage: BTreeMap::from([(B256::from(U256::from(1)), B256::from(U256::from(2)))]),
+                ..Default::default()
+            }
+        );
+        assert_eq!(diff.post[&caller].nonce, Some(1));
+    }
+
+    #[test]
+    fn disable_code_and_storage() {
+        let config = PrestateConfig {
+            disable_code: true,
+            disable_storage: true,
+            ..Default::default()
+        };
+        let (tracer, state) = trace(db(), config, SpecId::LATEST, CONTRACT);
+
+        let pre = tracer.pre_state();
+        assert_eq!(
+            pre.0[&CONTRACT],
+            AccountState {
+                balance: Some(U256::ZERO),
+                nonce: Some(1),
+                ..Default::default()
+            }
+        );
+        let diff = tracer.diff(&state);
+        assert_eq!(pre.0[&CONTRACT], diff.pre[&CONTRACT]);
+        // slot change is still a modification, but its value is not reported.
+        assert_eq!(diff.post[&CONTRACT], AccountState::default());
+
+        let (tracer, state) = trace(db(), PrestateConfig::default(), SpecId::LATEST, CONTRACT);
+        let diff = tracer.diff(&state);
+        assert!(diff.pre[&CONTRACT].code.is_some());
+        assert_eq!(
+            diff.post[&CONTRACT].storage,
+            BTreeMap::from([(B256::from(U256::from(1)), B256::from(U256::from(2)))])
+        );
+    }
+
+    #[test]
+    fn touched_empty_account_deleted_since_spurious_dragon() {
+        let empty = address!("1000000000000000000000000000000000000003");
+        let mut db = db();
+        db.insert_account_info(empty, AccountInfo::default());
+
+        let (tracer, state) = trace(db.clone(), PrestateConfig::default(), SpecId::LATEST, empty);
+        let diff = tracer.diff(&state);
+        assert_eq!(
+            diff.pre[&empty],
+            AccountState {
+                balance: Some(U256::ZERO),
+                ..Default::default()
+            }
+        );
+        assert!(!diff.post.contains_key(&empty));
+
+        let (tracer, state) = trace(db, PrestateConfig::default(), SpecId::HOMESTEAD, empty);
+        let diff = tracer.diff(&state);
+        assert!(!diff.pre.contains_key(&empty));
+        assert!(!diff.post.contains_key(&empty));
+    }
+
+    #[cfg(feature = "serde-json")]
+    #[test]
+    fn geth_json_shape() {
+        let (tracer, state) = trace(db(), PrestateConfig::default(), SpecId::LATEST, CONTRACT);
+        let json = serde_json::to_value(tracer.pre_state()).unwrap();
+        assert_eq!(
+            json[CONTRACT.to_string().to_lowercase()],
+            serde_json::json!({
+                "balance": "0x0",
+                "nonce": 1,
+                "code": "0x600260015500",
+                "storage": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000001":
+                        "0x0000000000000000000000000000000000000000000000000000000000000001"
+                }
+            })
+        );
+
+        // output of geth `prestateTracer` in the diff mode.
+        let geth = r#"{
+            "pre": {
+                "0x1000000000000000000000000000000000000001": {
+                    "balance": "0x3b9aca00"
+                },
+                "0x1000000000000000000000000000000000000002": {
+                    "balance": "0x0",
+                    "nonce": 1,
+                    "code": "0x600260015500",
+                    "storage": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000001"
+                    }
+                }
+            },
+            "post": {
+                "0x1000000000000000000000000000000000000001": {
+                    "nonce": 1
+                },
+                "0x1000000000000000000000000000000000000002": {
+                    "storage": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000001": "0x0000000000000000000000000000000000000000000000000000000000000002"
+                    }
+                }
+            }
+        }"#;
+        let frame = PrestateTracer {
+            config: PrestateConfig {
+                diff_mode: true,
+                ..Default::default()
+            },
+            ..tracer
+        }
+        .into_frame(&state);
+        assert_eq!(serde_json::from_str::<PreStateFrame>(geth).unwrap(), frame);
+        assert_eq!(
+            serde_json::to_value(&frame).unwrap(),
+            serde_json::from_str::<serde_json::Value>(geth).unwrap()
+        );
+    }
+}
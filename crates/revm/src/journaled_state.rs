@@ -1,11 +1,17 @@
-use crate::interpreter::{InstructionResult, SelfDestructResult};
+use crate::interpreter::{analysis::to_analysed_with_kind, InstructionResult, SelfDestructResult};
 use crate::primitives::{
-    db::Database, hash_map::Entry, Account, Address, Bytecode, EVMError, EvmState, EvmStorageSlot,
-    HashMap, HashSet, Log, SpecId::*, TransientStorage, KECCAK_EMPTY, PRECOMPILE3, U256,
+    db::Database, hash_map::Entry, Account, AccountInfo, Address, AnalysisKind, Bytecode, EVMError,
+    Env, EvmState, EvmStorageSlot, HashMap, HashSet, Log, SpecId::*, TransactTo, TransientStorage,
+    B256, EIP7702_MAGIC_BYTES, KECCAK_EMPTY, PRECOMPILE3, U256,
 };
+use crate::read_write_set::ReadSet;
+#[cfg(feature = "std")]
+use crate::BytecodeCache;
 use core::mem;
 use revm_interpreter::primitives::SpecId;
 use revm_interpreter::{LoadAccountResult, SStoreResult};
+#[cfg(feature = "std")]
+use std::sync::Arc;
 use std::vec::Vec;
 
 /// JournalState is internal EVM state that is used to contain state and track changes to that state.
@@ -34,6 +40,31 @@ pub struct JournaledState {
     /// Note that this not include newly loaded accounts, account and storage
     /// is considered warm if it is found in the `State`.
     pub warm_preloaded_addresses: HashSet<Address>,
+    /// Records values read from the database when set.
+    ///
+    /// Recording is opt-in and is disabled when the journaled state is cleared.
+    pub read_set: Option<ReadSet>,
+    /// Balance increments that are not yet applied to the accounts.
+    ///
+    /// Increments are commutative, so they can be recorded without loading the account
+    /// and are applied only when the balance is needed. They are kept by [`Self::finalize`]
+    /// and discarded when the journaled state is cleared.
+    pub balance_increments: HashMap<Address, U256>,
+    /// Accounts and storage slots read ahead of the execution by [`Self::prefetch`].
+    pub prefetched: Prefetched,
+    /// Code analysed by [`Self::load_analysed_code`] in this transaction, by the code hash
+    /// and the analysis.
+    ///
+    /// Kept outside of the accounts, so the analysis doesn't change the state.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub analysed_code: HashMap<(B256, AnalysisKind), Bytecode>,
+    /// Cache of the analysed code loaded by [`Self::load_analysed_code`], shared with other
+    /// EVMs.
+    ///
+    /// Cache is kept when the journaled state is cleared.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub bytecode_cache: Option<Arc<BytecodeCache>>,
 }
 
 impl JournaledState {
@@ -57,6 +88,12 @@ impl JournaledState {
             depth: 0,
             spec,
             warm_preloaded_addresses,
+            read_set: None,
+            balance_increments: HashMap::new(),
+            prefetched: Prefetched::default(),
+            analysed_code: HashMap::new(),
+            #[cfg(feature = "std")]
+            bytecode_cache: None,
         }
     }
 
@@ -91,10 +128,23 @@ impl JournaledState {
         }
     }
 
-    /// Clears the JournaledState. Preserving only the spec.
+    /// Clears the JournaledState. Preserving only the spec and the bytecode cache.
     pub fn clear(&mut self) {
         let spec = self.spec;
+        #[cfg(feature = "std")]
+        let bytecode_cache = self.bytecode_cache.take();
         *self = Self::new(spec, HashSet::new());
+        #[cfg(feature = "std")]
+        {
+            self.bytecode_cache = bytecode_cache;
+        }
+    }
+
+    /// Sets the cache of the analysed code, see [`BytecodeCache`].
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn set_bytecode_cache(&mut self, bytecode_cache: Option<Arc<BytecodeCache>>) {
+        self.bytecode_cache = bytecode_cache;
     }
 
     /// Does cleanup and returns modified state.
@@ -111,9 +161,18 @@ impl JournaledState {
             // kept, see [Self::new]
             spec: _,
             warm_preloaded_addresses: _,
+            // taken by the caller after the transaction is finalized.
+            read_set: _,
+            balance_increments: _,
+            prefetched,
+            analysed_code,
+            #[cfg(feature = "std")]
+                bytecode_cache: _,
         } = self;
 
         *transient_storage = TransientStorage::default();
+        *prefetched = Prefetched::default();
+        *analysed_code = HashMap::new();
         *journal = vec![vec![]];
         *depth = 0;
         let state = mem::take(state);
@@ -122,6 +181,106 @@ impl JournaledState {
         (state, logs)
     }
 
+    /// Starts recording of the values read from the database, discarding the previous records.
+    #[inline]
+    pub fn record_reads(&mut self) {
+        self.read_set = Some(ReadSet::default());
+    }
+
+    /// Takes the recorded values read from the database and stops recording.
+    #[inline]
+    pub fn take_read_set(&mut self) -> Option<ReadSet> {
+        self.read_set.take()
+    }
+
+    /// Records the balance increment of the account without loading it.
+    ///
+    /// Increment is applied by [`Self::apply_balance_increments`].
+    #[inline]
+    pub fn increment_balance_lazily(&mut self, address: Address, amount: U256) {
+        let increment = self.balance_increments.entry(address).or_default();
+        *increment = increment.saturating_add(amount);
+    }
+
+    /// Loads the accounts with pending balance increments and applies the increments.
+    pub fn apply_balance_increments<DB: Database>(
+        &mut self,
+        db: &mut DB,
+    ) -> Result<(), EVMError<DB::Error>> {
+        for (address, increment) in mem::take(&mut self.balance_increments) {
+            let (account, _) = self.load_account(address, db)?;
+            account.info.balance = account.info.balance.saturating_add(increment);
+            self.touch(&address);
+        }
+        Ok(())
+    }
+
+    /// Reads the accounts and storage slots the transaction is known to access ahead of the
+    /// execution, with one [`Database::basic_many`] and one [`Database::storage_many`] call.
+    ///
+    /// Caller, call target, coinbase and the access list are read. Values are used instead
+    /// of the database when the account or slot is first loaded, so accesses are charged
+    /// the same as without the prefetch. They are discarded when the state is finalized.
+    pub fn prefetch<DB: Database>(
+        &mut self,
+        env: &Env,
+        db: &mut DB,
+    ) -> Result<(), EVMError<DB::Error>> {
+        let target = match env.tx.transact_to {
+            TransactTo::Call(address) => Some(address),
+            TransactTo::Create => None,
+        };
+        let mut seen = HashSet::new();
+        let addresses = [Some(env.tx.caller), target, Some(env.block.coinbase)]
+            .into_iter()
+            .flatten()
+            .chain(env.tx.access_list.iter().map(|(address, _)| *address))
+            .filter(|address| {
+                !self.state.contains_key(address)
+                    && !self.prefetched.accounts.contains_key(address)
+                    && seen.insert(*address)
+            })
+            .collect::<Vec<_>>();
+
+        let mut seen = HashSet::new();
+        let slots = env
+            .tx
+            .access_list
+            .iter()
+            .flat_map(|(address, keys)| keys.iter().map(|key| (*address, *key)))
+            .filter(|(address, key)| {
+                !self
+                    .state
+                    .get(address)
+                    .is_some_and(|account| account.storage.contains_key(key))
+                    && !self
+                        .prefetched
+                        .storage
+                        .get(address)
+                        .is_some_and(|storage| storage.contains_key(key))
+                    && seen.insert((*address, *key))
+            })
+            .collect::<Vec<_>>();
+
+        if !addresses.is_empty() {
+            let infos = db.basic_many(&addresses).map_err(EVMError::Database)?;
+            self.prefetched
+                .accounts
+                .extend(addresses.into_iter().zip(infos));
+        }
+        if !slots.is_empty() {
+            let values = db.storage_many(&slots).map_err(EVMError::Database)?;
+            for ((address, key), value) in slots.into_iter().zip(values) {
+                self.prefetched
+                    .storage
+                    .entry(address)
+                    .or_default()
+                    .insert(key, value);
+            }
+        }
+        Ok(())
+    }
+
     /// Returns the _loaded_ [Account] for the given address.
     ///
     /// This assumes that the account has already been loaded.
@@ -221,6 +380,37 @@ impl JournaledState {
         Ok(None)
     }
 
+    /// Transfers balance from the loaded account to the account that is credited with
+    /// a lazy balance increment, see [`Self::increment_balance_lazily`].
+    ///
+    /// Receiver is not loaded. Transfer is journaled and the increment is removed on revert.
+    #[inline]
+    pub fn transfer_lazily(
+        &mut self,
+        from: &Address,
+        to: Address,
+        balance: U256,
+    ) -> Option<InstructionResult> {
+        let from_account = self.state.get_mut(from).unwrap();
+        Self::touch_account(self.journal.last_mut().unwrap(), from, from_account);
+        let from_balance = &mut from_account.info.balance;
+        let Some(from_balance_decr) = from_balance.checked_sub(balance) else {
+            return Some(InstructionResult::OutOfFunds);
+        };
+        *from_balance = from_balance_decr;
+
+        self.increment_balance_lazily(to, balance);
+        self.journal
+            .last_mut()
+            .unwrap()
+            .push(JournalEntry::LazyBalanceTransfer {
+                from: *from,
+                to,
+                balance,
+            });
+        None
+    }
+
     /// Create account or return false if collision is detected.
     ///
     /// There are few steps done:
@@ -307,6 +497,7 @@ impl JournaledState {
     fn journal_revert(
         state: &mut EvmState,
         transient_storage: &mut TransientStorage,
+        balance_increments: &mut HashMap<Address, U256>,
         journal_entries: Vec<JournalEntry>,
         is_spurious_dragon_enabled: bool,
     ) {
@@ -352,6 +543,15 @@ impl JournaledState {
                     let to = state.get_mut(&to).unwrap();
                     to.info.balance -= balance;
                 }
+                JournalEntry::LazyBalanceTransfer { from, to, balance } => {
+                    state.get_mut(&from).unwrap().info.balance += balance;
+                    if let Entry::Occupied(mut increment) = balance_increments.entry(to) {
+                        *increment.get_mut() -= balance;
+                        if increment.get().is_zero() {
+                            increment.remove();
+                        }
+                    }
+                }
                 JournalEntry::NonceChange { address } => {
                     state.get_mut(&address).unwrap().info.nonce -= 1;
                 }
@@ -433,6 +633,7 @@ impl JournaledState {
         let is_spurious_dragon_enabled = SpecId::enabled(self.spec, SPURIOUS_DRAGON);
         let state = &mut self.state;
         let transient_storage = &mut self.transient_storage;
+        let balance_increments = &mut self.balance_increments;
         self.depth -= 1;
         // iterate over last N journals sets and revert our global state
         let leng = self.journal.len();
@@ -444,6 +645,7 @@ impl JournaledState {
                 Self::journal_revert(
                     state,
                     transient_storage,
+                    balance_increments,
                     mem::take(cs),
                     is_spurious_dragon_enabled,
                 )
@@ -536,17 +738,24 @@ impl JournaledState {
         // load or get account.
         let account = match self.state.entry(address) {
             Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(vac) => vac.insert(
-                db.basic(address)
-                    .map_err(EVMError::Database)?
-                    .map(|i| i.into())
-                    .unwrap_or(Account::new_not_existing()),
-            ),
+            Entry::Vacant(vac) => {
+                let info = self.prefetched.basic(address, db)?;
+                if let Some(read_set) = &mut self.read_set {
+                    read_set.record_account(address, info.as_ref());
+                }
+                vac.insert(
+                    info.map(|i| i.into())
+                        .unwrap_or(Account::new_not_existing()),
+                )
+            }
         };
         // preload storages.
         for slot in slots {
             if let Entry::Vacant(entry) = account.storage.entry(*slot) {
-                let storage = db.storage(address, *slot).map_err(EVMError::Database)?;
+                let storage = self.prefetched.storage(address, *slot, db)?;
+                if let Some(read_set) = &mut self.read_set {
+                    read_set.record_storage(address, *slot, storage);
+                }
                 entry.insert(EvmStorageSlot::new(storage));
             }
         }
@@ -567,12 +776,15 @@ impl JournaledState {
                 (account, is_cold)
             }
             Entry::Vacant(vac) => {
-                let account =
-                    if let Some(account) = db.basic(address).map_err(EVMError::Database)? {
-                        account.into()
-                    } else {
-                        Account::new_not_existing()
-                    };
+                let info = self.prefetched.basic(address, db)?;
+                if let Some(read_set) = &mut self.read_set {
+                    read_set.record_account(address, info.as_ref());
+                }
+                let account = if let Some(account) = info {
+                    account.into()
+                } else {
+                    Account::new_not_existing()
+                };
 
                 // precompiles are warm loaded so we need to take that into account
                 let is_cold = !self.warm_preloaded_addresses.contains(&address);
@@ -594,7 +806,11 @@ impl JournaledState {
 
     /// Load account from database to JournaledState.
     ///
-    /// Return boolean pair where first is `is_cold` second bool `is_exists`.
+    /// Returns [`LoadAccountResult`] with `is_cold` and `is_empty` flags of the account.
+    ///
+    /// From Prague the code of the account is loaded as well and if it is an
+    /// EIP-7702 delegation designator, the delegated account is loaded and its
+    /// warm/cold state is returned in `is_delegate_account_cold`.
     #[inline]
     pub fn load_account_exist<DB: Database>(
         &mut self,
@@ -602,7 +818,12 @@ impl JournaledState {
         db: &mut DB,
     ) -> Result<LoadAccountResult, EVMError<DB::Error>> {
         let spec = self.spec;
-        let (acc, is_cold) = self.load_account(address, db)?;
+        let is_prague_enabled = SpecId::enabled(spec, PRAGUE);
+        let (acc, is_cold) = if is_prague_enabled {
+            self.load_code(address, db)?
+        } else {
+            self.load_account(address, db)?
+        };
 
         let is_spurious_dragon_enabled = SpecId::enabled(spec, SPURIOUS_DRAGON);
         let is_empty = if is_spurious_dragon_enabled {
@@ -613,7 +834,46 @@ impl JournaledState {
             loaded_not_existing && is_not_touched
         };
 
-        Ok(LoadAccountResult { is_empty, is_cold })
+        // EIP-7702: Load the delegated account.
+        let delegated_address = acc
+            .info
+            .code
+            .as_ref()
+            .and_then(|code| code.eip7702())
+            .map(|eip7702| eip7702.address());
+        let is_delegate_account_cold = match delegated_address {
+            Some(delegated_address) => Some(self.load_account(delegated_address, db)?.1),
+            None => None,
+        };
+
+        Ok(LoadAccountResult {
+            is_empty,
+            is_cold,
+            is_delegate_account_cold,
+        })
+    }
+
+    /// Returns the code hash of the account.
+    ///
+    /// Account is not loaded if it is not present in the state, only its code hash
+    /// is read from the database.
+    #[inline]
+    pub fn code_hash<DB: Database>(
+        &mut self,
+        address: Address,
+        db: &mut DB,
+    ) -> Result<B256, EVMError<DB::Error>> {
+        if let Some(account) = self.state.get(&address) {
+            return Ok(account.info.code_hash);
+        }
+        let code_hash = match self.prefetched.accounts.get(&address) {
+            Some(info) => info.as_ref().map_or(KECCAK_EMPTY, |info| info.code_hash),
+            None => db.code_hash(address).map_err(EVMError::Database)?,
+        };
+        if let Some(read_set) = &mut self.read_set {
+            read_set.record_code_hash(address, code_hash);
+        }
+        Ok(code_hash)
     }
 
     /// Loads code.
@@ -623,21 +883,65 @@ impl JournaledState {
         address: Address,
         db: &mut DB,
     ) -> Result<(&mut Account, bool), EVMError<DB::Error>> {
-        let (acc, is_cold) = self.load_account(address, db)?;
+        let (_, is_cold) = self.load_account(address, db)?;
+        let acc = self.state.get_mut(&address).unwrap();
         if acc.info.code.is_none() {
             if acc.info.code_hash == KECCAK_EMPTY {
                 let empty = Bytecode::default();
                 acc.info.code = Some(empty);
             } else {
-                let code = db
+                let mut code = db
                     .code_by_hash(acc.info.code_hash)
                     .map_err(EVMError::Database)?;
+                if let Some(read_set) = &mut self.read_set {
+                    read_set.record_code(acc.info.code_hash);
+                }
+                // Database can return EIP-7702 delegation designator as raw bytes.
+                if let Bytecode::LegacyRaw(raw) = &code {
+                    if raw.starts_with(&EIP7702_MAGIC_BYTES) {
+                        code = Bytecode::new_raw(raw.clone());
+                    }
+                }
                 acc.info.code = Some(code);
             }
         }
         Ok((acc, is_cold))
     }
 
+    /// Loads code and analyses it with the given kind, see [`to_analysed_with_kind`].
+    ///
+    /// Returns the code hash, the analysed code and whether the account was cold. Code is
+    /// analysed once per transaction and the account keeps the code as loaded. If the
+    /// [`BytecodeCache`] is set, code is analysed through it. With [`AnalysisKind::Raw`] the
+    /// code is returned as loaded.
+    #[inline]
+    pub fn load_analysed_code<DB: Database>(
+        &mut self,
+        address: Address,
+        db: &mut DB,
+        kind: &AnalysisKind,
+    ) -> Result<(B256, Bytecode, bool), EVMError<DB::Error>> {
+        let (acc, is_cold) = self.load_code(address, db)?;
+        let code_hash = acc.info.code_hash();
+        let code = acc.info.code.clone().unwrap_or_default();
+        if *kind == AnalysisKind::Raw {
+            return Ok((code_hash, code, is_cold));
+        }
+        let key = (code_hash, kind.clone());
+        if let Some(code) = self.analysed_code.get(&key) {
+            return Ok((code_hash, code.clone(), is_cold));
+        }
+        #[cfg(feature = "std")]
+        let code = match &self.bytecode_cache {
+            Some(cache) => cache.get_or_insert(code_hash, kind, code),
+            None => to_analysed_with_kind(code, kind),
+        };
+        #[cfg(not(feature = "std"))]
+        let code = to_analysed_with_kind(code, kind);
+        self.analysed_code.insert(key, code.clone());
+        Ok((code_hash, code, is_cold))
+    }
+
     /// Load storage slot
     ///
     /// # Panics
@@ -665,7 +969,11 @@ impl JournaledState {
                 let value = if is_newly_created {
                     U256::ZERO
                 } else {
-                    db.storage(address, key).map_err(EVMError::Database)?
+                    let value = self.prefetched.storage(address, key, db)?;
+                    if let Some(read_set) = &mut self.read_set {
+                        read_set.record_storage(address, key, value);
+                    }
+                    value
                 };
 
                 vac.insert(EvmStorageSlot::new(value));
@@ -794,6 +1102,51 @@ impl JournaledState {
     }
 }
 
+/// Accounts and storage slots read ahead of the execution, see [`JournaledState::prefetch`].
+///
+/// Values are taken out when the account or slot is loaded into the state.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Prefetched {
+    /// Prefetched accounts, `None` if the account does not exist.
+    pub accounts: HashMap<Address, Option<AccountInfo>>,
+    /// Prefetched storage slots.
+    pub storage: HashMap<Address, HashMap<U256, U256>>,
+}
+
+impl Prefetched {
+    /// Takes the prefetched account or reads it from the database.
+    #[inline]
+    fn basic<DB: Database>(
+        &mut self,
+        address: Address,
+        db: &mut DB,
+    ) -> Result<Option<AccountInfo>, EVMError<DB::Error>> {
+        match self.accounts.remove(&address) {
+            Some(info) => Ok(info),
+            None => db.basic(address).map_err(EVMError::Database),
+        }
+    }
+
+    /// Takes the prefetched storage slot or reads it from the database.
+    #[inline]
+    fn storage<DB: Database>(
+        &mut self,
+        address: Address,
+        key: U256,
+        db: &mut DB,
+    ) -> Result<U256, EVMError<DB::Error>> {
+        match self
+            .storage
+            .get_mut(&address)
+            .and_then(|storage| storage.remove(&key))
+        {
+            Some(value) => Ok(value),
+            None => db.storage(address, key).map_err(EVMError::Database),
+        }
+    }
+}
+
 /// Journal entries that are used to track changes to the state and are used to revert it.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -824,6 +1177,14 @@ pub enum JournalEntry {
         to: Address,
         balance: U256,
     },
+    /// Transfer balance to the account that is credited with a lazy balance increment
+    /// Action: Transfer balance and record the increment
+    /// Revert: Transfer balance back and remove the increment
+    LazyBalanceTransfer {
+        from: Address,
+        to: Address,
+        balance: U256,
+    },
     /// Increment nonce
     /// Action: Increment nonce by one
     /// Revert: Decrement nonce by one
@@ -9,6 +9,8 @@ extern crate alloc as std;
 // Define modules.
 
 mod builder;
+#[cfg(feature = "std")]
+mod bytecode_cache;
 mod context;
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -16,16 +18,23 @@ pub mod test_utils;
 
 pub mod db;
 mod evm;
+mod executor;
 mod frame;
 pub mod handler;
 mod inspector;
 mod journaled_state;
 #[cfg(feature = "optimism")]
 pub mod optimism;
+#[cfg(feature = "std")]
+mod parallel;
+mod read_write_set;
+mod simulate;
 
 // Export items.
 
 pub use builder::EvmBuilder;
+#[cfg(feature = "std")]
+pub use bytecode_cache::{BytecodeCache, BytecodeCacheMetrics, DEFAULT_BYTECODE_CACHE_CAPACITY};
 pub use context::{
     Context, ContextPrecompile, ContextPrecompiles, ContextStatefulPrecompile,
     ContextStatefulPrecompileArc, ContextStatefulPrecompileBox, ContextStatefulPrecompileMut,
@@ -35,14 +44,22 @@ pub use db::{
     CacheState, DBBox, State, StateBuilder, StateDBBox, TransitionAccount, TransitionState,
 };
 pub use db::{Database, DatabaseCommit, DatabaseRef, InMemoryDB};
-pub use evm::{Evm, CALL_STACK_LIMIT};
+pub use evm::{Evm, GasEstimate, CALL_STACK_LIMIT};
+pub use executor::{BlockExecutionError, BlockExecutionOutput, BlockExecutor, Withdrawal};
 pub use frame::{CallFrame, CreateFrame, Frame, FrameData, FrameOrResult, FrameResult};
 pub use handler::Handler;
 pub use inspector::{inspector_handle_register, inspectors, GetInspector, Inspector};
-pub use journaled_state::{JournalCheckpoint, JournalEntry, JournaledState};
+pub use journaled_state::{JournalCheckpoint, JournalEntry, JournaledState, Prefetched};
 // export Optimism types, helpers, and constants
 #[cfg(feature = "optimism")]
 pub use optimism::{L1BlockInfo, BASE_FEE_RECIPIENT, L1_BLOCK_CONTRACT, L1_FEE_RECIPIENT};
+#[cfg(feature = "std")]
+pub use parallel::{ParallelExecutionOutput, ParallelExecutor, DEFAULT_MAX_INCARNATIONS};
+pub use read_write_set::{ReadSet, WriteSet};
+pub use simulate::{
+    transfer_log_handle_register, SimulatedBlock, SimulatedBlockResult, SimulationError,
+    SimulationOptions, Simulator, TRANSFER_LOG_ADDRESS, TRANSFER_LOG_TOPIC,
+};
 
 // Reexport libraries
 
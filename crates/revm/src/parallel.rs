@@ -0,0 +1,550 @@
+//! Parallel execution of the block transactions.
+
+use crate::{
+    db::{
+        mv_memory::{Incarnation, TxIdx},
+        MvDatabase, MvDatabaseError, MvMemory,
+    },
+    handler::lazy_balance::lazy_balance_handle_register,
+    primitives::{
+        db::{Database, DatabaseRef},
+        hash_map::Entry,
+        Account, EVMError, EnvWithHandlerCfg, ResultAndState, TxEnv,
+    },
+    BytecodeCache, Evm,
+};
+use core::{mem, num::NonZeroUsize};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    vec::Vec,
+};
+
+/// Default maximum number of incarnations of a transaction before the parallel execution
+/// falls back to the sequential one.
+pub const DEFAULT_MAX_INCARNATIONS: usize = 8;
+
+/// Output of the [`ParallelExecutor`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParallelExecutionOutput {
+    /// Results and changed state of every transaction, in the block order.
+    ///
+    /// State of every transaction is relative to the state after the previous transaction
+    /// and they should be committed in order.
+    pub results: Vec<ResultAndState>,
+    /// True if the parallel execution was aborted because of repeated conflicts and
+    /// transactions were executed sequentially.
+    pub sequential_fallback: bool,
+}
+
+/// Executes transactions of the block in parallel using the [Block-STM] algorithm.
+///
+/// Transactions are executed optimistically on multiple threads, each with its own [`Evm`],
+/// over the shared [`MvMemory`]. After execution the values read by the transaction are
+/// validated and if a lower transaction wrote to them in the meantime the transaction is
+/// aborted and re-executed. Transaction that reads the write of the aborted transaction
+/// waits until it is re-executed.
+///
+/// If any transaction is executed more than `max_incarnations` times, parallel execution
+/// is stopped and the block is executed sequentially.
+///
+/// Transactions are executed with the [`lazy_balance_handle_register`], so fees paid to the
+/// beneficiary and pure value transfers are written as balance increments that don't conflict
+/// with each other. Returned state of the transaction has the balances of the incremented
+/// accounts as seen after the transaction.
+///
+/// [Block-STM]: https://arxiv.org/abs/2203.06871
+#[derive(Clone, Debug)]
+pub struct ParallelExecutor {
+    /// Environment of the block, transaction environment is replaced for every transaction.
+    env: EnvWithHandlerCfg,
+    /// Number of threads executing transactions.
+    concurrency_level: NonZeroUsize,
+    /// Maximum number of incarnations of a transaction.
+    max_incarnations: usize,
+    /// Cache of the analysed code shared by the workers.
+    bytecode_cache: Option<Arc<BytecodeCache>>,
+}
+
+impl ParallelExecutor {
+    /// Creates new parallel executor with the environment of the block.
+    ///
+    /// Concurrency level defaults to the available parallelism.
+    pub fn new(env: EnvWithHandlerCfg) -> Self {
+        Self {
+            env,
+            concurrency_level: thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+            max_incarnations: DEFAULT_MAX_INCARNATIONS,
+            bytecode_cache: None,
+        }
+    }
+
+    /// Sets the number of threads executing transactions.
+    pub fn with_concurrency_level(mut self, concurrency_level: NonZeroUsize) -> Self {
+        self.concurrency_level = concurrency_level;
+        self
+    }
+
+    /// Sets the maximum number of incarnations of a transaction before falling back to
+    /// the sequential execution.
+    pub fn with_max_incarnations(mut self, max_incarnations: usize) -> Self {
+        self.max_incarnations = max_incarnations;
+        self
+    }
+
+    /// Sets the cache of the analysed code shared by the workers, and by other executors
+    /// and EVMs that use the same cache.
+    pub fn with_bytecode_cache(mut self, bytecode_cache: Arc<BytecodeCache>) -> Self {
+        self.bytecode_cache = Some(bytecode_cache);
+        self
+    }
+
+    /// Executes transactions of the block on top of the `db` state.
+    ///
+    /// State is not committed, changes of every transaction are returned in the output.
+    /// Returns the first error in the block order if any transaction fails validation.
+    pub fn execute<DB>(
+        &self,
+        db: &DB,
+        txs: &[TxEnv],
+    ) -> Result<ParallelExecutionOutput, EVMError<DB::Error>>
+    where
+        DB: DatabaseRef + Sync,
+        DB::Error: Send,
+    {
+        let concurrency_level = self.concurrency_level.get().min(txs.len());
+        if concurrency_level > 1 {
+            if let Some(results) = self.execute_parallel(db, txs, concurrency_level) {
+                return Ok(ParallelExecutionOutput {
+                    results: results?,
+                    sequential_fallback: false,
+                });
+            }
+        }
+        Ok(ParallelExecutionOutput {
+            results: self.execute_sequential(db, txs)?,
+            sequential_fallback: concurrency_level > 1,
+        })
+    }
+
+    /// Executes transactions in parallel, returns `None` if execution was aborted.
+    fn execute_parallel<DB>(
+        &self,
+        db: &DB,
+        txs: &[TxEnv],
+        concurrency_level: usize,
+    ) -> Option<Result<Vec<ResultAndState>, EVMError<DB::Error>>>
+    where
+        DB: DatabaseRef + Sync,
+        DB::Error: Send,
+    {
+        let memory = MvMemory::new(txs.len());
+        let scheduler = Scheduler::new(txs.len(), self.max_incarnations);
+        let results = (0..txs.len()).map(|_| Mutex::new(None)).collect::<Vec<_>>();
+
+        thread::scope(|scope| {
+            for _ in 0..concurrency_level {
+                scope.spawn(|| self.run_worker(&scheduler, &memory, db, txs, &results));
+            }
+        });
+
+        if scheduler.is_aborted() {
+            return None;
+        }
+        let results = results
+            .into_iter()
+            .map(|result| {
+                result
+                    .into_inner()
+                    .unwrap()
+                    .expect("all transactions are executed")
+                    .map_err(into_base_error)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|mut results| {
+                apply_balance_increments(&memory, db, &mut results)?;
+                Ok(results)
+            });
+        Some(results)
+    }
+
+    /// Executes the tasks of the scheduler until all transactions are executed and validated.
+    fn run_worker<DB: DatabaseRef>(
+        &self,
+        scheduler: &Scheduler,
+        memory: &MvMemory,
+        db: &DB,
+        txs: &[TxEnv],
+        results: &[Mutex<Option<ExecutionOutput<DB::Error>>>],
+    ) {
+        let mut evm = Evm::builder()
+            .with_db(MvDatabase::new(memory, db, 0))
+            .with_env_with_handler_cfg(self.env.clone())
+            .append_handler_register(lazy_balance_handle_register)
+            .build();
+        evm.context
+            .evm
+            .journaled_state
+            .set_bytecode_cache(self.bytecode_cache.clone());
+
+        loop {
+            match scheduler.next_task() {
+                Task::Execute(tx_idx, incarnation) => {
+                    *evm.db_mut() = MvDatabase::new(memory, db, tx_idx);
+                    *evm.tx_mut() = txs[tx_idx].clone();
+                    let output = evm.transact_with_read_write_sets();
+                    let reads = evm.db_mut().take_reads();
+
+                    let output = match output {
+                        Err(EVMError::Database(MvDatabaseError::Estimate(blocking_tx_idx))) => {
+                            scheduler.finish_blocked(tx_idx, incarnation, blocking_tx_idx);
+                            continue;
+                        }
+                        Ok((result, _, write_set)) => {
+                            memory.record_state(
+                                tx_idx,
+                                incarnation,
+                                reads,
+                                &result.state,
+                                &write_set,
+                            );
+                            Ok(result)
+                        }
+                        // transaction can fail because of the values it read, it is validated
+                        // as any other transaction.
+                        Err(error) => {
+                            memory.record(tx_idx, incarnation, reads, Vec::new());
+                            Err(error)
+                        }
+                    };
+                    *results[tx_idx].lock().unwrap() = Some(output);
+                    scheduler.finish_execution(tx_idx, incarnation);
+                }
+                Task::Validate(tx_idx, incarnation) => {
+                    let aborted = !memory.validate_reads(tx_idx)
+                        && scheduler.try_validation_abort(tx_idx, incarnation);
+                    if aborted {
+                        memory.convert_writes_to_estimates(tx_idx);
+                    }
+                    scheduler.finish_validation(tx_idx, incarnation, aborted);
+                }
+                Task::Wait => thread::yield_now(),
+                Task::Done => break,
+            }
+        }
+    }
+
+    /// Executes transactions one after another.
+    fn execute_sequential<DB: DatabaseRef>(
+        &self,
+        db: &DB,
+        txs: &[TxEnv],
+    ) -> Result<Vec<ResultAndState>, EVMError<DB::Error>> {
+        let memory = MvMemory::new(txs.len());
+        let mut evm = Evm::builder()
+            .with_db(MvDatabase::new(&memory, db, 0))
+            .with_env_with_handler_cfg(self.env.clone())
+            .append_handler_register(lazy_balance_handle_register)
+            .build();
+        evm.context
+            .evm
+            .journaled_state
+            .set_bytecode_cache(self.bytecode_cache.clone());
+
+        let mut results = Vec::with_capacity(txs.len());
+        for (tx_idx, tx) in txs.iter().enumerate() {
+            *evm.db_mut() = MvDatabase::new(&memory, db, tx_idx);
+            *evm.tx_mut() = tx.clone();
+            let (result, _, write_set) = evm
+                .transact_with_read_write_sets()
+                .map_err(into_base_error)?;
+            memory.record_state(tx_idx, 0, Vec::new(), &result.state, &write_set);
+            results.push(result);
+        }
+        apply_balance_increments(&memory, db, &mut results)?;
+        Ok(results)
+    }
+}
+
+/// Adds the accounts with balance increments to the state of every transaction, with
+/// the balances as seen after the transaction.
+fn apply_balance_increments<DB: DatabaseRef>(
+    memory: &MvMemory,
+    db: &DB,
+    results: &mut [ResultAndState],
+) -> Result<(), EVMError<DB::Error>> {
+    for (tx_idx, result) in results.iter_mut().enumerate() {
+        let mut view = MvDatabase::new(memory, db, tx_idx + 1);
+        for address in memory.balance_increments(tx_idx) {
+            let info = view
+                .basic(address)
+                .map_err(|error| into_base_error(EVMError::Database(error)))?
+                .unwrap_or_default();
+            let account = match result.state.entry(address) {
+                Entry::Occupied(entry) => {
+                    let account = entry.into_mut();
+                    account.info.balance = info.balance;
+                    account
+                }
+                Entry::Vacant(entry) => entry.insert(Account::from(info)),
+            };
+            account.mark_touch();
+        }
+    }
+    Ok(())
+}
+
+/// Output of the transaction execution over the [`MvDatabase`].
+type ExecutionOutput<E> = Result<ResultAndState, EVMError<MvDatabaseError<E>>>;
+
+/// Converts the error of the transaction that was not blocked.
+fn into_base_error<E>(error: EVMError<MvDatabaseError<E>>) -> EVMError<E> {
+    error.map_db_err(|error| match error {
+        MvDatabaseError::Database(error) => error,
+        MvDatabaseError::Estimate(_) => unreachable!("blocked transactions are re-executed"),
+    })
+}
+
+/// Task of the worker thread.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Task {
+    /// Execute the incarnation of the transaction.
+    Execute(TxIdx, Incarnation),
+    /// Validate reads of the executed incarnation of the transaction.
+    Validate(TxIdx, Incarnation),
+    /// No task is available now, other workers are still running.
+    Wait,
+    /// All transactions are executed and validated, or execution is aborted.
+    Done,
+}
+
+/// Execution status of the transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TxStatus {
+    ReadyToExecute,
+    Executing,
+    Executed,
+    Aborting,
+    /// Waiting on the re-execution of the lower transaction.
+    Blocked,
+}
+
+#[derive(Debug)]
+struct SchedulerState {
+    /// Status and current incarnation of every transaction.
+    statuses: Vec<(TxStatus, Incarnation)>,
+    /// Transactions blocked by every transaction.
+    dependents: Vec<Vec<TxIdx>>,
+    /// Next transaction to execute.
+    execution_idx: TxIdx,
+    /// Next transaction to validate.
+    validation_idx: TxIdx,
+    /// Number of tasks that are being processed.
+    active_tasks: usize,
+    /// Parallel execution is aborted.
+    aborted: bool,
+}
+
+/// Collaborative scheduler of the Block-STM.
+///
+/// Transactions with lower index are prioritized, both for execution and validation.
+#[derive(Debug)]
+struct Scheduler {
+    state: Mutex<SchedulerState>,
+    /// Maximum incarnations of a transaction before execution is aborted.
+    max_incarnations: usize,
+}
+
+impl Scheduler {
+    fn new(block_size: usize, max_incarnations: usize) -> Self {
+        Self {
+            state: Mutex::new(SchedulerState {
+                statuses: vec![(TxStatus::ReadyToExecute, 0); block_size],
+                dependents: vec![Vec::new(); block_size],
+                execution_idx: 0,
+                validation_idx: 0,
+                active_tasks: 0,
+                aborted: false,
+            }),
+            max_incarnations,
+        }
+    }
+
+    fn is_aborted(&self) -> bool {
+        self.state.lock().unwrap().aborted
+    }
+
+    fn next_task(&self) -> Task {
+        let mut state = self.state.lock().unwrap();
+        let block_size = state.statuses.len();
+        while !state.aborted {
+            if state.validation_idx < state.execution_idx {
+                let tx_idx = state.validation_idx;
+                state.validation_idx += 1;
+                if let (TxStatus::Executed, incarnation) = state.statuses[tx_idx] {
+                    state.active_tasks += 1;
+                    return Task::Validate(tx_idx, incarnation);
+                }
+            } else if state.execution_idx < block_size {
+                let tx_idx = state.execution_idx;
+                state.execution_idx += 1;
+                if let (TxStatus::ReadyToExecute, incarnation) = state.statuses[tx_idx] {
+                    state.statuses[tx_idx].0 = TxStatus::Executing;
+                    state.active_tasks += 1;
+                    return Task::Execute(tx_idx, incarnation);
+                }
+            } else if state.active_tasks == 0 {
+                return Task::Done;
+            } else {
+                return Task::Wait;
+            }
+        }
+        Task::Done
+    }
+
+    /// Schedules the re-execution of the transaction with the next incarnation.
+    fn set_ready(&self, state: &mut SchedulerState, tx_idx: TxIdx) {
+        let (status, incarnation) = &mut state.statuses[tx_idx];
+        *status = TxStatus::ReadyToExecute;
+        *incarnation += 1;
+        if *incarnation >= self.max_incarnations {
+            state.aborted = true;
+        }
+        state.execution_idx = state.execution_idx.min(tx_idx);
+    }
+
+    fn finish_execution(&self, tx_idx: TxIdx, incarnation: Incarnation) {
+        let mut state = self.state.lock().unwrap();
+        state.statuses[tx_idx] = (TxStatus::Executed, incarnation);
+        for dependent in mem::take(&mut state.dependents[tx_idx]) {
+            self.set_ready(&mut state, dependent);
+        }
+        // transactions that read from the previous incarnation need to be validated again.
+        state.validation_idx = state.validation_idx.min(tx_idx);
+        state.active_tasks -= 1;
+    }
+
+    fn finish_blocked(&self, tx_idx: TxIdx, incarnation: Incarnation, blocking_tx_idx: TxIdx) {
+        let mut state = self.state.lock().unwrap();
+        state.statuses[tx_idx] = (TxStatus::Blocked, incarnation);
+        if state.statuses[blocking_tx_idx].0 == TxStatus::Executed {
+            // blocking transaction was re-executed in the meantime.
+            self.set_ready(&mut state, tx_idx);
+        } else {
+            state.dependents[blocking_tx_idx].push(tx_idx);
+        }
+        state.active_tasks -= 1;
+    }
+
+    fn try_validation_abort(&self, tx_idx: TxIdx, incarnation: Incarnation) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.statuses[tx_idx] == (TxStatus::Executed, incarnation) {
+            state.statuses[tx_idx].0 = TxStatus::Aborting;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn finish_validation(&self, tx_idx: TxIdx, incarnation: Incarnation, aborted: bool) {
+        let mut state = self.state.lock().unwrap();
+        if aborted {
+            debug_assert_eq!(state.statuses[tx_idx], (TxStatus::Aborting, incarnation));
+            self.set_ready(&mut state, tx_idx);
+            // higher transactions may have read the writes of the aborted incarnation.
+            state.validation_idx = state.validation_idx.min(tx_idx + 1);
+        }
+        state.active_tasks -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{
+            address, hex, AccountInfo, Address, Bytecode, Env, HandlerCfg, SpecId, TransactTo, U256,
+        },
+        Database, DatabaseCommit,
+    };
+
+    fn sender(index: usize) -> Address {
+        let mut address = address!("1000000000000000000000000000000000000000");
+        address.0[19] = index as u8;
+        address
+    }
+
+    #[test]
+    fn parallel_execution_matches_sequential() {
+        let counter = address!("2000000000000000000000000000000000000000");
+        let receiver = address!("3000000000000000000000000000000000000000");
+        let coinbase = address!("4000000000000000000000000000000000000000");
+        // increments the slot zero.
+        let code = Bytecode::new_raw(hex!("5f546001015f5500").into());
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            counter,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code),
+        );
+        let txs = (0..32)
+            .map(|index| {
+                db.insert_account_info(
+                    sender(index),
+                    AccountInfo::from_balance(U256::from(1_000_000)),
+                );
+                TxEnv {
+                    caller: sender(index),
+                    // every third transaction increments the counter, others send value
+                    // to the same receiver.
+                    transact_to: TransactTo::Call(if index % 3 == 0 { counter } else { receiver }),
+                    value: U256::from(index % 3),
+                    gas_limit: 100_000,
+                    gas_price: U256::from(1),
+                    ..Default::default()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut env = Box::<Env>::default();
+        // every transaction pays the fee to the coinbase.
+        env.block.coinbase = coinbase;
+        let env = EnvWithHandlerCfg::new(env, HandlerCfg::new(SpecId::CANCUN));
+        for (concurrency_level, max_incarnations) in [(1, 8), (4, 1), (4, 8), (8, 32)] {
+            let output = ParallelExecutor::new(env.clone())
+                .with_concurrency_level(NonZeroUsize::new(concurrency_level).unwrap())
+                .with_max_incarnations(max_incarnations)
+                .execute(&db, &txs)
+                .unwrap();
+            if concurrency_level == 1 {
+                assert!(!output.sequential_fallback);
+            }
+
+            let mut state = db.clone();
+            let mut gas_used = Vec::new();
+            for result in output.results {
+                assert!(result.result.is_success());
+                gas_used.push(result.result.gas_used());
+                state.commit(result.state);
+            }
+            assert_eq!(state.storage(counter, U256::ZERO).unwrap(), U256::from(11));
+            assert_eq!(
+                state.basic(receiver).unwrap().unwrap().balance,
+                U256::from((0..32).map(|index| index % 3).sum::<usize>())
+            );
+            for (index, gas_used) in gas_used.iter().enumerate() {
+                let info = state.basic(sender(index)).unwrap().unwrap();
+                assert_eq!(info.nonce, 1);
+                assert_eq!(
+                    info.balance,
+                    U256::from(1_000_000 - index as u64 % 3 - gas_used)
+                );
+            }
+            assert_eq!(
+                state.basic(coinbase).unwrap().unwrap().balance,
+                U256::from(gas_used.iter().sum::<u64>())
+            );
+        }
+    }
+}
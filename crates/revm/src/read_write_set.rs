@@ -0,0 +1,243 @@
+use crate::primitives::{AccountInfo, Address, Bytecode, EvmState, HashMap, HashSet, B256, U256};
+
+/// State read by the transaction from the database.
+///
+/// Every value is recorded when it is first loaded from the database by the
+/// [`JournaledState`](crate::JournaledState), with the value that was read. Values the
+/// transaction wrote before reading them are never loaded and are not part of the read set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReadSet {
+    /// Accounts read from the database, without the code. `None` if account does not exist.
+    ///
+    /// Account is read as a whole, so its balance, nonce and code hash are all part of
+    /// the read set.
+    pub accounts: HashMap<Address, Option<AccountInfo>>,
+    /// Storage slots read from the database.
+    pub storage: HashMap<Address, HashMap<U256, U256>>,
+    /// Hashes of the code read from the database.
+    pub code_hashes: HashSet<B256>,
+    /// Code hashes of the accounts read from the database without reading the whole account.
+    pub account_code_hashes: HashMap<Address, B256>,
+}
+
+impl ReadSet {
+    /// Returns true if nothing was read.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+            && self.storage.is_empty()
+            && self.code_hashes.is_empty()
+            && self.account_code_hashes.is_empty()
+    }
+
+    /// Records the account read from the database.
+    pub fn record_account(&mut self, address: Address, info: Option<&AccountInfo>) {
+        self.accounts
+            .entry(address)
+            .or_insert_with(|| info.map(|info| info.clone().without_code()));
+    }
+
+    /// Records the storage slot read from the database.
+    pub fn record_storage(&mut self, address: Address, slot: U256, value: U256) {
+        self.storage
+            .entry(address)
+            .or_default()
+            .entry(slot)
+            .or_insert(value);
+    }
+
+    /// Records the code read from the database.
+    pub fn record_code(&mut self, code_hash: B256) {
+        self.code_hashes.insert(code_hash);
+    }
+
+    /// Records the code hash of the account read from the database.
+    pub fn record_code_hash(&mut self, address: Address, code_hash: B256) {
+        self.account_code_hashes.entry(address).or_insert(code_hash);
+    }
+
+    /// Returns true if any of the values read is written by the write set.
+    ///
+    /// Code is identified by its hash and can't be changed, so only accounts, their code
+    /// hashes and storage slots can conflict.
+    pub fn conflicts_with(&self, write_set: &WriteSet) -> bool {
+        self.accounts
+            .keys()
+            .any(|address| write_set.writes_account(address))
+            || self.account_code_hashes.keys().any(|address| {
+                write_set.code.contains_key(address) || write_set.clears_storage(address)
+            })
+            || self.storage.iter().any(|(address, slots)| {
+                write_set.clears_storage(address)
+                    || write_set
+                        .storage
+                        .get(address)
+                        .is_some_and(|written| slots.keys().any(|slot| written.contains_key(slot)))
+            })
+    }
+}
+
+/// State written by the transaction.
+///
+/// Only values that differ from the values the transaction read are part of the write set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WriteSet {
+    /// New balances of the accounts.
+    pub balances: HashMap<Address, U256>,
+    /// New nonces of the accounts.
+    pub nonces: HashMap<Address, u64>,
+    /// New code of the accounts.
+    pub code: HashMap<Address, Bytecode>,
+    /// Changed storage slots.
+    pub storage: HashMap<Address, HashMap<U256, U256>>,
+    /// Accounts created by the transaction. Storage of the account before creation is cleared.
+    pub created: HashSet<Address>,
+    /// Accounts destroyed by the transaction. They have no balance, nonce, code or storage.
+    pub destroyed: HashSet<Address>,
+    /// Balance increments of the accounts that were not loaded by the transaction.
+    ///
+    /// See [`JournaledState::balance_increments`](crate::JournaledState::balance_increments).
+    pub balance_increments: HashMap<Address, U256>,
+}
+
+impl WriteSet {
+    /// Creates the write set from the state changed by the transaction and the values it read.
+    pub fn new(state: &EvmState, read_set: &ReadSet) -> Self {
+        let mut this = Self::default();
+        for (address, account) in state {
+            if !account.is_touched() {
+                continue;
+            }
+            if account.is_selfdestructed() {
+                this.destroyed.insert(*address);
+                continue;
+            }
+
+            let original = match read_set.accounts.get(address) {
+                // created account does not inherit anything.
+                Some(Some(info)) if !account.is_created() => info.clone(),
+                _ => AccountInfo::default(),
+            };
+            if account.is_created() {
+                this.created.insert(*address);
+            }
+            if account.info.balance != original.balance {
+                this.balances.insert(*address, account.info.balance);
+            }
+            if account.info.nonce != original.nonce {
+                this.nonces.insert(*address, account.info.nonce);
+            }
+            if account.info.code_hash != original.code_hash {
+                this.code
+                    .insert(*address, account.info.code.clone().unwrap_or_default());
+            }
+
+            let storage = account
+                .changed_storage_slots()
+                .map(|(slot, value)| (*slot, value.present_value))
+                .collect::<HashMap<_, _>>();
+            if !storage.is_empty() {
+                this.storage.insert(*address, storage);
+            }
+        }
+        this
+    }
+
+    /// Returns true if nothing was written.
+    pub fn is_empty(&self) -> bool {
+        self.balances.is_empty()
+            && self.nonces.is_empty()
+            && self.code.is_empty()
+            && self.storage.is_empty()
+            && self.created.is_empty()
+            && self.destroyed.is_empty()
+            && self.balance_increments.is_empty()
+    }
+
+    /// Returns true if balance, nonce or code of the account is written or its balance
+    /// is incremented.
+    pub fn writes_account(&self, address: &Address) -> bool {
+        self.balances.contains_key(address)
+            || self.balance_increments.contains_key(address)
+            || self.nonces.contains_key(address)
+            || self.code.contains_key(address)
+            || self.clears_storage(address)
+    }
+
+    /// Returns true if the storage of the account is cleared, as it is created or destroyed.
+    pub fn clears_storage(&self, address: &Address) -> bool {
+        self.created.contains(address) || self.destroyed.contains(address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{CacheDB, EmptyDB},
+        primitives::{address, hex, TransactTo},
+        Evm,
+    };
+
+    #[test]
+    fn record_read_write_sets() {
+        let caller1 = address!("1000000000000000000000000000000000000001");
+        let caller2 = address!("1000000000000000000000000000000000000002");
+        let counter = address!("1000000000000000000000000000000000000003");
+        let receiver = address!("1000000000000000000000000000000000000004");
+
+        // increments the slot zero.
+        let code = Bytecode::new_raw(hex!("5f546001015f5500").into());
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(caller1, AccountInfo::from_balance(U256::from(1000)));
+        db.insert_account_info(caller2, AccountInfo::from_balance(U256::from(1000)));
+        db.insert_account_info(
+            counter,
+            AccountInfo::new(U256::ZERO, 1, code.hash_slow(), code.clone()),
+        );
+
+        let mut evm = Evm::builder()
+            .with_db(db)
+            .modify_tx_env(|tx| {
+                tx.caller = caller1;
+                tx.transact_to = TransactTo::Call(counter);
+            })
+            .build();
+
+        let (_, read_set1, write_set1) = evm.transact_with_read_write_sets().unwrap();
+        assert_eq!(
+            read_set1.storage,
+            HashMap::from_iter([(counter, HashMap::from_iter([(U256::ZERO, U256::ZERO)]))])
+        );
+        // code is returned with the account, so it is not read by the hash.
+        assert!(read_set1.code_hashes.is_empty());
+        assert_eq!(
+            read_set1.accounts[&caller1],
+            Some(AccountInfo::from_balance(U256::from(1000)))
+        );
+        assert_eq!(
+            write_set1.storage,
+            HashMap::from_iter([(counter, HashMap::from_iter([(U256::ZERO, U256::from(1))]))])
+        );
+        assert_eq!(write_set1.nonces, HashMap::from_iter([(caller1, 1)]));
+        // gas is free, so balances are not changed.
+        assert!(write_set1.balances.is_empty());
+        // recording is disabled after the transaction.
+        assert!(evm.context.evm.journaled_state.read_set.is_none());
+
+        // second increment reads the value written by the first one.
+        evm.tx_mut().caller = caller2;
+        let (_, read_set2, write_set2) = evm.transact_with_read_write_sets().unwrap();
+        assert!(read_set2.conflicts_with(&write_set1));
+
+        // transfer does not touch the counter.
+        evm.tx_mut().transact_to = TransactTo::Call(receiver);
+        evm.tx_mut().value = U256::from(10);
+        let (_, read_set3, write_set3) = evm.transact_with_read_write_sets().unwrap();
+        assert!(!read_set3.conflicts_with(&write_set1));
+        assert!(read_set3.conflicts_with(&write_set2));
+        assert_eq!(write_set3.balances[&receiver], U256::from(10));
+        assert!(read_set3.accounts[&receiver].is_none());
+    }
+}
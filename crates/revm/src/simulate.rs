@@ -0,0 +1,726 @@
+//! Simulation of a sequence of blocks of calls, same as `eth_simulateV1`.
+
+use crate::{
+    db::{AccountOverride, AccountState, CacheDB, DatabaseRef, StateOverride, StateOverrideError},
+    handler::register::EvmHandler,
+    interpreter::{opcode, InstructionResult},
+    primitives::{
+        address, b256, AccountInfo, Address, BlockEnv, BlockOverrides, Bytecode, EVMError,
+        ExecutionResult, Log, LogData, TxEnv, B256, U256,
+    },
+    Database, DatabaseCommit, Evm, Frame, FrameOrResult, FrameResult,
+};
+use core::fmt;
+use std::{sync::Arc, vec::Vec};
+
+/// Address of the synthetic logs emitted for the ether transfers.
+pub const TRANSFER_LOG_ADDRESS: Address = address!("eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee");
+
+/// Topic of the synthetic transfer logs, `keccak256("Transfer(address,address,uint256)")`.
+pub const TRANSFER_LOG_TOPIC: B256 =
+    b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+/// Time between the simulated blocks, if timestamp is not overridden.
+const BLOCK_TIME: u64 = 12;
+
+/// Options of the [`Simulator`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimulationOptions {
+    /// Enforces the nonces, the fees and the gas limits of the calls as for the real
+    /// transactions.
+    ///
+    /// Calls without the nonce use the nonce of the caller, and the gas limits of the calls
+    /// of the block can't add up to more than the block gas limit.
+    ///
+    /// When disabled, nonces of the calls are not checked and calls with zero gas price
+    /// are executed with zero base fee.
+    pub validation: bool,
+    /// Emits a synthetic ERC-20 like `Transfer` log from [`TRANSFER_LOG_ADDRESS`] for every
+    /// ether transfer of the calls, creates and selfdestructs.
+    pub trace_transfers: bool,
+}
+
+/// Block of calls to simulate.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimulatedBlock {
+    /// Overrides of the block environment.
+    ///
+    /// Block number and timestamp default to the ones of the previous block increased
+    /// by one and by twelve seconds.
+    pub block_overrides: BlockOverrides,
+    /// Overrides of the state applied before the calls of the block.
+    pub state_overrides: StateOverride,
+    /// Calls of the block, executed in order.
+    pub calls: Vec<TxEnv>,
+}
+
+/// Result of the simulated block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimulatedBlockResult {
+    /// Environment the block was executed with.
+    pub block: BlockEnv,
+    /// Results of the calls with their logs and gas used, in order.
+    pub calls: Vec<ExecutionResult>,
+    /// Gas used by all calls of the block.
+    pub gas_used: u64,
+}
+
+/// Errors that can happen during the simulation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SimulationError<DBError> {
+    /// Block number is not higher than the number of the previous block.
+    InvalidBlockNumber {
+        /// Index of the block in the simulation.
+        block: usize,
+        /// Number of the block.
+        number: U256,
+        /// Number of the previous block.
+        parent_number: U256,
+    },
+    /// Block timestamp is not higher than the timestamp of the previous block.
+    InvalidTimestamp {
+        /// Index of the block in the simulation.
+        block: usize,
+        /// Timestamp of the block.
+        timestamp: U256,
+        /// Timestamp of the previous block.
+        parent_timestamp: U256,
+    },
+    /// Gas limit of the call is higher than the gas left in the block.
+    BlockGasLimitReached {
+        /// Index of the block in the simulation.
+        block: usize,
+        /// Index of the call in the block.
+        index: usize,
+        /// Gas limit of the call.
+        gas_limit: u64,
+        /// Gas left in the block.
+        gas_left: U256,
+    },
+    /// State overrides of the block are invalid.
+    StateOverride {
+        /// Index of the block in the simulation.
+        block: usize,
+        /// Error of the overrides.
+        error: StateOverrideError,
+    },
+    /// Call could not be executed.
+    Transaction {
+        /// Index of the block in the simulation.
+        block: usize,
+        /// Index of the call in the block.
+        index: usize,
+        /// Error returned by the EVM.
+        error: EVMError<DBError>,
+    },
+    /// Database error while applying the state overrides.
+    Database(DBError),
+}
+
+#[cfg(feature = "std")]
+impl<DBError: std::error::Error + 'static> std::error::Error for SimulationError<DBError> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::StateOverride { error, .. } => Some(error),
+            Self::Transaction { error, .. } => Some(error),
+            Self::Database(error) => Some(error),
+            Self::InvalidBlockNumber { .. }
+            | Self::InvalidTimestamp { .. }
+            | Self::BlockGasLimitReached { .. } => None,
+        }
+    }
+}
+
+impl<DBError: fmt::Display> fmt::Display for SimulationError<DBError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidBlockNumber {
+                block,
+                number,
+                parent_number,
+            } => write!(
+                f,
+                "block {block} number {number} is not higher than previous block number {parent_number}"
+            ),
+            Self::InvalidTimestamp {
+                block,
+                timestamp,
+                parent_timestamp,
+            } => write!(
+                f,
+                "block {block} timestamp {timestamp} is not higher than previous block timestamp {parent_timestamp}"
+            ),
+            Self::BlockGasLimitReached {
+                block,
+                index,
+                gas_limit,
+                gas_left,
+            } => write!(
+                f,
+                "block {block} call {index} gas limit {gas_limit} is higher than gas left in the block {gas_left}"
+            ),
+            Self::StateOverride { block, error } => {
+                write!(f, "block {block} state override error: {error}")
+            }
+            Self::Transaction {
+                block,
+                index,
+                error,
+            } => write!(f, "block {block} call {index} error: {error}"),
+            Self::Database(error) => write!(f, "database error: {error}"),
+        }
+    }
+}
+
+/// Simulates blocks of calls on top of the [`CacheDB`].
+///
+/// Every block starts from the previous one, or from the block environment of the [`Evm`],
+/// with the [`BlockOverrides`] applied, and the [`StateOverride`] of the block is written
+/// to the cache. Calls are executed with [`Evm::transact`] and committed to the cache,
+/// so later calls and blocks see the changes of the earlier ones, while the wrapped
+/// database is never written to.
+pub struct Simulator<'a, EXT, DB: DatabaseRef> {
+    /// EVM used to execute the calls.
+    evm: Evm<'a, EXT, CacheDB<DB>>,
+    /// Options of the simulation.
+    options: SimulationOptions,
+}
+
+impl<'a, EXT, DB: DatabaseRef> Simulator<'a, EXT, DB> {
+    /// Creates new simulator from the EVM.
+    ///
+    /// [`transfer_log_handle_register`] is appended to the EVM if transfers are traced.
+    pub fn new(evm: Evm<'a, EXT, CacheDB<DB>>, options: SimulationOptions) -> Self {
+        let evm = if options.trace_transfers {
+            evm.modify()
+                .append_handler_register(transfer_log_handle_register)
+                .build()
+        } else {
+            evm
+        };
+        Self { evm, options }
+    }
+
+    /// Returns reference to the EVM.
+    pub fn evm(&self) -> &Evm<'a, EXT, CacheDB<DB>> {
+        &self.evm
+    }
+
+    /// Consumes the simulator and returns the EVM, with the state of all simulated blocks.
+    pub fn into_evm(self) -> Evm<'a, EXT, CacheDB<DB>> {
+        self.evm
+    }
+
+    /// Simulates the blocks in order and returns the results of their calls.
+    ///
+    /// Simulation stops on the first call that can't be executed. Changes of the calls
+    /// executed before the error stay in the cache.
+    pub fn simulate(
+        &mut self,
+        blocks: &[SimulatedBlock],
+    ) -> Result<Vec<SimulatedBlockResult>, SimulationError<DB::Error>> {
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| self.simulate_block(index, block))
+            .collect()
+    }
+
+    /// Simulates the block on top of the block environment of the EVM.
+    ///
+    /// Calls are executed with their own block environment, which is replaced by the
+    /// environment of the simulated block, or by the previous one on error.
+    fn simulate_block(
+        &mut self,
+        index: usize,
+        block: &SimulatedBlock,
+    ) -> Result<SimulatedBlockResult, SimulationError<DB::Error>> {
+        let parent = self.evm.block().clone();
+        let result = self.simulate_block_inner(index, block, &parent);
+        *self.evm.block_mut() = match &result {
+            Ok(result) => result.block.clone(),
+            Err(_) => parent,
+        };
+        result
+    }
+
+    fn simulate_block_inner(
+        &mut self,
+        index: usize,
+        block: &SimulatedBlock,
+        parent: &BlockEnv,
+    ) -> Result<SimulatedBlockResult, SimulationError<DB::Error>> {
+        let mut env = parent.clone();
+        env.number = parent.number.saturating_add(U256::from(1));
+        env.timestamp = parent.timestamp.saturating_add(U256::from(BLOCK_TIME));
+        env.apply_overrides(&block.block_overrides);
+        if env.number <= parent.number {
+            return Err(SimulationError::InvalidBlockNumber {
+                block: index,
+                number: env.number,
+                parent_number: parent.number,
+            });
+        }
+        if env.timestamp <= parent.timestamp {
+            return Err(SimulationError::InvalidTimestamp {
+                block: index,
+                timestamp: env.timestamp,
+                parent_timestamp: parent.timestamp,
+            });
+        }
+
+        for (address, account) in &block.state_overrides {
+            apply_account_override(self.evm.db_mut(), *address, account).map_err(|error| {
+                match error {
+                    OverrideError::StateOverride(error) => SimulationError::StateOverride {
+                        block: index,
+                        error,
+                    },
+                    OverrideError::Database(error) => SimulationError::Database(error),
+                }
+            })?;
+        }
+
+        let mut calls = Vec::with_capacity(block.calls.len());
+        let mut gas_used = 0;
+        for (call_index, call) in block.calls.iter().enumerate() {
+            let mut block_env = env.clone();
+            let mut tx = call.clone();
+            if self.options.validation {
+                if tx.nonce.is_none() {
+                    let info = self
+                        .evm
+                        .db_mut()
+                        .basic(tx.caller)
+                        .map_err(SimulationError::Database)?;
+                    tx.nonce = Some(info.map_or(0, |info| info.nonce));
+                }
+                // same as the gas pool of the block, gas limit of the call is reserved.
+                let gas_left = env.gas_limit.saturating_sub(U256::from(gas_used));
+                if U256::from(tx.gas_limit) > gas_left {
+                    return Err(SimulationError::BlockGasLimitReached {
+                        block: index,
+                        index: call_index,
+                        gas_limit: tx.gas_limit,
+                        gas_left,
+                    });
+                }
+            } else {
+                tx.nonce = None;
+                // same as geth, zero gas price is allowed by lowering the base fee.
+                if tx.gas_price.is_zero() {
+                    block_env.basefee = U256::ZERO;
+                }
+            }
+            *self.evm.block_mut() = block_env;
+            *self.evm.tx_mut() = tx;
+
+            let result = self
+                .evm
+                .transact()
+                .map_err(|error| SimulationError::Transaction {
+                    block: index,
+                    index: call_index,
+                    error,
+                })?;
+            self.evm.db_mut().commit(result.state);
+            gas_used += result.result.gas_used();
+            calls.push(result.result);
+        }
+
+        Ok(SimulatedBlockResult {
+            block: env,
+            calls,
+            gas_used,
+        })
+    }
+}
+
+/// Error of applying the [`AccountOverride`] to the [`CacheDB`].
+enum OverrideError<E> {
+    StateOverride(StateOverrideError),
+    Database(E),
+}
+
+/// Writes the account override to the cache.
+fn apply_account_override<DB: DatabaseRef>(
+    db: &mut CacheDB<DB>,
+    address: Address,
+    account: &AccountOverride,
+) -> Result<(), OverrideError<DB::Error>> {
+    if account.state.is_some() && account.state_diff.is_some() {
+        return Err(OverrideError::StateOverride(
+            StateOverrideError::StateAndStateDiff(address),
+        ));
+    }
+
+    let db_account = db.load_account(address).map_err(OverrideError::Database)?;
+    let mut info = db_account.info.clone();
+    if let Some(balance) = account.balance {
+        info.balance = balance;
+    }
+    if let Some(nonce) = account.nonce {
+        info.nonce = nonce;
+    }
+    if let Some(code) = &account.code {
+        let code = Bytecode::new_raw(code.clone());
+        info = AccountInfo::new(info.balance, info.nonce, code.hash_slow(), code);
+    }
+    db.insert_contract(&mut info);
+    let db_account = db.load_account(address).map_err(OverrideError::Database)?;
+    db_account.info = info.without_code();
+    // overridden account exists.
+    if db_account.account_state == AccountState::NotExisting {
+        db_account.account_state = AccountState::None;
+    }
+
+    let to_storage = |slots: &crate::primitives::HashMap<B256, B256>| {
+        slots
+            .iter()
+            .map(|(slot, value)| (U256::from_be_bytes(slot.0), U256::from_be_bytes(value.0)))
+            .collect::<Vec<_>>()
+    };
+    if let Some(state) = &account.state {
+        db.replace_account_storage(address, to_storage(state).into_iter().collect())
+            .map_err(OverrideError::Database)?;
+    }
+    if let Some(state_diff) = &account.state_diff {
+        for (slot, value) in to_storage(state_diff) {
+            db.insert_account_storage(address, slot, value)
+                .map_err(OverrideError::Database)?;
+        }
+    }
+    Ok(())
+}
+
+/// Registers the handles that emit a synthetic `Transfer` log for every ether transfer.
+///
+/// Log is emitted from [`TRANSFER_LOG_ADDRESS`] with [`TRANSFER_LOG_TOPIC`], sender and
+/// receiver as topics and the value as data. It is part of the journal, so it is reverted
+/// together with the call that made the transfer.
+///
+/// Transfers of the calls, `CREATE`, `CREATE2` and `EOFCREATE` are logged. `SELFDESTRUCT` is
+/// logged as the transfer of the whole balance to the target, even if the target is the
+/// destroyed account itself.
+pub fn transfer_log_handle_register<EXT, DB: Database>(handler: &mut EvmHandler<'_, EXT, DB>) {
+    handler
+        .instruction_table
+        .update_boxed(opcode::SELFDESTRUCT, |prev, interpreter, host| {
+            let from = interpreter.contract.target_address;
+            let to = interpreter
+                .stack
+                .peek(0)
+                .map(|target| Address::from_word(B256::from(target)));
+            let value = host
+                .evm
+                .journaled_state
+                .state
+                .get(&from)
+                .map_or(U256::ZERO, |account| account.info.balance);
+            prev(interpreter, host);
+            if let (Ok(to), InstructionResult::SelfDestruct) = (to, interpreter.instruction_result)
+            {
+                if !value.is_zero() {
+                    host.evm.journaled_state.log(transfer_log(from, to, value));
+                }
+            }
+        });
+
+    let prev_handle = handler.execution.call.clone();
+    handler.execution.call = Arc::new(move |ctx, inputs| {
+        let transfer = inputs
+            .transfer_value()
+            .filter(|value| !value.is_zero())
+            .map(|value| (inputs.caller, inputs.target_address, value));
+        let frame_or_result = prev_handle(ctx, inputs)?;
+        let is_transferred = match &frame_or_result {
+            FrameOrResult::Frame(_) => true,
+            FrameOrResult::Result(FrameResult::Call(outcome)) => outcome.result.result.is_ok(),
+            FrameOrResult::Result(_) => false,
+        };
+        if let (Some((from, to, value)), true) = (transfer, is_transferred) {
+            ctx.evm.journaled_state.log(transfer_log(from, to, value));
+        }
+        Ok(frame_or_result)
+    });
+
+    let prev_handle = handler.execution.create.clone();
+    handler.execution.create = Arc::new(move |ctx, inputs| {
+        let transfer = (!inputs.value.is_zero()).then_some((inputs.caller, inputs.value));
+        let frame_or_result = prev_handle(ctx, inputs)?;
+        if let (Some((from, value)), FrameOrResult::Frame(Frame::Create(frame))) =
+            (transfer, &frame_or_result)
+        {
+            let log = transfer_log(from, frame.created_address, value);
+            ctx.evm.journaled_state.log(log);
+        }
+        Ok(frame_or_result)
+    });
+
+    let prev_handle = handler.execution.eofcreate.clone();
+    handler.execution.eofcreate = Arc::new(move |ctx, inputs| {
+        let transfer = (!inputs.value.is_zero()).then_some((inputs.caller, inputs.value));
+        let frame_or_result = prev_handle(ctx, inputs)?;
+        if let (Some((from, value)), FrameOrResult::Frame(Frame::EOFCreate(frame))) =
+            (transfer, &frame_or_result)
+        {
+            let log = transfer_log(from, frame.created_address, value);
+            ctx.evm.journaled_state.log(log);
+        }
+        Ok(frame_or_result)
+    });
+}
+
+/// Creates the synthetic transfer log.
+fn transfer_log(from: Address, to: Address, value: U256) -> Log {
+    Log {
+        address: TRANSFER_LOG_ADDRESS,
+        data: LogData::new_unchecked(
+            vec![TRANSFER_LOG_TOPIC, from.into_word(), to.into_word()],
+            value.to_be_bytes_vec().into(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::EmptyDB,
+        primitives::{hex, keccak256, Bytes, TransactTo},
+    };
+
+    #[test]
+    fn transfer_log_topic() {
+        assert_eq!(
+            keccak256("Transfer(address,address,uint256)"),
+            TRANSFER_LOG_TOPIC
+        );
+    }
+
+    #[test]
+    fn simulate_blocks() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let receiver = address!("1000000000000000000000000000000000000002");
+        let contract = address!("1000000000000000000000000000000000000003");
+
+        let db = CacheDB::new(EmptyDB::default());
+        let evm = Evm::builder()
+            .with_db(CacheDB::new(&db))
+            .modify_block_env(|block| block.basefee = U256::from(10))
+            .build();
+        let mut simulator = Simulator::new(
+            evm,
+            SimulationOptions {
+                validation: false,
+                trace_transfers: true,
+            },
+        );
+
+        let transfer = TxEnv {
+            caller,
+            transact_to: TransactTo::Call(receiver),
+            value: U256::from(100),
+            ..Default::default()
+        };
+        // stores NUMBER into slot 0.
+        let code = Bytes::from_static(&hex!("435f5500"));
+        let blocks = [
+            SimulatedBlock {
+                state_overrides: StateOverride::from_iter([(
+                    caller,
+                    AccountOverride {
+                        balance: Some(U256::from(150)),
+                        ..Default::default()
+                    },
+                )]),
+                calls: vec![transfer.clone()],
+                ..Default::default()
+            },
+            SimulatedBlock {
+                block_overrides: BlockOverrides {
+                    number: Some(U256::from(10)),
+                    ..Default::default()
+                },
+                state_overrides: StateOverride::from_iter([(
+                    contract,
+                    AccountOverride {
+                        code: Some(code),
+                        ..Default::default()
+                    },
+                )]),
+                calls: vec![TxEnv {
+                    caller: receiver,
+                    transact_to: TransactTo::Call(contract),
+                    ..Default::default()
+                }],
+            },
+        ];
+
+        let results = simulator.simulate(&blocks).unwrap();
+        assert_eq!(results[0].block.number, U256::from(1));
+        assert_eq!(results[0].block.timestamp, U256::from(13));
+        assert_eq!(results[0].gas_used, 21_000);
+        assert_eq!(
+            results[0].calls[0].logs(),
+            [transfer_log(caller, receiver, U256::from(100))]
+        );
+        assert_eq!(results[1].block.number, U256::from(10));
+        assert!(results[1].calls[0].is_success());
+        assert!(matches!(
+            simulator.simulate(&blocks[1..]),
+            Err(SimulationError::InvalidBlockNumber { .. })
+        ));
+        // caller has only 50 left after the first block.
+        let block = SimulatedBlock {
+            calls: vec![transfer],
+            ..Default::default()
+        };
+        assert!(matches!(
+            simulator.simulate(&[block]),
+            Err(SimulationError::Transaction {
+                block: 0,
+                index: 0,
+                ..
+            })
+        ));
+
+        let evm = simulator.into_evm();
+        assert_eq!(
+            evm.db().storage_ref(contract, U256::ZERO),
+            Ok(U256::from(10))
+        );
+        // wrapped database is not changed.
+        assert!(db.accounts.is_empty());
+    }
+
+    #[test]
+    fn simulate_with_validation() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let receiver = address!("1000000000000000000000000000000000000002");
+
+        let evm = Evm::builder()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .modify_block_env(|block| block.gas_limit = U256::from(50_000))
+            .build();
+        let mut simulator = Simulator::new(
+            evm,
+            SimulationOptions {
+                validation: true,
+                trace_transfers: false,
+            },
+        );
+
+        let call = TxEnv {
+            caller,
+            gas_limit: 30_000,
+            transact_to: TransactTo::Call(receiver),
+            ..Default::default()
+        };
+        let state_overrides = StateOverride::from_iter([(
+            caller,
+            AccountOverride {
+                nonce: Some(5),
+                ..Default::default()
+            },
+        )]);
+        // nonce of the call is taken from the caller.
+        let block = SimulatedBlock {
+            state_overrides: state_overrides.clone(),
+            calls: vec![call.clone()],
+            ..Default::default()
+        };
+        let results = simulator.simulate(&[block]).unwrap();
+        assert!(results[0].calls[0].is_success());
+        assert_eq!(
+            simulator
+                .evm()
+                .db()
+                .basic_ref(caller)
+                .unwrap()
+                .unwrap()
+                .nonce,
+            6
+        );
+
+        // second call does not fit into the gas left in the block.
+        let parent = simulator.evm().block().clone();
+        let block = SimulatedBlock {
+            calls: vec![call.clone(), call.clone()],
+            ..Default::default()
+        };
+        assert_eq!(
+            simulator.simulate(&[block]),
+            Err(SimulationError::BlockGasLimitReached {
+                block: 0,
+                index: 1,
+                gas_limit: 30_000,
+                gas_left: U256::from(29_000),
+            })
+        );
+        // block environment of the failed block is discarded.
+        assert_eq!(simulator.evm().block(), &parent);
+
+        let timestamp = parent.timestamp;
+        let block = SimulatedBlock {
+            block_overrides: BlockOverrides {
+                time: Some(timestamp),
+                ..Default::default()
+            },
+            calls: vec![call],
+            ..Default::default()
+        };
+        assert!(matches!(
+            simulator.simulate(&[block]),
+            Err(SimulationError::InvalidTimestamp { block: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn selfdestruct_transfer_log() {
+        let caller = address!("1000000000000000000000000000000000000001");
+        let receiver = address!("1000000000000000000000000000000000000002");
+        let contract = address!("1000000000000000000000000000000000000003");
+
+        let evm = Evm::builder()
+            .with_db(CacheDB::new(EmptyDB::default()))
+            .build();
+        let mut simulator = Simulator::new(
+            evm,
+            SimulationOptions {
+                validation: false,
+                trace_transfers: true,
+            },
+        );
+
+        // SELFDESTRUCT to the receiver.
+        let mut code = vec![0x73];
+        code.extend_from_slice(receiver.as_slice());
+        code.push(0xff);
+        let block = SimulatedBlock {
+            state_overrides: StateOverride::from_iter([(
+                contract,
+                AccountOverride {
+                    balance: Some(U256::from(100)),
+                    code: Some(code.into()),
+                    ..Default::default()
+                },
+            )]),
+            calls: vec![TxEnv {
+                caller,
+                transact_to: TransactTo::Call(contract),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let results = simulator.simulate(&[block]).unwrap();
+        assert_eq!(
+            results[0].calls[0].logs(),
+            [transfer_log(contract, receiver, U256::from(100))]
+        );
+    }
+}
@@ -1,11 +1,11 @@
+use alloy_eips::BlockId;
 use alloy_provider::{network::Ethereum, ProviderBuilder, RootProvider};
-use alloy_rpc_types::BlockId;
 use alloy_sol_types::{sol, SolCall, SolValue};
 use alloy_transport_http::Http;
 use anyhow::{anyhow, Result};
 use reqwest::Client;
 use revm::{
-    db::{AlloyDB, CacheDB},
+    db::{AlloyDB, AlloyTransport, CacheDB, WrapDatabaseAsync},
     primitives::{
         address, keccak256, AccountInfo, Address, Bytes, ExecutionResult, Output, TransactTo, U256,
     },
@@ -14,7 +14,11 @@ use revm::{
 use std::ops::Div;
 use std::sync::Arc;
 
-type AlloyCacheDB = CacheDB<AlloyDB<Http<Client>, Ethereum, Arc<RootProvider<Http<Client>>>>>;
+type AlloyCacheDB = CacheDB<
+    WrapDatabaseAsync<
+        AlloyDB<AlloyTransport<Http<Client>, Ethereum, Arc<RootProvider<Http<Client>>>>>,
+    >,
+>;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -24,7 +28,8 @@ async fn main() -> Result<()> {
             .unwrap(),
     );
     let client = Arc::new(client);
-    let mut cache_db = CacheDB::new(AlloyDB::new(client, BlockId::default()));
+    let alloy_db = WrapDatabaseAsync::new(AlloyDB::new(client, BlockId::default())).unwrap();
+    let mut cache_db = CacheDB::new(alloy_db);
 
     // Random empty account
     let account = address!("18B06aaF27d44B756FCF16Ca20C1f183EB49111f");